@@ -0,0 +1,51 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+
+use crate::csv_value::parse_unit_enum;
+
+/// Generates `ArrowValue` implementation for the unit-only enum. Values are
+/// exported as dictionary of the variant names.
+pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let item = parse_unit_enum(input, "ArrowValue")?;
+
+    let ident = &item.ident;
+    let variants = item.variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
+    let names = variants
+        .iter()
+        .map(|v| Literal::string(v.to_string().as_str()))
+        .collect::<Vec<_>>();
+
+    Ok(quote! {
+        impl ArrowValue for #ident {
+            const TYPE: ArrowType = ArrowType::Dictionary;
+
+            fn to_arrow(&self) -> ArrowScalar {
+                match self {
+                    #(Self::#variants => ArrowScalar::Dictionary(#names),)*
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use crate::arrow_value::expand;
+
+    #[test]
+    fn test() {
+        let input = quote! {
+            pub enum SomeEnum {
+                First,
+                Second,
+            }
+        };
+
+        let res = expand(input).unwrap();
+        assert!(res
+            .to_string()
+            .contains("Self :: Second => ArrowScalar :: Dictionary (\"Second\")"));
+    }
+}
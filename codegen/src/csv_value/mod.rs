@@ -0,0 +1,98 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Fields, ItemEnum};
+
+/// Parses enum that `derive` is applied to. Only enums with unit variants
+/// are allowed.
+pub fn parse_unit_enum(input: TokenStream, derive: &str) -> syn::Result<ItemEnum> {
+    let item = match syn::parse2::<ItemEnum>(input.clone()) {
+        Ok(item) => item,
+        Err(err) => return Err(syn::Error::new(input.span(), err.to_string())),
+    };
+    if let Some(variant) = item
+        .variants
+        .iter()
+        .find(|v| !matches!(v.fields, Fields::Unit))
+    {
+        return Err(syn::Error::new(
+            variant.span(),
+            format!(
+                "{} can be derived only for enums with unit variants",
+                derive
+            ),
+        ));
+    }
+
+    Ok(item)
+}
+
+/// Generates `CsvValue` implementation for the unit-only enum. Values are
+/// written as variant names.
+pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let item = parse_unit_enum(input, "CsvValue")?;
+
+    let ident = &item.ident;
+    let variants = item.variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
+    let names = variants
+        .iter()
+        .map(|v| Literal::string(v.to_string().as_str()))
+        .collect::<Vec<_>>();
+    let expected = Literal::string(
+        item.variants
+            .iter()
+            .map(|v| v.ident.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+            .as_str(),
+    );
+
+    Ok(quote! {
+        impl CsvValue for #ident {
+            fn to_csv(&self) -> String {
+                match self {
+                    #(Self::#variants => #names,)*
+                }
+                .to_string()
+            }
+
+            fn from_csv(value: &str) -> core::result::Result<Self, String> {
+                match value {
+                    #(#names => core::result::Result::Ok(Self::#variants),)*
+                    _ => core::result::Result::Err(format!("unknown variant {}, expected one of ({})", value, #expected)),
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use crate::csv_value::expand;
+
+    #[test]
+    fn test() {
+        let input = quote! {
+            pub enum SomeEnum {
+                First,
+                Second,
+            }
+        };
+
+        let res = expand(input).unwrap();
+        assert!(res.to_string().contains("\"Second\" =>"));
+    }
+
+    #[test]
+    fn test_not_unit_variant() {
+        let input = quote! {
+            pub enum SomeEnum {
+                First(u64),
+            }
+        };
+
+        assert!(expand(input).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+mod arrow_value;
+mod csv_value;
 mod persist_index;
 mod persist_table;
 mod worktable;
@@ -26,3 +28,17 @@ pub fn persist_table(input: TokenStream) -> TokenStream {
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
+
+#[proc_macro_derive(CsvValue)]
+pub fn csv_value(input: TokenStream) -> TokenStream {
+    csv_value::expand(input.into())
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(ArrowValue)]
+pub fn arrow_value(input: TokenStream) -> TokenStream {
+    arrow_value::expand(input.into())
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
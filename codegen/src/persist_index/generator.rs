@@ -112,6 +112,52 @@ impl Generator {
             })
             .collect::<Vec<_>>();
 
+        let set_layout_logic = self
+            .struct_def
+            .fields
+            .iter()
+            .map(|f| {
+                (
+                    Literal::string(f.ident.as_ref().unwrap().to_string().as_str()),
+                    f.ident.as_ref().unwrap(),
+                )
+            })
+            .map(|(l, i)| {
+                quote! {
+                    layout.set_secondary_index(#l, &self.#i);
+                }
+            })
+            .collect::<Vec<_>>();
+        let retain_changed_logic = self
+            .struct_def
+            .fields
+            .iter()
+            .map(|f| {
+                (
+                    Literal::string(f.ident.as_ref().unwrap().to_string().as_str()),
+                    f.ident.as_ref().unwrap(),
+                )
+            })
+            .map(|(l, i)| {
+                quote! {
+                    self.#i = layout.place_secondary_index(#l, std::mem::take(&mut self.#i));
+                }
+            })
+            .collect::<Vec<_>>();
+        let persist_changed_logic = self
+            .struct_def
+            .fields
+            .iter()
+            .map(|f| f.ident.as_ref().unwrap())
+            .map(|i| {
+                quote! {
+                    for mut page in &mut self.#i {
+                        journal.write_page(&mut page)?;
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
         let parse_from_file = self.gen_parse_from_file()?;
         Ok(quote! {
             impl #name_ident {
@@ -137,6 +183,20 @@ impl Generator {
                     Ok(())
                 }
 
+                pub fn set_layout(&self, layout: &mut SpaceLayout) {
+                    #(#set_layout_logic)*
+                }
+
+                pub fn retain_changed(&mut self, layout: &mut SpaceLayout) {
+                    #(#retain_changed_logic)*
+                }
+
+                pub fn persist_changed(&mut self, journal: &mut PageJournal) -> eyre::Result<()> {
+                    #(#persist_changed_logic)*
+
+                    Ok(())
+                }
+
                 #parse_from_file
             }
         })
@@ -157,12 +217,12 @@ impl Generator {
                 let mut #i = vec![];
                 let intervals = map.get(#l).expect("exists");
                 for interval in intervals {
-                    for page_id in interval.0..interval.1 {
-                        let index = parse_page::<IndexData<_>, { #page_const_name as u32 }>(file, page_id as u32)?;
-                        #i.push(index);
+                    for page_id in interval.0..=interval.1 {
+                        if verifier.check(file, page_id as u32, PageType::Index)? {
+                            let index = parse_page::<IndexData<_>, { #page_const_name as u32 }>(file, page_id as u32)?;
+                            #i.push(index);
+                        }
                     }
-                    let index = parse_page::<IndexData<_>, { #page_const_name as u32 }>(file, interval.1 as u32)?;
-                    #i.push(index);
                 }
             })
             .collect();
@@ -174,7 +234,11 @@ impl Generator {
             .collect::<Vec<_>>();
 
         Ok(quote! {
-            pub fn parse_from_file(file: &mut std::fs::File, map: &std::collections::HashMap<String, Vec<Interval>>) -> eyre::Result<Self> {
+            pub fn parse_from_file(
+                file: &mut std::fs::File,
+                map: &std::collections::HashMap<String, Vec<Interval>>,
+                verifier: &mut PageVerifier,
+            ) -> eyre::Result<Self> {
                 #(#field_names_lits)*
 
                 Ok(Self {
@@ -269,6 +333,50 @@ impl Generator {
         })
     }
 
+    /// Generates `entries` method that returns contents of the indexes for
+    /// the `.wt` files inspection.
+    pub fn gen_entries_impl(&self) -> syn::Result<TokenStream> {
+        let ident = &self.struct_def.ident;
+        let entries = self
+            .struct_def
+            .fields
+            .iter()
+            .map(|f| {
+                let i = f.ident.as_ref().unwrap();
+                let name = Literal::string(i.to_string().as_str());
+                let is_unique = !f
+                    .ty
+                    .to_token_stream()
+                    .to_string()
+                    .to_lowercase()
+                    .contains("lockfree");
+                let links = if is_unique {
+                    quote! { vec![*value] }
+                } else {
+                    quote! { value.iter().map(|l| *l.as_ref()).collect() }
+                };
+                quote! {
+                    IndexEntries {
+                        name: #name.to_string(),
+                        entries: TableIndex::iter(&self.#i)
+                            .map(|(key, value)| (format!("{:?}", key), #links))
+                            .collect(),
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(quote! {
+            impl #ident {
+                /// Returns entries of the indexes with keys formatted by
+                /// `Debug`.
+                pub fn entries(&self) -> Vec<IndexEntries> {
+                    vec![#(#entries)*]
+                }
+            }
+        })
+    }
+
     fn gen_from_persisted_fn(&self) -> syn::Result<TokenStream> {
         let idents = self
             .struct_def
@@ -14,10 +14,12 @@ pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
     let type_def = gen.gen_persist_type()?;
     let persistable_def = gen.gen_persistable_impl()?;
     let impl_def = gen.gen_persist_impl()?;
+    let entries_def = gen.gen_entries_impl()?;
 
     Ok(quote! {
         #type_def
         #impl_def
+        #entries_def
 
         #persistable_def
     })
@@ -0,0 +1,85 @@
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, TokenStream};
+use quote::__private::Span;
+use quote::quote;
+
+use crate::persist_table::generator::Generator;
+
+impl Generator {
+    pub fn gen_inspect_impl(&self) -> syn::Result<TokenStream> {
+        let wt_ident = &self.struct_def.ident;
+        let name = self.struct_def.ident.to_string().replace("WorkTable", "");
+        let name_underscore = name.from_case(Case::Pascal).to_case(Case::Snake);
+        let pk_type = &self.pk_ident;
+        let row_ident = Ident::new(format!("{}Row", name).as_str(), Span::mixed_site());
+        let page_const_name = Ident::new(
+            format!("{}_PAGE_SIZE", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let inner_const_name = Ident::new(
+            format!("{}_INNER_SIZE", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+
+        Ok(quote! {
+            impl InspectSpace for #wt_ident {
+                type Row = #row_ident;
+
+                const FILE_NAME: &'static str = #name_underscore;
+
+                fn inspect(file: &mut std::fs::File) -> eyre::Result<SpaceReport> {
+                    let mut report = SpaceReport::read::<{ #page_const_name }, { #inner_const_name }>(file)?;
+                    let info = match parse_page::<SpaceInfoData<<<#pk_type as TablePrimaryKey>::Generator as PrimaryKeyGeneratorState>::State>, { #page_const_name as u32 }>(file, 0) {
+                        Ok(info) => info,
+                        Err(e) => {
+                            report.problems.push(format!("space info page can't be parsed: {}", e));
+                            return Ok(report);
+                        }
+                    };
+                    report.info = Some(format!("{:?}", info.inner));
+                    report.empty_links = info.inner.empty_links_list.clone();
+                    report.set_sections(SpaceSections {
+                        primary_key: info.inner.primary_key_intervals.iter().map(|i| (i.0, i.1)).collect(),
+                        secondary_indexes: info.inner.secondary_index_intervals
+                            .iter()
+                            .map(|(name, intervals)| (name.clone(), intervals.iter().map(|i| (i.0, i.1)).collect()))
+                            .collect(),
+                        data: info.inner.data_intervals.iter().map(|i| (i.0, i.1)).collect(),
+                    });
+
+                    // Indexes are read from the table, so their contents are
+                    // shown only if the whole file is valid.
+                    match Self::read_table(file) {
+                        Ok(table) => {
+                            let primary_key = IndexEntries {
+                                name: "primary key".to_string(),
+                                entries: TableIndex::iter(&table.0.pk_map)
+                                    .map(|(key, link)| (format!("{:?}", key), vec![*link]))
+                                    .collect(),
+                            };
+                            report.indexes = std::iter::once(primary_key)
+                                .chain(table.0.indexes.entries())
+                                .collect();
+                        }
+                        Err(e) => report.problems.push(format!("table can't be read from the file: {}", e)),
+                    }
+
+                    Ok(report)
+                }
+
+                fn read_rows(file: &mut std::fs::File) -> eyre::Result<Vec<Self::Row>> {
+                    Ok(Self::read_table(file)?.select_all().execute()?)
+                }
+            }
+
+            impl #wt_ident {
+                /// Reads table from the file without replaying its log.
+                fn read_table(file: &mut std::fs::File) -> eyre::Result<Self> {
+                    let manager = std::sync::Arc::new(DatabaseManager::new(String::new(), String::new()));
+                    let version = Self::file_schema_version(file)?;
+                    Self::from_file_version(manager, file, version)
+                }
+            }
+        })
+    }
+}
@@ -1,6 +1,7 @@
 use proc_macro2::Ident;
 use syn::ItemStruct;
 
+mod inspect;
 mod size_measurable;
 mod space_deserialize;
 mod space_serialize;
@@ -24,11 +24,25 @@ impl Generator {
         let wt_ident = &self.struct_def.ident;
         let name = self.struct_def.ident.to_string().replace("WorkTable", "");
         let index_ident = Ident::new(format!("{}Index", name).as_str(), Span::mixed_site());
+        let lock_timeout_const_name = Ident::new(
+            format!("{}_LOCK_TIMEOUT", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let row_ident = Ident::new(format!("{}Row", name).as_str(), Span::mixed_site());
+        let page_const_name = Ident::new(
+            format!("{}_PAGE_SIZE", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let inner_const_name = Ident::new(
+            format!("{}_INNER_SIZE", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
 
         Ok(quote! {
-            pub fn into_worktable(self, db_manager: std::sync::Arc<DatabaseManager>) -> #wt_ident {
+            pub fn into_worktable(mut self, db_manager: std::sync::Arc<DatabaseManager>) -> #wt_ident {
+                let layout = self.layout();
                 let mut page_id = 0;
-                let data = self.data.into_iter().map(|p| {
+                let data = std::mem::take(&mut self.data).into_iter().map(|p| {
                     let mut data = Data::from_data_page(p);
                     data.set_page_id(page_id.into());
                     page_id += 1;
@@ -36,8 +50,39 @@ impl Generator {
                     std::sync::Arc::new(data)
                 })
                     .collect();
-                let data = DataPages::from_data(data)
-                    .with_empty_links(self.info.inner.empty_links_list);
+                self.into_worktable_with_data(db_manager, DataPages::from_data(data), layout)
+            }
+
+            /// Creates table whose data pages are read from the `source` on
+            /// first access. At most `cache_pages` of the read pages are kept
+            /// in memory. Space must be parsed without data pages.
+            pub fn into_lazy_worktable(
+                self,
+                db_manager: std::sync::Arc<DatabaseManager>,
+                source: FilePageSource<#page_const_name, #inner_const_name>,
+                cache_pages: usize,
+            ) -> #wt_ident {
+                let mut layout = self.layout();
+                layout.set_data_pages(source.page_ids().to_vec());
+                let count = source.page_ids().len();
+                let data = DataPages::<#row_ident, #inner_const_name>::lazy(count, std::sync::Arc::new(source), cache_pages);
+                self.into_worktable_with_data(db_manager, data, layout)
+            }
+
+            fn into_worktable_with_data(
+                self,
+                db_manager: std::sync::Arc<DatabaseManager>,
+                data: DataPages<#row_ident, #inner_const_name>,
+                layout: SpaceLayout,
+            ) -> #wt_ident {
+                // Table is persisted to the file it's loaded from only if
+                // paths are same, so only then changed pages can be written.
+                let space_layout = if db_manager.config_path == db_manager.database_files_dir {
+                    Some(layout)
+                } else {
+                    None
+                };
+                let data = data.with_empty_links(self.info.inner.empty_links_list);
                 let indexes = #index_ident::from_persisted(self.indexes);
 
                 let pk_map = TreeIndex::new();
@@ -52,6 +97,10 @@ impl Generator {
                     pk_gen: PrimaryKeyGeneratorState::from_state(self.info.inner.pk_gen_state),
                     lock_map: LockMap::new(),
                     table_name: "",
+                    lock_timeout: #lock_timeout_const_name,
+                    versions: Default::default(),
+                    wal: None,
+                    space_layout: std::sync::Mutex::new(space_layout),
                     pk_phantom: std::marker::PhantomData
                 };
 
@@ -81,26 +130,89 @@ impl Generator {
 
         Ok(quote! {
             pub fn parse_file(file: &mut std::fs::File) -> eyre::Result<Self> {
+                let mut verifier = PageVerifier::new(file, #page_const_name, false)?;
+                Self::parse_verified_file(file, &mut verifier, true, true)
+            }
+
+            /// Parses file without data pages, leaving data empty. Data pages
+            /// are placed to the pages of the info's data intervals.
+            pub fn parse_file_without_data(file: &mut std::fs::File) -> eyre::Result<Self> {
+                let mut verifier = PageVerifier::new(file, #page_const_name, false)?;
+                Self::parse_verified_file(file, &mut verifier, true, false)
+            }
+
+            /// Parses file without secondary index pages, leaving indexes
+            /// empty. Used for files written with the older schema, as keys of
+            /// their indexes could have other types.
+            pub fn parse_file_without_indexes(file: &mut std::fs::File) -> eyre::Result<Self> {
+                let mut verifier = PageVerifier::new(file, #page_const_name, false)?;
+                Self::parse_verified_file(file, &mut verifier, false, true)
+            }
+
+            /// Parses file skipping pages that don't match their checksums.
+            /// Skipped data pages are replaced by empty ones, so links to the
+            /// other pages stay valid. Returns skipped pages.
+            pub fn parse_file_skipping_corrupted(file: &mut std::fs::File) -> eyre::Result<(Self, Vec<CorruptedPage>)> {
+                let mut verifier = PageVerifier::new(file, #page_const_name, true)?;
+                let space = Self::parse_verified_file(file, &mut verifier, true, true)?;
+                Ok((space, verifier.into_corrupted()))
+            }
+
+            fn parse_verified_file(
+                file: &mut std::fs::File,
+                verifier: &mut PageVerifier,
+                with_indexes: bool,
+                with_data: bool,
+            ) -> eyre::Result<Self> {
+                if !verifier.check(file, 0, PageType::SpaceInfo)? {
+                    eyre::bail!("space info page is corrupted, so table's pages can't be found")
+                }
                 let info = parse_page::<SpaceInfoData<<<#pk_type as TablePrimaryKey>::Generator as PrimaryKeyGeneratorState>::State>, { #page_const_name as u32 }>(file, 0)?;
+                let schema = SpaceSchema::read(file, #page_const_name)?.unwrap_or_default();
 
                 let mut primary_index = vec![];
                 for interval in &info.inner.primary_key_intervals {
-                    for page_id in interval.0..interval.1 {
-                        let index = parse_page::<IndexData<#pk_type>, { #page_const_name as u32 }>(file, page_id as u32)?;
-                        primary_index.push(index);
+                    for page_id in interval.0..=interval.1 {
+                        if verifier.check(file, page_id as u32, PageType::Index)? {
+                            let index = parse_page::<IndexData<#pk_type>, { #page_const_name as u32 }>(file, page_id as u32)?;
+                            primary_index.push(index);
+                        }
                     }
-                    let index = parse_page::<IndexData<#pk_type>, { #page_const_name as u32 }>(file, interval.1 as u32)?;
-                    primary_index.push(index);
                 }
-                let indexes = #persisted_index_name::parse_from_file(file, &info.inner.secondary_index_intervals)?;
+                let indexes = if with_indexes {
+                    #persisted_index_name::parse_from_file(file, &info.inner.secondary_index_intervals, verifier)?
+                } else {
+                    #persisted_index_name::default()
+                };
                 let mut data = vec![];
-                for interval in &info.inner.data_intervals {
-                    for page_id in interval.0..interval.1 {
-                        let index = parse_data_page::<{ #page_const_name }, { #inner_const_name }>(file, page_id as u32)?;
-                        data.push(index);
+                let data_intervals = if with_data {
+                    info.inner.data_intervals.as_slice()
+                } else {
+                    &[]
+                };
+                for interval in data_intervals {
+                    for page_id in interval.0..=interval.1 {
+                        let page = if verifier.check(file, page_id as u32, PageType::Data)? {
+                            parse_data_page::<{ #page_const_name }, { #inner_const_name }>(file, page_id as u32)?
+                        } else {
+                            GeneralPage {
+                                header: GeneralHeader {
+                                    data_version: DATA_VERSION,
+                                    page_id: (page_id as u32).into(),
+                                    previous_id: 0.into(),
+                                    next_id: 0.into(),
+                                    page_type: PageType::Data,
+                                    space_id: 0.into(),
+                                    data_length: 0,
+                                },
+                                inner: DataPage {
+                                    data: [0; #inner_const_name],
+                                    length: 0,
+                                },
+                            }
+                        };
+                        data.push(page);
                     }
-                    let index = parse_data_page::<{ #page_const_name }, { #inner_const_name }>(file, interval.1 as u32)?;
-                    data.push(index);
                 }
 
                 Ok(Self {
@@ -108,7 +220,8 @@ impl Generator {
                     info,
                     primary_index,
                     indexes,
-                    data
+                    data,
+                    schema,
                 })
             }
         })
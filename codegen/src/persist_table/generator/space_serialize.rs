@@ -25,6 +25,7 @@ impl Generator {
                 pub path: String,
 
                 pub info: GeneralPage<SpaceInfoData>,
+                pub schema: SpaceSchema,
                 pub primary_index: Vec<GeneralPage<IndexData<#pk_type>>>,
                 pub indexes: #index_persisted_ident,
                 pub data: Vec<GeneralPage<DataPage<DATA_LENGTH>>>,
@@ -39,10 +40,20 @@ impl Generator {
         let into_space = self.gen_into_space()?;
 
         let persist_fn = self.gen_persist_fn()?;
+        let changed_space_fn = self.gen_changed_space_fn()?;
         let from_file_fn = self.gen_from_file_fn()?;
 
         let space_persist = self.gen_space_persist_fn()?;
 
+        let name = self.struct_def.ident.to_string().replace("WorkTable", "");
+        let file_name = Literal::string(
+            format!("{}.wt", name.from_case(Case::Pascal).to_case(Case::Snake)).as_str(),
+        );
+        let schema_const_name = Ident::new(
+            format!("{}_SCHEMA_VERSION", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+
         Ok(quote! {
             impl #ident {
                 #space_info_fn
@@ -50,38 +61,380 @@ impl Generator {
                 #into_space
 
                 #persist_fn
+                #changed_space_fn
                 #from_file_fn
             }
 
+            impl Checkpointable for #ident {
+                fn table_name(&self) -> &str {
+                    self.0.table_name
+                }
+
+                fn writes_count(&self) -> u64 {
+                    self.0.versions.writes_count()
+                }
+
+                fn checkpoint(&self) -> eyre::Result<()> {
+                    self.persist()
+                }
+            }
+
+            impl Backupable for #ident {
+                fn file_name(&self) -> &str {
+                    #file_name
+                }
+
+                fn schema_version(&self) -> u32 {
+                    #schema_const_name
+                }
+
+                fn pause_writes(&self) -> WritesPause<'_> {
+                    self.0.versions.pause_writes()
+                }
+
+                fn capture(&self, dir: &str) -> eyre::Result<Box<dyn CapturedSpace>> {
+                    let mut space = self.into_space()?;
+                    space.path = dir.to_string();
+                    Ok(Box::new(space))
+                }
+            }
+
+            impl Restorable for #ident {
+                const FILE_NAME: &'static str = #file_name;
+
+                fn load(manager: std::sync::Arc<DatabaseManager>) -> eyre::Result<Self> {
+                    Self::load_from_file(manager)
+                }
+            }
+
             #space_persist
         })
     }
 
     fn gen_persist_fn(&self) -> syn::Result<TokenStream> {
+        let name = self.struct_def.ident.to_string().replace("WorkTable", "");
+        let file_name = Literal::string(
+            format!("{}.wt", name.from_case(Case::Pascal).to_case(Case::Snake)).as_str(),
+        );
+        let page_const_name = Ident::new(
+            format!("{}_PAGE_SIZE", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let inner_const_name = Ident::new(
+            format!("{}_INNER_SIZE", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+
         Ok(quote! {
             pub fn persist(&self) -> eyre::Result<()> {
-                let mut space = self.into_space();
-                space.persist()?;
+                let mut layout = self.0.space_layout.lock().unwrap();
+                let path = format!("{}/{}", self.1.config_path.as_str(), #file_name);
+                // Log is still needed if state is persisted not in the
+                // directory it's loaded from.
+                let wal = self
+                    .0
+                    .wal
+                    .as_ref()
+                    .filter(|_| self.1.config_path == self.1.database_files_dir);
+                let (mut space, placed) = {
+                    // Writes are paused only while state is captured, so they
+                    // are not blocked while it's written to the file. Changes
+                    // made after capture are logged to the new log.
+                    let _pause = self.0.versions.pause_writes();
+                    if let Some(wal) = wal {
+                        wal.rotate()?;
+                    }
+                    match layout.take() {
+                        Some(mut layout) if std::path::Path::new(path.as_str()).exists() => {
+                            (self.changed_space(&mut layout), Some(layout))
+                        }
+                        _ => {
+                            self.0.data.clear_dirty();
+                            (self.into_space()?, None)
+                        }
+                    }
+                };
+                // Layout is left unset if persist fails, so next persist
+                // rewrites the whole file.
+                let persisted = match placed {
+                    Some(layout) => {
+                        space.persist_changed()?;
+                        layout
+                    }
+                    None => {
+                        space.persist()?;
+                        space.layout()
+                    }
+                };
+                if self.0.data.is_lazy() {
+                    // Pages are placed to the other file pages when the whole
+                    // file is written, so pages that are not in memory are
+                    // read from the new file.
+                    let source = FilePageSource::<#page_const_name, #inner_const_name>::open(
+                        path.as_str(),
+                        persisted.data_pages().to_vec(),
+                    )?;
+                    self.0.data.set_source(std::sync::Arc::new(source));
+                }
+                *layout = Some(persisted);
+                if let Some(wal) = wal {
+                    wal.retire_rotated()?;
+                }
                 Ok(())
             }
         })
     }
 
+    fn gen_changed_space_fn(&self) -> syn::Result<TokenStream> {
+        let ident = &self.struct_def.ident;
+        let name = self.struct_def.ident.to_string().replace("WorkTable", "");
+        let const_name = Ident::new(
+            format!("{}_INNER_SIZE", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let space_ident = Ident::new(format!("{}Space", name).as_str(), Span::mixed_site());
+        let schema_const_name = Ident::new(
+            format!("{}_SCHEMA_VERSION", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let columns_const_name = Ident::new(
+            format!("{}_SCHEMA_COLUMNS", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+
+        Ok(quote! {
+            /// Returns space with only pages changed since the last persist,
+            /// placed to the file's pages by the `layout`.
+            fn changed_space(&self, layout: &mut SpaceLayout) -> #space_ident<#const_name> {
+                let path = self.1.config_path.clone();
+
+                let mut info = #ident::space_info_default();
+                info.inner.pk_gen_state = self.0.pk_gen.get_state();
+                info.inner.empty_links_list = self.0.data.empty_links();
+
+                let mut primary_index = map_index_pages_to_general(
+                    self.get_peristed_primary_key(),
+                    &mut info.header
+                );
+                let previous_header = &mut primary_index
+                    .last_mut()
+                    .expect("Primary index page always exists, even if empty")
+                    .header;
+                let mut indexes = self.0.indexes.get_persisted_index(previous_header);
+                let primary_index = layout.place_primary_index(primary_index);
+                indexes.retain_changed(layout);
+                let data = layout.place_data(&self.0.data);
+
+                info.inner.page_count = layout.page_count();
+                info.inner.primary_key_intervals = layout.primary_key_intervals();
+                info.inner.secondary_index_intervals = layout.secondary_index_intervals();
+                info.inner.data_intervals = layout.data_intervals();
+
+                #space_ident {
+                    path,
+                    info,
+                    primary_index,
+                    indexes,
+                    data,
+                    schema: SpaceSchema::new(#schema_const_name, #columns_const_name[#schema_const_name as usize]),
+                }
+            }
+        })
+    }
+
     fn gen_from_file_fn(&self) -> syn::Result<TokenStream> {
         let name = self.struct_def.ident.to_string().replace("WorkTable", "");
         let space_ident = Ident::new(format!("{}Space", name).as_str(), Span::mixed_site());
         let wt_ident = self.struct_def.ident.clone();
         let name_underscore = name.from_case(Case::Pascal).to_case(Case::Snake);
+        let wal_sync_const_name = Ident::new(
+            format!("{}_WAL_SYNC", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let schema_const_name = Ident::new(
+            format!("{}_SCHEMA_VERSION", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let columns_const_name = Ident::new(
+            format!("{}_SCHEMA_COLUMNS", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let page_const_name = Ident::new(
+            format!("{}_PAGE_SIZE", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
 
         Ok(quote! {
             pub fn load_from_file(manager: std::sync::Arc<DatabaseManager>) -> eyre::Result<Self> {
-                let filename = format!("{}/{}.wt", manager.database_files_dir.as_str(), #name_underscore);
-                let filename = std::path::Path::new(filename.as_str());
-                let Ok(mut file) = std::fs::File::open(filename) else {
-                    return Ok(#wt_ident::new(manager));
+                let space_file = SpaceFile::new(format!("{}/{}.wt", manager.database_files_dir.as_str(), #name_underscore));
+                space_file.recover()?;
+                Self::check_schema(&space_file)?;
+                let loaded = space_file.load(|file| Self::from_file(manager.clone(), file))?;
+                Self::from_loaded(manager, loaded)
+            }
+
+            /// Loads table like `load_from_file`, but reads only the space
+            /// info and index pages of its file. Data pages are read from the
+            /// file on first access and at most `cache_pages` of them are kept
+            /// in memory, so table is usable before its rows are read. Pages
+            /// changed since they were read are kept until they are persisted.
+            ///
+            /// Corrupted data pages are detected only when they are accessed.
+            /// Files written with the older schema and files whose current
+            /// generation can't be read are loaded by `load_from_file`.
+            pub fn load_from_file_lazy(manager: std::sync::Arc<DatabaseManager>, cache_pages: usize) -> eyre::Result<Self> {
+                let path = format!("{}/{}.wt", manager.database_files_dir.as_str(), #name_underscore);
+                let space_file = SpaceFile::new(path.as_str());
+                space_file.recover()?;
+                Self::check_schema(&space_file)?;
+                let Ok(mut file) = std::fs::File::open(space_file.path()) else {
+                    return Self::load_from_file(manager);
+                };
+                match Self::from_file_lazy(manager.clone(), &mut file, path.as_str(), cache_pages) {
+                    Ok(table) => Self::from_loaded(manager, Some((table, FileGeneration::Current))),
+                    Err(_) => Self::load_from_file(manager),
+                }
+            }
+
+            /// Creates table from the file without reading its data pages.
+            /// Fails if the file is written with the older schema.
+            fn from_file_lazy(
+                manager: std::sync::Arc<DatabaseManager>,
+                file: &mut std::fs::File,
+                path: &str,
+                cache_pages: usize,
+            ) -> eyre::Result<Self> {
+                let version = Self::file_schema_version(file)?;
+                if version != #schema_const_name {
+                    eyre::bail!("table's file with schema version {} must be migrated", version)
+                }
+                let space = #space_ident::parse_file_without_data(file)?;
+                let page_ids = space
+                    .info
+                    .inner
+                    .data_intervals
+                    .iter()
+                    .flat_map(|interval| interval.0 as u32..=interval.1 as u32)
+                    .collect();
+                let source = FilePageSource::open(path, page_ids)?;
+                Ok(space.into_lazy_worktable(manager, source, cache_pages))
+            }
+
+            /// Loads table like `load_from_file`, but if no generation of the
+            /// table's file is valid, loads the current one skipping pages that
+            /// don't match their checksums. Rows of the skipped pages are lost.
+            /// Only files written with the current schema are loaded this way.
+            /// Returns the table and skipped pages.
+            pub fn load_from_file_skipping_corrupted(manager: std::sync::Arc<DatabaseManager>) -> eyre::Result<(Self, Vec<CorruptedPage>)> {
+                let space_file = SpaceFile::new(format!("{}/{}.wt", manager.database_files_dir.as_str(), #name_underscore));
+                space_file.recover()?;
+                Self::check_schema(&space_file)?;
+                let error = match space_file.load(|file| Self::from_file(manager.clone(), file)) {
+                    Ok(loaded) => return Ok((Self::from_loaded(manager, loaded)?, vec![])),
+                    Err(e) => e,
+                };
+                let Ok(mut file) = std::fs::File::open(space_file.path()) else {
+                    return Err(error);
                 };
-                let space = #space_ident::parse_file(&mut file)?;
-                let table = space.into_worktable(manager);
+                let schema = match SpaceSchema::read(&mut file, #page_const_name) {
+                    Ok(schema) => schema.unwrap_or_default(),
+                    Err(_) => return Err(error),
+                };
+                if schema.version != #schema_const_name {
+                    return Err(error);
+                }
+                schema.check_columns(#columns_const_name[#schema_const_name as usize])?;
+                let (mut space, corrupted) = #space_ident::parse_file_skipping_corrupted(&mut file)?;
+                let lost = CorruptedPage::lost_data_pages(&corrupted, &space.data);
+                space.info.inner.empty_links_list.retain(|l| !lost.contains(&l.page_id));
+                let mut table = space.into_worktable(manager.clone());
+                table.0.rebuild_indexes(&lost)?;
+                // Skipped pages are left in the file, so it's rewritten as a
+                // whole.
+                *table.0.space_layout.get_mut().unwrap() = None;
+                let table = Self::from_loaded(manager, Some((table, FileGeneration::Current)))?;
+                Ok((table, corrupted))
+            }
+
+            /// Fails if the current generation of the table's file is written
+            /// with the newer schema or with other columns, so it's not
+            /// replaced by the previous one.
+            fn check_schema(space_file: &SpaceFile) -> eyre::Result<()> {
+                let Ok(mut file) = std::fs::File::open(space_file.path()) else {
+                    return Ok(());
+                };
+                if let Ok(Some(schema)) = SpaceSchema::read(&mut file, #page_const_name) {
+                    if schema.version > #schema_const_name {
+                        eyre::bail!(
+                            "table's file has schema version {}, which is newer than the table's version {}",
+                            schema.version,
+                            #schema_const_name
+                        )
+                    }
+                    schema.check_columns(#columns_const_name[schema.version as usize])?;
+                }
+                Ok(())
+            }
+
+            /// Creates table from the file, converting rows written with the
+            /// older schema to the current row type.
+            fn from_file(manager: std::sync::Arc<DatabaseManager>, file: &mut std::fs::File) -> eyre::Result<Self> {
+                let version = Self::file_schema_version(file)?;
+                if version < #schema_const_name {
+                    // Logged rows have the file's schema, so they can't be
+                    // replayed after migration.
+                    let wal_path = format!("{}/{}.wal", manager.database_files_dir.as_str(), #name_underscore);
+                    if #wal_sync_const_name.is_some() && !Wal::read(wal_path.as_str())?.is_empty() {
+                        eyre::bail!("table's file with schema version {} can't be migrated until its WAL is empty", version)
+                    }
+                }
+                Self::from_file_version(manager, file, version)
+            }
+
+            /// Returns schema version of the file. Fails if it's newer than
+            /// the table's version or if the file's columns differ from the
+            /// columns of its schema version.
+            fn file_schema_version(file: &mut std::fs::File) -> eyre::Result<u32> {
+                let schema = SpaceSchema::read(file, #page_const_name)?.unwrap_or_default();
+                if schema.version > #schema_const_name {
+                    eyre::bail!(
+                        "table's file has schema version {}, which is newer than the table's version {}",
+                        schema.version,
+                        #schema_const_name
+                    )
+                }
+                schema.check_columns(#columns_const_name[schema.version as usize])?;
+                Ok(schema.version)
+            }
+
+            /// Creates table from the file written with the schema `version`.
+            fn from_file_version(
+                manager: std::sync::Arc<DatabaseManager>,
+                file: &mut std::fs::File,
+                version: u32,
+            ) -> eyre::Result<Self> {
+                if version == #schema_const_name {
+                    Ok(#space_ident::parse_file(file)?.into_worktable(manager))
+                } else {
+                    Self::migrate_from_file(manager, file, version)
+                }
+            }
+
+            /// Replays the table's log on top of the table loaded from the
+            /// `generation` of its file and opens the log.
+            fn from_loaded(
+                manager: std::sync::Arc<DatabaseManager>,
+                loaded: Option<(Self, FileGeneration)>,
+            ) -> eyre::Result<Self> {
+                let (mut table, generation) = loaded.unwrap_or_else(|| (#wt_ident::new(manager), FileGeneration::Current));
+                if let Some(policy) = #wal_sync_const_name {
+                    let wal_path = format!("{}/{}.wal", table.1.database_files_dir.as_str(), #name_underscore);
+                    if generation == FileGeneration::Previous {
+                        Wal::restore_retired(wal_path.as_str())?;
+                    }
+                    table.replay_wal(Wal::read(wal_path.as_str())?)?;
+                    table.0.wal = Some(Wal::open(wal_path, policy)?);
+                }
                 Ok(table)
             }
         })
@@ -145,14 +498,22 @@ impl Generator {
             Span::mixed_site(),
         );
         let space_ident = Ident::new(format!("{}Space", name).as_str(), Span::mixed_site());
+        let schema_const_name = Ident::new(
+            format!("{}_SCHEMA_VERSION", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let columns_const_name = Ident::new(
+            format!("{}_SCHEMA_COLUMNS", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
 
         Ok(quote! {
-            pub fn into_space(&self) -> #space_ident<#const_name> {
+            pub fn into_space(&self) -> eyre::Result<#space_ident<#const_name>> {
                 let path = self.1.config_path.clone();
 
                 let mut info = #ident::space_info_default();
                 info.inner.pk_gen_state = self.0.pk_gen.get_state();
-                info.inner.empty_links_list = self.0.data.get_empty_links();
+                info.inner.empty_links_list = self.0.data.empty_links();
                 info.inner.page_count = 1;
                 let mut header = &mut info.header;
 
@@ -187,7 +548,7 @@ impl Generator {
                     Some(previous_header) => previous_header,
                     None => previous_header,
                 };
-                let data = map_data_pages_to_general(self.0.data.get_bytes().into_iter().map(|(b, offset)| DataPage {
+                let data = map_data_pages_to_general(self.0.data.get_bytes()?.into_iter().map(|(b, offset)| DataPage {
                     data: b,
                     length: offset,
                 }).collect::<Vec<_>>(), previous_header);
@@ -207,19 +568,24 @@ impl Generator {
                 );
                 info.inner.data_intervals = vec![interval];
 
-                #space_ident {
+                Ok(#space_ident {
                     path,
                     info,
                     primary_index,
                     indexes,
                     data,
-                }
+                    schema: SpaceSchema::new(#schema_const_name, #columns_const_name[#schema_const_name as usize]),
+                })
             }
         })
     }
 
     fn gen_space_persist_fn(&self) -> syn::Result<TokenStream> {
         let name = self.struct_def.ident.to_string().replace("WorkTable", "");
+        let page_const_name = Ident::new(
+            format!("{}_PAGE_SIZE", name.to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
         let space_ident = Ident::new(format!("{}Space", name).as_str(), Span::mixed_site());
         let file_name = Literal::string(
             format!("{}.wt", name.from_case(Case::Pascal).to_case(Case::Snake)).as_str(),
@@ -228,12 +594,8 @@ impl Generator {
         Ok(quote! {
             impl<const DATA_LENGTH: usize> #space_ident<DATA_LENGTH> {
                 pub fn persist(&mut self) -> eyre::Result<()> {
-                    let file_name = #file_name;
-                    let path = std::path::Path::new(format!("{}/{}", &self.path , file_name).as_str());
-                    let prefix = &self.path;
-                    std::fs::create_dir_all(prefix).unwrap();
-
-                    let mut file = std::fs::File::create(format!("{}/{}", &self.path , file_name))?;
+                    let space_file = SpaceFile::new(format!("{}/{}", &self.path, #file_name));
+                    let mut file = space_file.create_temp()?;
                     persist_page(&mut self.info, &mut file)?;
 
                     for mut primary_index_page in &mut self.primary_index {
@@ -243,8 +605,51 @@ impl Generator {
                     for mut data_page in &mut self.data {
                         persist_page(&mut data_page, &mut file)?;
                     }
+                    self.persist_schema(&mut file)?;
+
+                    space_file.commit(file, #page_const_name)
+                }
+
+                /// Writes space's pages to the journal, then to their places
+                /// in the existing file.
+                pub fn persist_changed(&mut self) -> eyre::Result<()> {
+                    let space_file = SpaceFile::new(format!("{}/{}", &self.path, #file_name));
+                    let mut journal = space_file.begin_journal(#page_const_name)?;
+
+                    journal.write_page(&mut self.info)?;
+                    let info_length = GENERAL_HEADER_SIZE + self.info.header.data_length as usize;
+                    journal.write_schema(&self.schema, info_length)?;
+                    for mut primary_index_page in &mut self.primary_index {
+                        journal.write_page(&mut primary_index_page)?;
+                    }
+                    self.indexes.persist_changed(&mut journal)?;
+                    for mut data_page in &mut self.data {
+                        journal.write_page(&mut data_page)?;
+                    }
+
+                    space_file.commit_journal(journal)
+                }
+
+                /// Writes schema to the end of the already written space info
+                /// page.
+                fn persist_schema(&self, file: &mut std::fs::File) -> eyre::Result<()> {
+                    let info_length = GENERAL_HEADER_SIZE + self.info.header.data_length as usize;
+                    self.schema.write(file, #page_const_name, info_length)
+                }
+
+                /// Returns placement of the space's pages in the file.
+                pub fn layout(&self) -> SpaceLayout {
+                    let mut layout = SpaceLayout::default();
+                    layout.set_primary_index(&self.primary_index);
+                    self.indexes.set_layout(&mut layout);
+                    layout.set_data(&self.data);
+                    layout
+                }
+            }
 
-                    Ok(())
+            impl<const DATA_LENGTH: usize> CapturedSpace for #space_ident<DATA_LENGTH> {
+                fn persist(&mut self) -> eyre::Result<()> {
+                    #space_ident::persist(self)
                 }
             }
         })
@@ -0,0 +1,48 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+
+use crate::worktable::generator::Generator;
+
+impl Generator {
+    /// Generates `ArrowRow` implementation for the row and `ArrowTable`
+    /// implementation for the table. Like in [`Generator::gen_csv_impl`],
+    /// column types are required to implement `ArrowValue` only where
+    /// they are exported.
+    pub fn gen_arrow_impl(&self) -> TokenStream {
+        let row_type = self.row_name.as_ref().unwrap();
+        let table_type = self.table_name.as_ref().unwrap();
+
+        let columns = self.columns.columns_map.keys().collect::<Vec<_>>();
+        let names = columns
+            .iter()
+            .map(|c| Literal::string(c.to_string().as_str()))
+            .collect::<Vec<_>>();
+        let types = self.columns.columns_map.values().collect::<Vec<_>>();
+
+        quote! {
+            impl ArrowRow for #row_type
+            where
+                #(for<'a> #types: ArrowValue,)*
+            {
+                const FIELDS: &'static [(&'static str, ArrowType, bool)] = &[
+                    #((#names, <#types as ArrowValue>::TYPE, <#types as ArrowValue>::NULLABLE)),*
+                ];
+
+                fn to_arrow_values(&self) -> Vec<ArrowScalar> {
+                    vec![#(ArrowValue::to_arrow(&self.#columns)),*]
+                }
+            }
+
+            impl ArrowTable for #table_type
+            where
+                for<'a> #row_type: ArrowRow,
+            {
+                type Row = #row_type;
+
+                fn arrow_rows(&self) -> core::result::Result<Vec<#row_type>, WorkTableError> {
+                    self.select_all().execute()
+                }
+            }
+        }
+    }
+}
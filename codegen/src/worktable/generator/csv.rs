@@ -0,0 +1,67 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+
+use crate::worktable::generator::Generator;
+
+impl Generator {
+    /// Generates `CsvRow` implementation for the row and `export_csv` and
+    /// `import_csv` methods of the table.
+    ///
+    /// Column types are required to implement `CsvValue` only where these
+    /// methods are used, so tables with other column types still compile.
+    /// Bounds are higher-ranked, because trivial bounds are not allowed.
+    pub fn gen_csv_impl(&self) -> syn::Result<TokenStream> {
+        let row_type = self.row_name.as_ref().unwrap();
+        let table_type = self.table_name.as_ref().unwrap();
+
+        let columns = self.columns.columns_map.keys().collect::<Vec<_>>();
+        let names = columns
+            .iter()
+            .map(|c| Literal::string(c.to_string().as_str()))
+            .collect::<Vec<_>>();
+        let types = self.columns.columns_map.values().collect::<Vec<_>>();
+        let indexes = (0..columns.len()).map(Literal::usize_unsuffixed);
+
+        Ok(quote! {
+            impl CsvRow for #row_type
+            where
+                #(for<'a> #types: CsvValue,)*
+            {
+                const COLUMNS: &'static [&'static str] = &[#(#names),*];
+
+                fn to_csv_values(&self) -> Vec<String> {
+                    vec![#(CsvValue::to_csv(&self.#columns)),*]
+                }
+
+                fn from_csv_values(values: &[&str]) -> core::result::Result<Self, (&'static str, String)> {
+                    core::result::Result::Ok(Self {
+                        #(#columns: CsvValue::from_csv(values[#indexes]).map_err(|e| (#names, e))?,)*
+                    })
+                }
+            }
+
+            impl #table_type {
+                /// Writes table's rows to the `writer` as CSV with header of
+                /// the column names. Rows are written in primary key order.
+                pub fn export_csv<W: std::io::Write>(&self, writer: W) -> core::result::Result<(), CsvError>
+                where
+                    for<'a> #row_type: CsvRow,
+                {
+                    let rows = self.select_all().execute().map_err(CsvError::Select)?;
+                    write_csv(rows, writer)
+                }
+
+                /// Inserts rows read from CSV with header of the column names.
+                /// Rows are inserted in batches of [`IMPORT_BATCH_SIZE`],
+                /// rows before the failed one stay in the table. Returns count
+                /// of the inserted rows.
+                pub fn import_csv<R: std::io::Read>(&self, reader: R) -> core::result::Result<usize, CsvError>
+                where
+                    for<'a> #row_type: CsvRow,
+                {
+                    self.import_rows(|insert_many, insert| read_csv(reader, insert_many, insert))
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,61 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::worktable::generator::Generator;
+use crate::worktable::model::GeneratorType;
+
+impl Generator {
+    /// Generates `import_rows` method that is used by CSV and JSON imports to
+    /// insert rows with the table's `try_insert_many` and `insert`.
+    pub fn gen_import_impl(&self) -> TokenStream {
+        let row_type = self.row_name.as_ref().unwrap();
+        let table_type = self.table_name.as_ref().unwrap();
+
+        // Imported rows must not get their primary keys again.
+        let advance_pk_gen = match self.columns.generator_type {
+            GeneratorType::Autoincrement => {
+                let pk_field = self.columns.primary_keys.0.first().unwrap();
+                quote! {
+                    let advance = |rows: &[#row_type]| {
+                        if let Some(max) = rows.iter().map(|row| row.#pk_field.saturating_add(1)).max() {
+                            PrimaryKeyGeneratorState::advance_to(&self.0.pk_gen, max);
+                        }
+                    };
+                }
+            }
+            GeneratorType::None | GeneratorType::Custom => quote! {
+                let advance = |_: &[#row_type]| {};
+            },
+        };
+
+        quote! {
+            impl #table_type {
+                /// Calls `import` with the table's `try_insert_many`, that
+                /// gives rows back if they were not inserted, and `insert`.
+                /// Primary key generator is moved past the imported keys.
+                fn import_rows<E>(
+                    &self,
+                    import: impl FnOnce(
+                        &mut dyn FnMut(Vec<#row_type>) -> core::result::Result<(), Vec<#row_type>>,
+                        &mut dyn FnMut(#row_type) -> core::result::Result<(), WorkTableError>,
+                    ) -> core::result::Result<usize, E>,
+                ) -> core::result::Result<usize, E> {
+                    #advance_pk_gen
+                    import(
+                        &mut |rows: Vec<#row_type>| {
+                            advance(&rows);
+                            self.0
+                                .try_insert_many::<{ #row_type::ROW_SIZE }>(rows)
+                                .map(|_| ())
+                                .map_err(|(_, rows)| rows)
+                        },
+                        &mut |row: #row_type| {
+                            advance(std::slice::from_ref(&row));
+                            self.insert(row).map(|_| ())
+                        },
+                    )
+                }
+            }
+        }
+    }
+}
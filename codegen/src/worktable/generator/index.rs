@@ -22,7 +22,7 @@ impl Generator {
             .iter()
             .map(|(i, idx)| {
                 let index_type = &idx.index_type;
-                let t = self.columns.columns_map.get(&i);
+                let t = self.columns.columns_map.get(i);
                 let i = &idx.name;
 
                 if idx.is_unique {
@@ -45,7 +45,7 @@ impl Generator {
     }
 
     fn gen_impl_def(&mut self) -> TokenStream {
-        let save_rows = self
+        let delete_rows = self
             .columns
             .indexes
             .iter()
@@ -53,8 +53,34 @@ impl Generator {
                 let index_field_name = &idx.name;
                 if idx.is_unique {
                     quote! {
-                        TableIndex::insert(&self.#index_field_name, row.#i, link)
-                            .map_err(|_| WorkTableError::AlreadyExists)?;
+                        TableIndex::remove(&self.#index_field_name, &row.#i);
+                    }
+                } else {
+                    quote! {
+                        if let Some(set) = TableIndex::peek(&self.#index_field_name, &row.#i) {
+                            set.remove(&link);
+                        }
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let save_rows = self
+            .columns
+            .indexes
+            .iter()
+            .enumerate()
+            .map(|(j, (i, idx))| {
+                let index_field_name = &idx.name;
+                // Index entries that were saved before are removed on error, so
+                // row is saved either to all indexes or to none of them.
+                let rollback = &delete_rows[..j];
+                if idx.is_unique {
+                    quote! {
+                        if TableIndex::insert(&self.#index_field_name, row.#i.clone(), link).is_err() {
+                            #(#rollback)*
+                            return Err(WorkTableError::AlreadyExists);
+                        }
                     }
                 } else {
                     quote! {
@@ -63,37 +89,173 @@ impl Generator {
                         } else {
                             let set = LockFreeSet::new();
                             set.insert(link).expect("is ok");
-                            self.#index_field_name
-                                .insert(row.#i, std::sync::Arc::new(set))
-                                .map_err(|_| WorkTableError::AlreadyExists)?;
+                            if self.#index_field_name
+                                .insert(row.#i.clone(), std::sync::Arc::new(set))
+                                .is_err()
+                            {
+                                #(#rollback)*
+                                return Err(WorkTableError::AlreadyExists);
+                            }
                         }
                     }
                 }
             })
             .collect::<Vec<_>>();
 
-        let delete_rows = self
+        let entries_idents = self
             .columns
             .indexes
-            .iter()
-            .map(|(i, idx)| {
+            .values()
+            .map(|idx| Ident::new(format!("{}_entries", idx.name).as_str(), Span::mixed_site()))
+            .collect::<Vec<_>>();
+        let sorted_entries = self
+            .columns
+            .indexes
+            .keys()
+            .zip(&entries_idents)
+            .map(|(i, entries)| {
+                quote! {
+                    let mut #entries = rows
+                        .iter()
+                        .map(|(row, link)| (row.#i.clone(), *link))
+                        .collect::<Vec<_>>();
+                    #entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                }
+            })
+            .collect::<Vec<_>>();
+        let unique_checks = self
+            .columns
+            .indexes
+            .values()
+            .zip(&entries_idents)
+            .filter(|(idx, _)| idx.is_unique)
+            .map(|(idx, entries)| {
+                let index_field_name = &idx.name;
+                quote! {
+                    if #entries.windows(2).any(|w| w[0].0 == w[1].0)
+                        || #entries
+                            .iter()
+                            .any(|(key, _)| TableIndex::peek(&self.#index_field_name, key).is_some())
+                    {
+                        return Err(WorkTableError::AlreadyExists);
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        let bulk_rollbacks = self
+            .columns
+            .indexes
+            .values()
+            .zip(&entries_idents)
+            .map(|(idx, entries)| {
                 let index_field_name = &idx.name;
                 if idx.is_unique {
                     quote! {
-                        TableIndex::remove(&self.#index_field_name, &row.#i);
+                        for (key, _) in #entries.iter() {
+                            TableIndex::remove(&self.#index_field_name, key);
+                        }
                     }
                 } else {
                     quote! {
-                        if let Some(set) = TableIndex::peek(&self.#index_field_name, &row.#i) {
-                            set.remove(&link);
+                        for (key, link) in #entries.iter() {
+                            if let Some(set) = TableIndex::peek(&self.#index_field_name, key) {
+                                set.remove(link);
+                            }
+                        }
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        let bulk_saves = self
+            .columns
+            .indexes
+            .values()
+            .zip(&entries_idents)
+            .enumerate()
+            .map(|(j, (idx, entries))| {
+                let index_field_name = &idx.name;
+                // Same as for the single row, entries saved to the previous
+                // indexes and to this index are removed on error.
+                let rollback = &bulk_rollbacks[..j];
+                if idx.is_unique {
+                    quote! {
+                        for (saved, (key, link)) in #entries.iter().enumerate() {
+                            if TableIndex::insert(&self.#index_field_name, key.clone(), *link).is_err() {
+                                for (key, _) in #entries[..saved].iter() {
+                                    TableIndex::remove(&self.#index_field_name, key);
+                                }
+                                #(#rollback)*
+                                return Err(WorkTableError::AlreadyExists);
+                            }
+                        }
+                    }
+                } else {
+                    quote! {
+                        let mut start = 0;
+                        while start < #entries.len() {
+                            let key = &#entries[start].0;
+                            let end = start + #entries[start..].iter().take_while(|(k, _)| k == key).count();
+                            if let Some(set) = TableIndex::peek(&self.#index_field_name, key) {
+                                for (_, link) in #entries[start..end].iter() {
+                                    set.insert(*link).expect("is ok");
+                                }
+                            } else {
+                                let set = LockFreeSet::new();
+                                for (_, link) in #entries[start..end].iter() {
+                                    set.insert(*link).expect("is ok");
+                                }
+                                if self.#index_field_name
+                                    .insert(key.clone(), std::sync::Arc::new(set))
+                                    .is_err()
+                                {
+                                    for (key, link) in #entries[..start].iter() {
+                                        if let Some(set) = TableIndex::peek(&self.#index_field_name, key) {
+                                            set.remove(link);
+                                        }
+                                    }
+                                    #(#rollback)*
+                                    return Err(WorkTableError::AlreadyExists);
+                                }
+                            }
+                            start = end;
                         }
                     }
                 }
             })
             .collect::<Vec<_>>();
 
+        let clears = self
+            .columns
+            .indexes
+            .values()
+            .map(|idx| {
+                let index_field_name = &idx.name;
+                quote! {
+                    let keys = TableIndex::iter(&self.#index_field_name)
+                        .map(|(key, _)| key.clone())
+                        .collect::<Vec<_>>();
+                    for key in keys {
+                        TableIndex::remove(&self.#index_field_name, &key);
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
         let row_type_name = self.row_name.as_ref().unwrap();
         let index_type_name = self.index_name.as_ref().unwrap();
+        let save_rows_def = if self.columns.indexes.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                fn save_rows(&self, rows: &[(#row_type_name, Link)]) -> core::result::Result<(), WorkTableError> {
+                    #(#sorted_entries)*
+                    #(#unique_checks)*
+                    #(#bulk_saves)*
+
+                    core::result::Result::Ok(())
+                }
+            }
+        };
 
         quote! {
             impl TableSecondaryIndex<#row_type_name> for #index_type_name {
@@ -108,6 +270,12 @@ impl Generator {
 
                     core::result::Result::Ok(())
                 }
+
+                fn clear(&self) {
+                    #(#clears)*
+                }
+
+                #save_rows_def
             }
         }
     }
@@ -0,0 +1,55 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::worktable::generator::Generator;
+
+impl Generator {
+    /// Generates `serde` derives for the table's types if `serde: true` is
+    /// set.
+    pub fn gen_serde_derive(&self) -> TokenStream {
+        if !self.is_serde {
+            return quote! {};
+        }
+        quote! {
+            #[derive(serde::Serialize, serde::Deserialize)]
+        }
+    }
+
+    /// Generates `to_json_lines` and `from_json_lines` methods that dump the
+    /// whole table as JSON lines and restore it. Generated only if
+    /// `serde: true` is set.
+    pub fn gen_json_impl(&self) -> TokenStream {
+        if !self.is_serde {
+            return quote! {};
+        }
+        let table_type = self.table_name.as_ref().unwrap();
+
+        let (args, new) = if self.is_persist {
+            (
+                quote! { manager: std::sync::Arc<DatabaseManager>, },
+                quote! { Self::new(manager) },
+            )
+        } else {
+            (quote! {}, quote! { Self::default() })
+        };
+
+        quote! {
+            impl #table_type {
+                /// Writes table's rows to the `writer` as JSON objects, one
+                /// per line. Rows are written in primary key order.
+                pub fn to_json_lines<W: std::io::Write>(&self, writer: W) -> core::result::Result<(), JsonLinesError> {
+                    let rows = self.select_all().execute().map_err(JsonLinesError::Select)?;
+                    write_json_lines(rows, writer)
+                }
+
+                /// Creates table with rows read from the JSON lines written by
+                /// `to_json_lines`.
+                pub fn from_json_lines<R: std::io::Read>(#args reader: R) -> core::result::Result<Self, JsonLinesError> {
+                    let table = #new;
+                    table.import_rows(|insert_many, insert| read_json_lines(reader, insert_many, insert))?;
+                    core::result::Result::Ok(table)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use indexmap::IndexMap;
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+
+use crate::worktable::generator::Generator;
+use crate::worktable::model::{ColumnChange, Migration};
+
+impl Generator {
+    /// Generates table's schema version, row columns of each schema version,
+    /// row types of the older schema versions with conversions between them
+    /// and `migrate_from_file` that loads files written with them. Generates nothing for the tables that
+    /// are not persisted.
+    pub fn gen_migrations_def(&self) -> syn::Result<TokenStream> {
+        if !self.is_persist {
+            if let Some(migration) = self.migrations.first() {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    format!(
+                        "Migration {} is declared for the table that is not persisted.",
+                        migration.version
+                    ),
+                ));
+            }
+            return Ok(quote! {});
+        }
+
+        let name = &self.name;
+        let schema_const_name = Ident::new(
+            format!("{}_SCHEMA_VERSION", name.to_string().to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let columns_const_name = Ident::new(
+            format!("{}_SCHEMA_COLUMNS", name.to_string().to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let version = Literal::u32_unsuffixed(self.migrations.len() as u32);
+
+        let versions = self.gen_schema_versions()?;
+        let versions_count = Literal::usize_unsuffixed(versions.len());
+        let schema_columns = versions.iter().map(|columns| {
+            let columns = columns.iter().map(|(name, type_)| {
+                let name = Literal::string(name.to_string().as_str());
+                let type_ = Literal::string(type_.to_string().as_str());
+                quote! { (#name, #type_) }
+            });
+            quote! { &[#(#columns),*] }
+        });
+        let mut rows = vec![];
+        for (version, columns) in versions.iter().enumerate().take(self.migrations.len()) {
+            rows.push(self.gen_versioned_row_def(version as u32, columns));
+        }
+        let mut conversions = vec![];
+        for migration in &self.migrations {
+            let version = migration.version as usize;
+            conversions.push(self.gen_migration_conversion(
+                migration,
+                &versions[version - 1],
+                &versions[version],
+            ));
+        }
+        let migrate_fn = self.gen_migrate_from_file_fn();
+
+        Ok(quote! {
+            const #schema_const_name: u32 = #version;
+            /// Names and types of the row's columns in each schema version.
+            const #columns_const_name: [&[(&str, &str)]; #versions_count] = [#(#schema_columns),*];
+
+            #(#rows)*
+            #(#conversions)*
+            #migrate_fn
+        })
+    }
+
+    /// Returns columns of each schema version, starting from `0`. Columns of
+    /// the older version are found by reverting migration's changes of the
+    /// newer one. Columns dropped by migration are placed after the others.
+    fn gen_schema_versions(&self) -> syn::Result<Vec<IndexMap<Ident, TokenStream>>> {
+        let primary_keys = &self.columns.primary_keys.0;
+        let mut versions = vec![self.columns.columns_map.clone()];
+        for migration in self.migrations.iter().rev() {
+            let mut columns = versions.last().unwrap().clone();
+            let not_found = |name: &Ident| {
+                syn::Error::new(
+                    name.span(),
+                    format!(
+                        "Column `{}` is not declared in schema version {}.",
+                        name, migration.version
+                    ),
+                )
+            };
+            let check_type =
+                |name: &Ident, type_: &TokenStream, columns: &IndexMap<Ident, TokenStream>| {
+                    let column_type = columns.get(name).ok_or(not_found(name))?;
+                    if column_type.to_string() != type_.to_string() {
+                        return Err(syn::Error::new(
+                            name.span(),
+                            format!(
+                                "Column `{}` has type `{}` in schema version {}.",
+                                name, column_type, migration.version
+                            ),
+                        ));
+                    }
+                    Ok(())
+                };
+            for change in migration.changes.iter().rev() {
+                let changed = match change {
+                    ColumnChange::Add { name, .. }
+                    | ColumnChange::Drop { name, .. }
+                    | ColumnChange::Widen { name, .. } => vec![name],
+                    ColumnChange::Rename { from, to } => vec![from, to],
+                };
+                if let Some(pk) = changed.into_iter().find(|c| primary_keys.contains(c)) {
+                    return Err(syn::Error::new(
+                        pk.span(),
+                        "Primary key columns can't be changed by migrations.",
+                    ));
+                }
+
+                match change {
+                    ColumnChange::Add { name, type_, .. } => {
+                        check_type(name, type_, &columns)?;
+                        columns.shift_remove(name);
+                    }
+                    ColumnChange::Drop { name, type_ } => {
+                        if columns.contains_key(name) {
+                            return Err(syn::Error::new(
+                                name.span(),
+                                format!(
+                                    "Dropped column `{}` is declared in schema version {}.",
+                                    name, migration.version
+                                ),
+                            ));
+                        }
+                        columns.insert(name.clone(), type_.clone());
+                    }
+                    ColumnChange::Rename { from, to } => {
+                        if !columns.contains_key(to) {
+                            return Err(not_found(to));
+                        }
+                        if columns.contains_key(from) {
+                            return Err(syn::Error::new(
+                                from.span(),
+                                format!(
+                                    "Renamed column `{}` is declared in schema version {}.",
+                                    from, migration.version
+                                ),
+                            ));
+                        }
+                        columns = columns
+                            .into_iter()
+                            .map(|(c, t)| if &c == to { (from.clone(), t) } else { (c, t) })
+                            .collect();
+                    }
+                    ColumnChange::Widen { name, from, to, .. } => {
+                        check_type(name, to, &columns)?;
+                        columns.insert(name.clone(), from.clone());
+                    }
+                }
+            }
+            versions.push(columns);
+        }
+        versions.reverse();
+
+        Ok(versions)
+    }
+
+    fn versioned_row_ident(&self, version: u32) -> Ident {
+        if version as usize == self.migrations.len() {
+            self.row_name.clone().unwrap()
+        } else {
+            Ident::new(
+                format!("{}RowV{}", self.name, version).as_str(),
+                Span::mixed_site(),
+            )
+        }
+    }
+
+    /// Generates row and its wrapper as they were in the schema `version`, so
+    /// rows can be read from the files written with it.
+    fn gen_versioned_row_def(
+        &self,
+        version: u32,
+        columns: &IndexMap<Ident, TokenStream>,
+    ) -> TokenStream {
+        let row_ident = self.versioned_row_ident(version);
+        let wrapper_ident = Ident::new(
+            format!("{}WrapperV{}", self.name, version).as_str(),
+            Span::mixed_site(),
+        );
+        let fields = columns.iter().map(|(name, type_)| {
+            quote! {pub #name: #type_,}
+        });
+        let row_locks = columns.keys().map(|i| {
+            let name = Ident::new(format!("{i}_lock").as_str(), Span::mixed_site());
+            quote! {
+                #name: u64,
+            }
+        });
+        let row_defaults = columns.keys().map(|i| {
+            let name = Ident::new(format!("{i}_lock").as_str(), Span::mixed_site());
+            quote! {
+                #name: Default::default(),
+            }
+        });
+
+        quote! {
+            #[derive(rkyv::Archive, Debug, rkyv::Deserialize, Clone, rkyv::Serialize, PartialEq)]
+            #[rkyv(derive(Debug))]
+            #[repr(C)]
+            pub struct #row_ident {
+                #(#fields)*
+            }
+
+            #[derive(rkyv::Archive, Debug, rkyv::Deserialize, rkyv::Serialize)]
+            #[repr(C)]
+            pub struct #wrapper_ident {
+                inner: #row_ident,
+
+                is_deleted: bool,
+
+                lock: u64,
+
+                #(#row_locks)*
+            }
+
+            impl StorableRow for #row_ident {
+                type WrappedRow = #wrapper_ident;
+            }
+
+            impl RowWrapper<#row_ident> for #wrapper_ident {
+                fn get_inner(self) -> #row_ident {
+                    self.inner
+                }
+
+                fn from_inner(inner: #row_ident) -> Self {
+                    Self {
+                        inner,
+                        is_deleted: Default::default(),
+                        lock: Default::default(),
+                        #(#row_defaults)*
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generates conversion of the row of the migration's previous schema
+    /// version to its version.
+    fn gen_migration_conversion(
+        &self,
+        migration: &Migration,
+        previous: &IndexMap<Ident, TokenStream>,
+        current: &IndexMap<Ident, TokenStream>,
+    ) -> TokenStream {
+        let previous_ident = self.versioned_row_ident(migration.version - 1);
+        let current_ident = self.versioned_row_ident(migration.version);
+
+        let mut values = previous
+            .keys()
+            .map(|name| (name.clone(), quote! { row.#name }))
+            .collect::<IndexMap<_, _>>();
+        for change in &migration.changes {
+            match change {
+                ColumnChange::Add { name, default, .. } => {
+                    values.insert(name.clone(), default.clone());
+                }
+                ColumnChange::Drop { name, .. } => {
+                    values.shift_remove(name);
+                }
+                ColumnChange::Rename { from, to } => {
+                    let value = values.shift_remove(from).unwrap();
+                    values.insert(to.clone(), value);
+                }
+                ColumnChange::Widen { name, optional, .. } => {
+                    let value = values[name].clone();
+                    let value = if *optional {
+                        quote! { #value.map(Into::into) }
+                    } else {
+                        quote! { #value.into() }
+                    };
+                    values.insert(name.clone(), value);
+                }
+            }
+        }
+        let fields = current.keys().map(|name| {
+            let value = &values[name];
+            quote! { #name: #value, }
+        });
+
+        quote! {
+            impl From<#previous_ident> for #current_ident {
+                fn from(row: #previous_ident) -> Self {
+                    Self {
+                        #(#fields)*
+                    }
+                }
+            }
+        }
+    }
+
+    fn gen_migrate_from_file_fn(&self) -> TokenStream {
+        let name = &self.name;
+        let table_ident = Ident::new(format!("{}WorkTable", name).as_str(), Span::mixed_site());
+        let space_ident = Ident::new(format!("{}Space", name).as_str(), Span::mixed_site());
+        let inner_const_name = Ident::new(
+            format!("{}_INNER_SIZE", name.to_string().to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+
+        if self.migrations.is_empty() {
+            return quote! {
+                impl #table_ident {
+                    fn migrate_from_file(
+                        _: std::sync::Arc<DatabaseManager>,
+                        _: &mut std::fs::File,
+                        version: u32,
+                    ) -> eyre::Result<Self> {
+                        eyre::bail!("table has no migrations from schema version {}", version)
+                    }
+                }
+            };
+        }
+
+        let arms = (0..self.migrations.len() as u32).map(|version| {
+            let row_ident = self.versioned_row_ident(version);
+            let conversions = (version + 1..=self.migrations.len() as u32).map(|v| {
+                let ident = self.versioned_row_ident(v);
+                quote! {
+                    let row = #ident::from(row);
+                }
+            });
+            let version = Literal::u32_unsuffixed(version);
+            quote! {
+                #version => {
+                    let mut page_id = 0;
+                    let data = data.into_iter().map(|p| {
+                        let mut data = Data::from_data_page(p);
+                        data.set_page_id(page_id.into());
+                        page_id += 1;
+
+                        std::sync::Arc::new(data)
+                    })
+                        .collect();
+                    let data = DataPages::<#row_ident, #inner_const_name>::from_data(data);
+                    for (_, link) in TableIndex::iter(&pk_map) {
+                        let row = data.select(*link).map_err(WorkTableError::PagesError)?;
+                        #(#conversions)*
+                        table.insert(row)?;
+                    }
+                }
+            }
+        });
+
+        quote! {
+            impl #table_ident {
+                /// Loads table from the file written with the older schema
+                /// `version`, converting its rows to the current row type.
+                /// Secondary indexes are built from the converted rows.
+                fn migrate_from_file(
+                    manager: std::sync::Arc<DatabaseManager>,
+                    file: &mut std::fs::File,
+                    version: u32,
+                ) -> eyre::Result<Self> {
+                    let space = #space_ident::parse_file_without_indexes(file)?;
+                    let mut table = Self::new(manager);
+                    table.0.pk_gen = PrimaryKeyGeneratorState::from_state(space.info.inner.pk_gen_state);
+                    let pk_map = TreeIndex::new();
+                    for page in space.primary_index {
+                        page.inner.append_to_unique_tree_index(&pk_map);
+                    }
+                    let data = space.data;
+                    match version {
+                        #(#arms)*
+                        _ => eyre::bail!("table has no migrations from schema version {}", version),
+                    }
+
+                    Ok(table)
+                }
+            }
+        }
+    }
+}
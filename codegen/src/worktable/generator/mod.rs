@@ -1,17 +1,25 @@
+mod arrow;
+mod csv;
+mod import;
 mod index;
+mod json;
+mod migration;
 mod primary_key;
 mod queries;
 mod row;
+mod snapshot;
 mod table;
+mod wal;
 mod wrapper;
 
 use proc_macro2::Ident;
 
-use crate::worktable::model::{Columns, Config, PrimaryKey, Queries};
+use crate::worktable::model::{Columns, Config, Migration, PrimaryKey, Queries};
 
 pub struct Generator {
     pub name: Ident,
     pub is_persist: bool,
+    pub is_serde: bool,
     pub table_name: Option<Ident>,
     pub row_name: Option<Ident>,
     pub wrapper_name: Option<Ident>,
@@ -19,6 +27,7 @@ pub struct Generator {
     pub pk: Option<PrimaryKey>,
     pub queries: Option<Queries>,
     pub config: Option<Config>,
+    pub migrations: Vec<Migration>,
 
     pub columns: Columns,
 }
@@ -28,6 +37,7 @@ impl Generator {
         Self {
             name,
             is_persist,
+            is_serde: false,
             table_name: None,
             row_name: None,
             wrapper_name: None,
@@ -35,6 +45,7 @@ impl Generator {
             pk: None,
             queries: None,
             config: None,
+            migrations: vec![],
             columns,
         }
     }
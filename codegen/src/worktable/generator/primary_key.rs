@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 use crate::worktable::generator::Generator;
 use crate::worktable::model::{GeneratorType, PrimaryKey};
@@ -16,18 +16,21 @@ impl Generator {
             .0
             .iter()
             .map(|i| (i.clone(), self.columns.columns_map.get(i).unwrap().clone()))
-            .collect::<HashMap<_, _>>();
+            .collect::<IndexMap<_, _>>();
 
+        let serde_derive = self.gen_serde_derive();
         let def = if vals.len() == 1 {
             let type_ = vals.values().next().unwrap();
             quote! {
                 #[derive(Clone, rkyv::Archive, Debug, rkyv::Deserialize, rkyv::Serialize, From, Eq, Into, PartialEq, PartialOrd, Ord)]
+                #serde_derive
                 pub struct #ident(#type_);
             }
         } else {
             let types = vals.values();
             quote! {
                 #[derive(Clone, rkyv::Archive, Debug, rkyv::Deserialize, rkyv::Serialize, From, Eq, Into, PartialEq, PartialOrd, Ord)]
+                #serde_derive
                 pub struct #ident(#(#types),*);
             }
         };
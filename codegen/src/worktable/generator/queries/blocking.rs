@@ -0,0 +1,58 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{FnArg, ImplItem, ItemImpl, Pat};
+
+use crate::worktable::generator::Generator;
+
+impl Generator {
+    /// Generates `_blocking` counterparts for all public async methods of the
+    /// provided table impl. They run async version in `block_on`, so both
+    /// versions share the same lock protocol.
+    pub fn gen_blocking_impl(&self, impls: &TokenStream) -> syn::Result<TokenStream> {
+        let item: ItemImpl = syn::parse2(impls.clone())?;
+        let table_ident = self.table_name.as_ref().unwrap();
+
+        let mut methods = Vec::new();
+        for item in item.items {
+            let ImplItem::Fn(f) = item else {
+                continue;
+            };
+            if f.sig.asyncness.is_none() || !matches!(f.vis, syn::Visibility::Public(_)) {
+                continue;
+            }
+
+            let name = &f.sig.ident;
+            let mut sig = f.sig.clone();
+            sig.asyncness = None;
+            sig.ident = Ident::new(format!("{}_blocking", name).as_str(), Span::mixed_site());
+            let args = f
+                .sig
+                .inputs
+                .iter()
+                .filter_map(|arg| match arg {
+                    FnArg::Receiver(_) => None,
+                    FnArg::Typed(arg) => Some(arg),
+                })
+                .map(|arg| match arg.pat.as_ref() {
+                    Pat::Ident(pat) => Ok(pat.ident.clone()),
+                    _ => Err(syn::Error::new(
+                        Span::call_site(),
+                        "Only identifier patterns are supported in async methods",
+                    )),
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            methods.push(quote! {
+                pub #sig {
+                    block_on(self.#name(#(#args),*))
+                }
+            })
+        }
+
+        Ok(quote! {
+            impl #table_ident {
+                #(#methods)*
+            }
+        })
+    }
+}
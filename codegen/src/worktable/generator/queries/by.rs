@@ -0,0 +1,60 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+
+use crate::worktable::generator::Generator;
+use crate::worktable::model::Index;
+
+impl Generator {
+    /// Returns type of the `by` argument of a query: the column's type for a
+    /// single column and a tuple of columns' types for multiple columns.
+    pub fn gen_by_type(&self, by: &[Ident]) -> syn::Result<TokenStream> {
+        let types = by
+            .iter()
+            .map(|i| {
+                self.columns
+                    .columns_map
+                    .get(i)
+                    .ok_or(syn::Error::new(i.span(), "Unexpected column name"))
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+        if types.len() == 1 {
+            let type_ = types[0];
+            Ok(quote! { #type_ })
+        } else {
+            Ok(quote! { (#(#types),*) })
+        }
+    }
+
+    /// Returns condition that checks if `row` matches the `by` argument. Used
+    /// for queries that fall back to a full scan.
+    pub fn gen_by_condition(by: &[Ident]) -> TokenStream {
+        if by.len() == 1 {
+            let field = &by[0];
+            quote! { row.#field == by }
+        } else {
+            let conditions = by.iter().enumerate().map(|(i, field)| {
+                let i = syn::Index::from(i);
+                quote! { row.#field == by.#i }
+            });
+            quote! { #(#conditions)&&* }
+        }
+    }
+
+    /// Returns index that is built on the single `by` column, if any.
+    pub fn get_by_index(&self, by: &[Ident]) -> Option<&Index> {
+        if by.len() != 1 {
+            return None;
+        }
+        self.columns
+            .indexes
+            .values()
+            .find(|idx| idx.field.to_string() == by[0].to_string())
+    }
+
+    /// Checks if `by` columns are exactly the table's primary key columns in
+    /// their declaration order, so `by` argument has the primary key's type.
+    pub fn is_by_primary_key(&self, by: &[Ident]) -> bool {
+        let pk = &self.columns.primary_keys.0;
+        pk.len() == by.len() && by.iter().zip(pk).all(|(i, p)| i == p)
+    }
+}
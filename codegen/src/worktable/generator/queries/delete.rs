@@ -8,7 +8,7 @@ use std::collections::HashMap;
 impl Generator {
     pub fn gen_query_delete_impl(&mut self) -> syn::Result<TokenStream> {
         let custom_deletes = if let Some(q) = &self.queries {
-            let custom_deletes = self.gen_custom_deletes(q.deletes.clone());
+            let custom_deletes = self.gen_custom_deletes(q.deletes.clone())?;
 
             quote! {
                 #custom_deletes
@@ -17,44 +17,257 @@ impl Generator {
             quote! {}
         };
         let full_row_delete = self.gen_full_row_delete();
+        let range_deletes = self.gen_range_deletes();
+        let delete_where = self.gen_delete_where();
 
         let table_ident = self.table_name.as_ref().unwrap();
         Ok(quote! {
             impl #table_ident {
                 #full_row_delete
+                #range_deletes
+                #delete_where
                 #custom_deletes
             }
         })
     }
 
-    fn gen_full_row_delete(&mut self) -> TokenStream {
+    /// Generates deletion of rows with provided primary keys. Rows that were
+    /// already deleted concurrently are skipped. Evaluates to count of deleted
+    /// rows.
+    fn gen_delete_pks(pks: TokenStream) -> TokenStream {
+        quote! {
+            {
+                let mut deleted = 0;
+                for pk in #pks {
+                    match self.delete(pk).await {
+                        core::result::Result::Ok(()) => deleted += 1,
+                        Err(WorkTableError::NotFound) => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+                deleted
+            }
+        }
+    }
+
+    fn gen_range_deletes(&self) -> TokenStream {
         let pk_ident = &self.pk.as_ref().unwrap().ident;
+        let pk_range_delete = if self.columns.primary_keys.0.len() == 1 {
+            let pk_field = self.columns.primary_keys.0.first().unwrap();
+            let type_ = self.columns.columns_map.get(pk_field).unwrap();
+            let method_ident = Ident::new(
+                format!("delete_range_by_{pk_field}").as_str(),
+                Span::mixed_site(),
+            );
+            let delete_pks = Self::gen_delete_pks(quote! { pks });
+            quote! {
+                pub async fn #method_ident(&self, range: impl std::ops::RangeBounds<#type_>) -> core::result::Result<usize, WorkTableError> {
+                    let range = (
+                        range.start_bound().map(|v| #pk_ident::from(v.clone())),
+                        range.end_bound().map(|v| #pk_ident::from(v.clone())),
+                    );
+                    let pks = TableIndex::range(&self.0.pk_map, range)
+                        .map(|(pk, _)| pk.clone())
+                        .collect::<Vec<_>>();
+                    core::result::Result::Ok(#delete_pks)
+                }
+            }
+        } else {
+            let delete_pks = Self::gen_delete_pks(quote! { pks });
+            quote! {
+                pub async fn delete_range_by_pk(&self, range: impl std::ops::RangeBounds<#pk_ident>) -> core::result::Result<usize, WorkTableError> {
+                    let pks = TableIndex::range(&self.0.pk_map, range)
+                        .map(|(pk, _)| pk.clone())
+                        .collect::<Vec<_>>();
+                    core::result::Result::Ok(#delete_pks)
+                }
+            }
+        };
+
+        let index_range_deletes = self
+            .columns
+            .indexes
+            .values()
+            .map(|idx| {
+                let index_name = &idx.name;
+                let field = &idx.field;
+                let type_ = self.columns.columns_map.get(field).unwrap();
+                let method_ident = Ident::new(
+                    format!("delete_range_by_{field}").as_str(),
+                    Span::mixed_site(),
+                );
+                let links = if idx.is_unique {
+                    quote! {
+                        TableIndex::range(&self.0.indexes.#index_name, range)
+                            .map(|(_, link)| *link)
+                            .collect::<Vec<_>>()
+                    }
+                } else {
+                    quote! {
+                        TableIndex::range(&self.0.indexes.#index_name, range)
+                            .flat_map(|(_, links)| links.iter().map(|link| *link.as_ref()).collect::<Vec<_>>())
+                            .collect::<Vec<_>>()
+                    }
+                };
+                let delete_pks = Self::gen_delete_pks(quote! { pks });
+                quote! {
+                    pub async fn #method_ident(&self, range: impl std::ops::RangeBounds<#type_>) -> core::result::Result<usize, WorkTableError> {
+                        let links = #links;
+                        let pks = links
+                            .into_iter()
+                            .filter_map(|link| self.0.data.select(link).ok())
+                            .map(|row| row.get_primary_key())
+                            .collect::<Vec<_>>();
+                        core::result::Result::Ok(#delete_pks)
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
 
         quote! {
-            pub async fn delete(&self, pk: #pk_ident) -> core::result::Result<(), WorkTableError> {
-                let link = {
-                    let guard = Guard::new();
-                    TableIndex::peek(&self.0.pk_map, &pk).ok_or(WorkTableError::NotFound)?
+            #pk_range_delete
+            #(#index_range_deletes)*
+        }
+    }
+
+    fn gen_delete_where(&self) -> TokenStream {
+        let row_ident = self.row_name.as_ref().unwrap();
+        let delete_pks = Self::gen_delete_pks(quote! { pks.into_inner() });
+
+        quote! {
+            pub async fn delete_where<F: Fn(&#row_ident) -> bool>(&self, predicate: F) -> core::result::Result<usize, WorkTableError> {
+                let pks = std::cell::RefCell::new(vec![]);
+                self.iter_with(|row| {
+                    if predicate(&row) {
+                        pks.borrow_mut().push(row.get_primary_key());
+                    }
+                    core::result::Result::Ok(())
+                })?;
+                core::result::Result::Ok(#delete_pks)
+            }
+        }
+    }
+
+    fn gen_full_row_delete(&mut self) -> TokenStream {
+        let pk_ident = &self.pk.as_ref().unwrap().ident;
+        let row_ident = self.row_name.as_ref().unwrap();
+        let wal_begin = self.gen_wal_begin();
+        let wal_delete = self.gen_wal_delete(quote! { pk });
+        let wal_commit = self.gen_wal_commit();
+        let row_lock = Self::gen_find_row_lock(
+            quote! { TableIndex::peek(&self.0.pk_map, &pk) },
+            quote! { archived.is_locked() },
+            quote! { archived.lock = op_id.into(); },
+        );
+        let row_unlock =
+            Self::gen_row_unlock(quote! { link }, quote! { archived.lock = 0u64.into(); });
+        let cleared_check = Self::gen_cleared_check();
+
+        // `delete` passes the row to the indexes instead of cloning it for the
+        // returned value. Checked variants delete the row only if it matches
+        // `check` once it's locked.
+        let delete_body = |returning: bool, checked: bool| {
+            let (delete_row, res) = if returning {
+                (
+                    quote! { self.0.indexes.delete_row(row.clone(), link)?; },
+                    if checked {
+                        quote! { Some(row) }
+                    } else {
+                        quote! { row }
+                    },
+                )
+            } else {
+                (
+                    quote! { self.0.indexes.delete_row(row, link)?; },
+                    if checked {
+                        quote! { true }
+                    } else {
+                        quote! { () }
+                    },
+                )
+            };
+            let check = if checked {
+                let skipped = if returning {
+                    quote! { None }
+                } else {
+                    quote! { false }
                 };
-                let id = self.0.data.with_ref(link, |archived| {
-                    archived.is_locked()
-                }).map_err(WorkTableError::PagesError)?;
-                if let Some(id) = id {
-                    if let Some(lock) = self.0.lock_map.get(&(id.into())) {
-                        lock.as_ref().await
+                quote! {
+                    if !check(&row) {
+                        return core::result::Result::Ok(#skipped);
                     }
                 }
-                let row = self.select(pk.clone()).unwrap();
-                self.0.indexes.delete_row(row, link)?;
+            } else {
+                quote! {}
+            };
+            quote! {
+                let mut lock_guard = LockGuard::new(&self.0.lock_map);
+                let op_id = lock_guard.id();
+
+                // Row could be deleted or moved by `update_pk` while this
+                // operation waited for the lock, then it's not found.
+                let link = #row_lock;
+                lock_guard.on_release(link, move || #row_unlock);
+                let versions = self.0.versions.begin_write();
+                #cleared_check
+                let row = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
+                #check
+                #wal_begin
+                versions.record_with(|| Some((pk.clone(), Some(row.clone()))));
+                #delete_row
                 self.0.pk_map.remove(&pk);
+                lock_guard.release();
                 self.0.data.delete(link).map_err(WorkTableError::PagesError)?;
+                #wal_delete
+                #wal_commit
+                drop(versions);
+                drop(lock_guard);
 
-                core::result::Result::Ok(())
+                core::result::Result::Ok(#res)
             }
+        };
+        let delete = delete_body(false, false);
+        let delete_returning = delete_body(true, false);
+        let delete_checked = delete_body(false, true);
+        let delete_returning_checked = delete_body(true, true);
+
+        // Checked deletes are used only by custom deletes that find rows not
+        // by primary key.
+        let checked_deletes = self
+            .queries
+            .as_ref()
+            .is_some_and(|q| q.deletes.values().any(|op| !self.is_by_primary_key(&op.by)));
+        let checked_deletes = checked_deletes.then(|| {
+            quote! {
+                /// Deletes row with `pk` if it matches `check` when it's locked.
+                /// Returns `false` if row doesn't match.
+                async fn delete_checked<F: Fn(&#row_ident) -> bool>(&self, pk: #pk_ident, check: F) -> core::result::Result<bool, WorkTableError> {
+                    #delete_checked
+                }
+
+                /// Deletes row with `pk` if it matches `check` when it's locked.
+                /// Returns `None` if row doesn't match.
+                async fn delete_returning_checked<F: Fn(&#row_ident) -> bool>(&self, pk: #pk_ident, check: F) -> core::result::Result<Option<#row_ident>, WorkTableError> {
+                    #delete_returning_checked
+                }
+            }
+        });
+
+        quote! {
+            pub async fn delete(&self, pk: #pk_ident) -> core::result::Result<(), WorkTableError> {
+                #delete
+            }
+
+            pub async fn delete_returning(&self, pk: #pk_ident) -> core::result::Result<#row_ident, WorkTableError> {
+                #delete_returning
+            }
+
+            #checked_deletes
         }
     }
 
-    fn gen_custom_deletes(&mut self, deleted: HashMap<Ident, Operation>) -> TokenStream {
+    fn gen_custom_deletes(&self, deleted: HashMap<Ident, Operation>) -> syn::Result<TokenStream> {
+        let row_ident = self.row_name.as_ref().unwrap();
         let defs = deleted
             .iter()
             .map(|(name, op)| {
@@ -66,79 +279,187 @@ impl Generator {
                     format!("delete_{snake_case_name}").as_str(),
                     Span::mixed_site(),
                 );
-                let index = self
-                    .columns
-                    .indexes
-                    .values()
-                    .find(|idx| idx.field.to_string() == op.by.to_string());
-                let type_ = self.columns.columns_map.get(&op.by).unwrap();
-                if let Some(index) = index {
+                let returning_ident = Ident::new(
+                    format!("delete_{snake_case_name}_returning").as_str(),
+                    Span::mixed_site(),
+                );
+                let count_ident = Ident::new(
+                    format!("delete_{snake_case_name}_count").as_str(),
+                    Span::mixed_site(),
+                );
+                let returning = DeletedRows::Returning(row_ident);
+                let count = DeletedRows::Count;
+                // Rows are deleted only if they still match `by` when they
+                // are locked, as they could be changed meanwhile.
+                let condition = Self::gen_by_condition(&op.by);
+                let (type_, returning, count) = if let Some(index) = self.get_by_index(&op.by) {
+                    let type_ = self.gen_by_type(&op.by)?;
                     let index_name = &index.name;
-
-                    if index.is_unique {
-                        Self::gen_unique_delete(&type_, &method_ident, index_name)
+                    let pks = if index.is_unique {
+                        quote! {
+                            TableIndex::peek(&self.0.indexes.#index_name, &by)
+                                .and_then(|link| self.0.data.select(link).ok())
+                                .map(|row| row.get_primary_key())
+                                .into_iter()
+                                .collect::<Vec<_>>()
+                        }
                     } else {
-                        Self::gen_non_unique_delete(&type_, &method_ident, index_name)
-                    }
+                        quote! {
+                            TableIndex::peek(&self.0.indexes.#index_name, &by)
+                                .map(|links| {
+                                    links
+                                        .iter()
+                                        .filter_map(|link| self.0.data.select(*link.as_ref()).ok())
+                                        .map(|row| row.get_primary_key())
+                                        .collect::<Vec<_>>()
+                                })
+                                .unwrap_or_default()
+                        }
+                    };
+                    (
+                        type_.clone(),
+                        Self::gen_checked_delete(&pks, &condition, &type_, &returning, &returning_ident),
+                        Self::gen_checked_delete(&pks, &condition, &type_, &count, &count_ident),
+                    )
+                } else if self.is_by_primary_key(&op.by) {
+                    let pk_ident = &self.pk.as_ref().unwrap().ident;
+                    let type_ = quote! { #pk_ident };
+                    (
+                        type_.clone(),
+                        Self::gen_pk_delete(&type_, &returning, &returning_ident),
+                        Self::gen_pk_delete(&type_, &count, &count_ident),
+                    )
                 } else {
-                    Self::gen_brute_force_delete_field(&op.by, &type_, &method_ident)
-                }
+                    let type_ = self.gen_by_type(&op.by)?;
+                    let pks = quote! {
+                        {
+                            let pks = std::cell::RefCell::new(vec![]);
+                            self.iter_with(|row| {
+                                if #condition {
+                                    pks.borrow_mut().push(row.get_primary_key());
+                                }
+                                core::result::Result::Ok(())
+                            })?;
+                            pks.into_inner()
+                        }
+                    };
+                    (
+                        type_.clone(),
+                        Self::gen_checked_delete(&pks, &condition, &type_, &returning, &returning_ident),
+                        Self::gen_checked_delete(&pks, &condition, &type_, &count, &count_ident),
+                    )
+                };
+
+                Ok(quote! {
+                    pub async fn #method_ident(&self, by: #type_) -> core::result::Result<(), WorkTableError> {
+                        self.#count_ident(by).await?;
+                        core::result::Result::Ok(())
+                    }
+
+                    #count
+                    #returning
+                })
             })
-            .collect::<Vec<_>>();
+            .collect::<syn::Result<Vec<_>>>()?;
 
-        quote! {
+        Ok(quote! {
             #(#defs)*
-        }
+        })
     }
 
-    fn gen_brute_force_delete_field(
-        field: &Ident,
+    /// Generates deletion of the rows with primary keys collected by `pks`
+    /// before any of them is deleted. Row is deleted only if it matches
+    /// `condition` once it's locked, and rows deleted concurrently are
+    /// skipped.
+    fn gen_checked_delete(
+        pks: &TokenStream,
+        condition: &TokenStream,
         type_: &TokenStream,
+        deleted: &DeletedRows,
         name: &Ident,
     ) -> TokenStream {
+        let result_type = deleted.result_type();
+        let init = deleted.init();
+        let delete = deleted.delete(quote! { pk }, condition);
         quote! {
-            pub async fn #name(&self, by: #type_) -> core::result::Result<(), WorkTableError> {
-                self.iter_with_async(|row| {
-                    if row.#field == by {
-                        futures::future::Either::Left(async move {
-                            self.delete(row.id.into()).await
-                        })
-                    } else {
-                        futures::future::Either::Right(async {
-                            Ok(())
-                        })
-                    }
-                }).await?;
-                core::result::Result::Ok(())
+            pub async fn #name(&self, by: #type_) -> core::result::Result<#result_type, WorkTableError> {
+                let mut deleted = #init;
+                for pk in #pks {
+                    #delete
+                }
+                core::result::Result::Ok(deleted)
             }
         }
     }
 
-    fn gen_non_unique_delete(type_: &TokenStream, name: &Ident, index: &Ident) -> TokenStream {
+    fn gen_pk_delete(type_: &TokenStream, deleted: &DeletedRows, name: &Ident) -> TokenStream {
+        let result_type = deleted.result_type();
+        let (delete, found, not_found) = match deleted {
+            DeletedRows::Returning(_) => (
+                quote! { self.delete_returning(by).await },
+                quote! { core::result::Result::Ok(row) => core::result::Result::Ok(vec![row]) },
+                quote! { vec![] },
+            ),
+            DeletedRows::Count => (
+                quote! { self.delete(by).await },
+                quote! { core::result::Result::Ok(()) => core::result::Result::Ok(1) },
+                quote! { 0 },
+            ),
+        };
         quote! {
-            pub async fn #name(&self, by: #type_) -> core::result::Result<(), WorkTableError> {
-                let rows_to_update = TableIndex::peek(&self.0.indexes.#index, &by);
-                if let Some(rows) = rows_to_update {
-                    for link in rows.iter() {
-                        let row = self.0.data.select(*link.as_ref()).map_err(WorkTableError::PagesError)?;
-                        self.delete(row.id.into()).await?;
-                    }
+            pub async fn #name(&self, by: #type_) -> core::result::Result<#result_type, WorkTableError> {
+                match #delete {
+                    #found,
+                    Err(WorkTableError::NotFound) => core::result::Result::Ok(#not_found),
+                    Err(e) => Err(e),
                 }
-                core::result::Result::Ok(())
             }
         }
     }
+}
 
-    fn gen_unique_delete(type_: &TokenStream, name: &Ident, index: &Ident) -> TokenStream {
-        quote! {
-            pub async fn #name(&self, by: #type_) -> core::result::Result<(), WorkTableError> {
-                let row_to_update = TableIndex::peek(&self.0.indexes.#index, &by);
-                if let Some(link) = row_to_update {
-                    let row = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
-                    self.delete(row.id.into()).await?;
+/// What custom delete query returns about the deleted rows.
+enum DeletedRows<'a> {
+    /// Deleted rows of the row type.
+    Returning(&'a Ident),
+    /// Only count of the deleted rows, so they are not collected.
+    Count,
+}
+
+impl DeletedRows<'_> {
+    fn result_type(&self) -> TokenStream {
+        match self {
+            DeletedRows::Returning(row_ident) => quote! { Vec<#row_ident> },
+            DeletedRows::Count => quote! { usize },
+        }
+    }
+
+    fn init(&self) -> TokenStream {
+        match self {
+            DeletedRows::Returning(_) => quote! { vec![] },
+            DeletedRows::Count => quote! { 0 },
+        }
+    }
+
+    /// Generates deletion of the row with `pk` that adds it to the `deleted`
+    /// accumulator if the row matches `condition` once it's locked. Rows that
+    /// are not found are skipped.
+    fn delete(&self, pk: TokenStream, condition: &TokenStream) -> TokenStream {
+        match self {
+            DeletedRows::Returning(_) => quote! {
+                match self.delete_returning_checked(#pk, |row| #condition).await {
+                    core::result::Result::Ok(Some(row)) => deleted.push(row),
+                    core::result::Result::Ok(None) | Err(WorkTableError::NotFound) => {}
+                    Err(e) => return Err(e),
                 }
-                core::result::Result::Ok(())
-            }
+            },
+            DeletedRows::Count => quote! {
+                match self.delete_checked(#pk, |row| #condition).await {
+                    core::result::Result::Ok(true) => deleted += 1,
+                    core::result::Result::Ok(false) | Err(WorkTableError::NotFound) => {}
+                    Err(e) => return Err(e),
+                }
+            },
         }
     }
 }
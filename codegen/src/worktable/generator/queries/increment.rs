@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+use crate::worktable::generator::Generator;
+use crate::worktable::model::Operation;
+
+impl Generator {
+    pub fn gen_query_increment_impl(&mut self) -> syn::Result<TokenStream> {
+        let custom_increments = if let Some(q) = &self.queries {
+            self.gen_custom_increments(q.increments.clone())?
+        } else {
+            quote! {}
+        };
+
+        let table_ident = self.table_name.as_ref().unwrap();
+        Ok(quote! {
+            impl #table_ident {
+                #custom_increments
+            }
+        })
+    }
+
+    fn gen_custom_increments(
+        &self,
+        increments: HashMap<Ident, Operation>,
+    ) -> syn::Result<TokenStream> {
+        let defs = increments
+            .iter()
+            .map(|(name, op)| {
+                let snake_case_name = name
+                    .to_string()
+                    .from_case(Case::Pascal)
+                    .to_case(Case::Snake);
+                // Rows are changed in place, so keys of the indexes would
+                // become stale.
+                if let Some(column) = op.columns.iter().find(|c| self.is_indexed_column(c)) {
+                    return Err(syn::Error::new(
+                        column.span(),
+                        "Increment queries can't change primary key or indexed columns",
+                    ));
+                }
+                let (by_type, link) = if let Some(index) = self.get_by_index(&op.by) {
+                    if !index.is_unique {
+                        return Err(syn::Error::new(
+                            op.name.span(),
+                            "Increment queries are only supported by primary key or unique index",
+                        ));
+                    }
+                    let index_name = &index.name;
+                    let by_ident = Ident::new(format!("{name}By").as_str(), Span::mixed_site());
+                    (
+                        quote! { #by_ident },
+                        quote! { TableIndex::peek(&self.0.indexes.#index_name, &by) },
+                    )
+                } else if self.is_by_primary_key(&op.by) {
+                    let pk_ident = &self.pk.as_ref().unwrap().ident;
+                    (
+                        quote! { #pk_ident },
+                        quote! { TableIndex::peek(&self.0.pk_map, &by) },
+                    )
+                } else {
+                    return Err(syn::Error::new(
+                        op.name.span(),
+                        "Increment queries are only supported by primary key or unique index",
+                    ));
+                };
+
+                Ok(self.gen_increment(snake_case_name, name, &op.columns, by_type, link))
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        Ok(quote! {
+            #(#defs)*
+        })
+    }
+
+    fn is_indexed_column(&self, column: &Ident) -> bool {
+        let column = column.to_string();
+        self.columns
+            .primary_keys
+            .0
+            .iter()
+            .any(|pk| pk.to_string() == column)
+            || self
+                .columns
+                .indexes
+                .values()
+                .any(|idx| idx.field.to_string() == column)
+    }
+
+    fn gen_increment(
+        &self,
+        snake_case_name: String,
+        name: &Ident,
+        idents: &Vec<Ident>,
+        by_type: TokenStream,
+        link: TokenStream,
+    ) -> TokenStream {
+        let query_ident = Ident::new(format!("{name}Query").as_str(), Span::mixed_site());
+        let apply_ident = Ident::new(
+            format!("apply_{snake_case_name}").as_str(),
+            Span::mixed_site(),
+        );
+        let ops = [
+            ("increment", quote! { IncrementOp::Add }),
+            ("decrement", quote! { IncrementOp::Sub }),
+            ("min", quote! { IncrementOp::Min }),
+            ("max", quote! { IncrementOp::Max }),
+        ]
+        .into_iter()
+        .map(|(prefix, op)| {
+            let method_ident = Ident::new(
+                format!("{prefix}_{snake_case_name}").as_str(),
+                Span::mixed_site(),
+            );
+            quote! {
+                pub async fn #method_ident(&self, row: #query_ident, by: #by_type) -> core::result::Result<#query_ident, WorkTableError> {
+                    self.#apply_ident(#op, row, by).await
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+        let check_ident = Ident::new(
+            format!("check_{snake_case_name}_lock").as_str(),
+            Span::mixed_site(),
+        );
+        let lock_ident = Ident::new(
+            format!("lock_{snake_case_name}").as_str(),
+            Span::mixed_site(),
+        );
+        let unlock_ident = Ident::new(
+            format!("unlock_{snake_case_name}").as_str(),
+            Span::mixed_site(),
+        );
+        let row_lock = Self::gen_find_row_lock(
+            link,
+            quote! { archived.#check_ident() },
+            quote! { archived.#lock_ident(op_id); },
+        );
+        let row_unlock =
+            Self::gen_row_unlock(quote! { link }, quote! { archived.#unlock_ident(); });
+        let cleared_check = Self::gen_cleared_check();
+        let row_types = idents
+            .iter()
+            .map(|i| self.columns.columns_map.get(i).unwrap())
+            .collect::<Vec<_>>();
+        let new_values = idents
+            .iter()
+            .zip(row_types)
+            .map(|(i, type_)| {
+                quote! {
+                    let #i = Incrementable::apply(
+                        rkyv::deserialize::<#type_, rkyv::rancor::Error>(&archived.inner.#i)
+                            .map_err(|_| WorkTableError::SerializeError)?,
+                        op,
+                        row.#i.clone(),
+                    )
+                    .ok_or(WorkTableError::Overflow)?;
+                }
+            })
+            .collect::<Vec<_>>();
+        let row_updates = idents
+            .iter()
+            .map(|i| {
+                quote! {
+                    archived.inner.#i = #i.clone().into();
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let save_version = Self::gen_save_version(quote! { link });
+        let wal_begin = self.gen_wal_begin();
+        let wal_update = self.gen_wal_update(quote! { link });
+        let wal_commit = self.gen_wal_commit();
+
+        quote! {
+            pub async fn #apply_ident(&self, op: IncrementOp, row: #query_ident, by: #by_type) -> core::result::Result<#query_ident, WorkTableError> {
+                let mut lock_guard = LockGuard::new(&self.0.lock_map);
+                let op_id = lock_guard.id();
+
+                let link = #row_lock;
+                lock_guard.on_release(link, move || #row_unlock);
+
+                let versions = self.0.versions.begin_write();
+                #cleared_check
+                #wal_begin
+                #save_version
+                let res = unsafe { self.0.data.with_mut_ref(link, |archived| -> core::result::Result<#query_ident, WorkTableError> {
+                    #(#new_values)*
+                    #(#row_updates)*
+
+                    core::result::Result::Ok(#query_ident {
+                        #(#idents,)*
+                    })
+                }).map_err(WorkTableError::PagesError)? };
+                if res.is_ok() {
+                    #wal_update
+                }
+                #wal_commit
+
+                drop(lock_guard);
+
+                res
+            }
+
+            #(#ops)*
+        }
+    }
+}
@@ -5,6 +5,98 @@ use quote::quote;
 use crate::worktable::generator::Generator;
 
 impl Generator {
+    /// Generates taking of the row at `link` by the operation with `op_id`
+    /// lock. `check` returns id of the lock that holds the row and `lock` sets
+    /// row's lock fields, they are run atomically using
+    /// [`LockGuard::acquire_row`]. If row is held by other operation, its lock
+    /// is waited for and row is checked again, so woken operations don't take
+    /// the row together.
+    ///
+    /// [`LockGuard::acquire_row`]: worktable::lock::LockGuard::acquire_row
+    pub fn gen_row_lock(link: TokenStream, check: TokenStream, lock: TokenStream) -> TokenStream {
+        quote! {
+            loop {
+                let id = lock_guard.acquire_row(#link, || unsafe {
+                    self.0.data.with_mut_ref(#link, |archived| {
+                        let id = #check;
+                        if id.is_none() {
+                            #lock
+                        }
+                        id
+                    })
+                })?.map_err(WorkTableError::PagesError)?;
+                match id {
+                    Some(id) => self.0.wait_lock(op_id, id).await?,
+                    None => break,
+                }
+            }
+        }
+    }
+
+    /// Generates finding of the row by `find`, that returns its `Option<Link>`,
+    /// and its taking like [`Generator::gen_row_lock`]. Evaluates to row's
+    /// `Link`. Row is found again together with the check of its lock fields,
+    /// so if it was moved or deleted while operation waited for its lock, it
+    /// is searched again instead of taking its old `Link`. Fails with
+    /// `NotFound` if row is not found.
+    pub fn gen_find_row_lock(
+        find: TokenStream,
+        check: TokenStream,
+        lock: TokenStream,
+    ) -> TokenStream {
+        quote! {
+            'row: loop {
+                let link = #find.ok_or(WorkTableError::NotFound)?;
+                loop {
+                    let taken = lock_guard.acquire_row(link, || {
+                        if #find != Some(link) {
+                            return core::result::Result::Ok(None);
+                        }
+                        unsafe {
+                            self.0.data.with_mut_ref(link, |archived| {
+                                let id = #check;
+                                if id.is_none() {
+                                    #lock
+                                }
+                                Some(id)
+                            })
+                        }
+                    })?.map_err(WorkTableError::PagesError)?;
+                    match taken {
+                        Some(Some(id)) => self.0.wait_lock(op_id, id).await?,
+                        Some(None) => break 'row link,
+                        None => continue 'row,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generates clearing of the row's lock fields by `unlock`. It's used in
+    /// operation's `LockGuard` release callback, that is run atomically with
+    /// the checks of the row's lock fields by other operations.
+    pub fn gen_row_unlock(link: TokenStream, unlock: TokenStream) -> TokenStream {
+        quote! {
+            unsafe {
+                let _ = self.0.data.with_mut_ref(#link, |archived| {
+                    #unlock
+                });
+            }
+        }
+    }
+
+    /// Generates check that row's lock was not removed by `truncate` before
+    /// operation started its write. It must be placed right after
+    /// `versions.begin_write`, as `truncate` waits for the writes in progress,
+    /// so rows taken by the operation can't be removed after the check.
+    pub fn gen_cleared_check() -> TokenStream {
+        quote! {
+            if lock_guard.is_cleared() {
+                return Err(WorkTableError::NotFound);
+            }
+        }
+    }
+
     pub fn gen_query_locks_impl(&mut self) -> syn::Result<TokenStream> {
         if let Some(q) = &self.queries {
             let wrapper_name = self.wrapper_name.as_ref().unwrap();
@@ -15,9 +107,11 @@ impl Generator {
 
             let fns = q
                 .updates
-                .keys()
-                .map(|name| {
-                    let snake_case_name = name
+                .values()
+                .chain(q.increments.values())
+                .map(|op| {
+                    let snake_case_name = op
+                        .name
                         .to_string()
                         .from_case(Case::Pascal)
                         .to_case(Case::Snake);
@@ -26,10 +120,7 @@ impl Generator {
                         format!("check_{snake_case_name}_lock").as_str(),
                         Span::mixed_site(),
                     );
-                    let checks = q
-                        .updates
-                        .get(name)
-                        .expect("exists")
+                    let checks = op
                         .columns
                         .iter()
                         .map(|col| {
@@ -47,10 +138,7 @@ impl Generator {
                         format!("lock_{snake_case_name}").as_str(),
                         Span::mixed_site(),
                     );
-                    let locks = q
-                        .updates
-                        .get(name)
-                        .expect("exists")
+                    let locks = op
                         .columns
                         .iter()
                         .map(|col| {
@@ -66,17 +154,14 @@ impl Generator {
                         format!("unlock_{snake_case_name}").as_str(),
                         Span::mixed_site(),
                     );
-                    let unlocks = q
-                        .updates
-                        .get(name)
-                        .expect("exists")
+                    let unlocks = op
                         .columns
                         .iter()
                         .map(|col| {
                             let col =
                                 Ident::new(format!("{}_lock", col).as_str(), Span::mixed_site());
                             quote! {
-                                self.#col = 0u16.into();
+                                self.#col = 0u64.into();
                             }
                         })
                         .collect::<Vec<_>>();
@@ -85,10 +170,7 @@ impl Generator {
                         format!("verify_{snake_case_name}_lock").as_str(),
                         Span::mixed_site(),
                     );
-                    let verify = q
-                        .updates
-                        .get(name)
-                        .expect("exists")
+                    let verify = op
                         .columns
                         .iter()
                         .map(|col| {
@@ -103,7 +185,7 @@ impl Generator {
                         .collect::<Vec<_>>();
 
                     quote! {
-                        pub fn #check_ident(&self) -> Option<u16> {
+                        pub fn #check_ident(&self) -> Option<u64> {
                             if self.lock != 0 {
                                 return Some(self.lock.into());
                             }
@@ -111,7 +193,7 @@ impl Generator {
                             None
                         }
 
-                        pub unsafe fn #lock_ident(&mut self, id: u16) {
+                        pub unsafe fn #lock_ident(&mut self, id: u64) {
                             #(#locks)*
                         }
 
@@ -119,7 +201,7 @@ impl Generator {
                             #(#unlocks)*
                         }
 
-                        pub fn #verify_ident(&self, id: u16) -> bool {
+                        pub fn #verify_ident(&self, id: u64) -> bool {
                             #(#verify)*
                             true
                         }
@@ -1,5 +1,9 @@
+mod blocking;
+mod by;
 mod delete;
+mod increment;
 mod locks;
 mod select;
 pub mod r#type;
 mod update;
+mod version;
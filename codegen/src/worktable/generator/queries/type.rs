@@ -6,15 +6,15 @@ use crate::worktable::generator::Generator;
 impl Generator {
     pub fn gen_result_types_def(&mut self) -> syn::Result<TokenStream> {
         if let Some(queries) = &self.queries {
+            let serde_derive = self.gen_serde_derive();
             let query_defs = queries
                 .updates
-                .keys()
-                .map(|v| {
-                    let ident = Ident::new(format!("{}Query", v).as_str(), Span::mixed_site());
-                    let rows = queries
-                        .updates
-                        .get(v)
-                        .expect("exists")
+                .values()
+                .chain(queries.increments.values())
+                .map(|op| {
+                    let ident =
+                        Ident::new(format!("{}Query", op.name).as_str(), Span::mixed_site());
+                    let rows = op
                         .columns
                         .iter()
                         .map(|i| {
@@ -32,6 +32,7 @@ impl Generator {
 
                     Ok::<_, syn::Error>(quote! {
                         #[derive(rkyv::Archive, Debug, rkyv::Deserialize, Clone, rkyv::Serialize)]
+                        #serde_derive
                         #[repr(C)]
                         pub struct #ident {
                             #(#rows)*
@@ -43,13 +44,15 @@ impl Generator {
             let by_defs = queries
                 .updates
                 .values()
+                .chain(queries.increments.values())
                 .map(|op| {
                     let ident = Ident::new(format!("{}By", &op.name).as_str(), Span::mixed_site());
-                    let field_type = self
-                        .columns
-                        .columns_map
-                        .get(&op.by)
-                        .ok_or(syn::Error::new(op.by.span(), "Unexpected column name"))?;
+                    let field_type = if self.is_by_primary_key(&op.by) {
+                        let pk_ident = &self.pk.as_ref().unwrap().ident;
+                        quote! { #pk_ident }
+                    } else {
+                        self.gen_by_type(&op.by)?
+                    };
 
                     Ok::<_, syn::Error>(quote! {
                         pub type #ident = #field_type;
@@ -10,27 +10,84 @@ use crate::worktable::model::Operation;
 impl Generator {
     pub fn gen_query_update_impl(&mut self) -> syn::Result<TokenStream> {
         let custom_updates = if let Some(q) = &self.queries {
-            let custom_updates = self.gen_custom_updates(q.updates.clone());
+            let pk_columns = &self.columns.primary_keys.0;
+            if let Some(column) = q
+                .updates
+                .values()
+                .flat_map(|op| op.columns.iter())
+                .find(|c| pk_columns.contains(c))
+            {
+                return Err(syn::Error::new(
+                    column.span(),
+                    "Update queries can't change primary key columns, `update_pk` should be used instead",
+                ));
+            }
+            let custom_updates = self.gen_custom_updates(q.updates.clone(), false);
+            let custom_updates_returning = self.gen_custom_updates(q.updates.clone(), true);
 
             quote! {
                 #custom_updates
+                #custom_updates_returning
             }
         } else {
             quote! {}
         };
-        let full_row_update = self.gen_full_row_update();
+        let full_row_update = self.gen_full_row_update(false);
+        let full_row_update_returning = self.gen_full_row_update(true);
+        let pk_update = self.gen_pk_change_update();
 
         let table_ident = self.table_name.as_ref().unwrap();
         Ok(quote! {
             impl #table_ident {
                 #full_row_update
+                #full_row_update_returning
+                #pk_update
                 #custom_updates
             }
         })
     }
 
-    fn gen_full_row_update(&mut self) -> TokenStream {
+    /// Generates parts of the update method that differ for the plain and
+    /// `_returning` variants: method name suffix, return type, row image
+    /// selects and returned value.
+    fn gen_returning_parts(
+        &self,
+        returning: bool,
+    ) -> (
+        &'static str,
+        TokenStream,
+        TokenStream,
+        TokenStream,
+        TokenStream,
+    ) {
+        let row_ident = self.row_name.as_ref().unwrap();
+        if returning {
+            (
+                "_returning",
+                quote! { (#row_ident, #row_ident) },
+                quote! {
+                    let old_row = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
+                },
+                quote! {
+                    let new_row = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
+                },
+                quote! { (old_row, new_row) },
+            )
+        } else {
+            ("", quote! { () }, quote! {}, quote! {}, quote! { () })
+        }
+    }
+
+    fn gen_full_row_update(&mut self, returning: bool) -> TokenStream {
         let row_ident = self.row_name.as_ref().unwrap();
+        let (suffix, res_type, select_old, select_new, res) = self.gen_returning_parts(returning);
+        let save_version = Self::gen_save_version(quote! { link });
+        let (wal_begin, wal_update, wal_commit) = (
+            self.gen_wal_begin(),
+            self.gen_wal_update(quote! { link }),
+            self.gen_wal_commit(),
+        );
+        let method_ident = Ident::new(format!("update{suffix}").as_str(), Span::mixed_site());
         let row_updates = self
             .columns
             .columns_map
@@ -41,44 +98,138 @@ impl Generator {
                 }
             })
             .collect::<Vec<_>>();
+        let row_lock = Self::gen_find_row_lock(
+            quote! { TableIndex::peek(&self.0.pk_map, &pk) },
+            quote! { archived.is_locked() },
+            quote! { archived.lock = op_id.into(); },
+        );
+        let row_unlock =
+            Self::gen_row_unlock(quote! { link }, quote! { archived.lock = 0u64.into(); });
+        let cleared_check = Self::gen_cleared_check();
 
         quote! {
-            pub async fn update(&self, row: #row_ident) -> core::result::Result<(), WorkTableError> {
+            pub async fn #method_ident(&self, row: #row_ident) -> core::result::Result<#res_type, WorkTableError> {
                 let pk = row.get_primary_key();
-                let op_id = self.0.lock_map.next_id();
-                let lock = std::sync::Arc::new(Lock::new());
-                self.0.lock_map.insert(op_id.into(), lock.clone());
+                let mut lock_guard = LockGuard::new(&self.0.lock_map);
+                let op_id = lock_guard.id();
 
                 let mut bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&row).map_err(|_| WorkTableError::SerializeError)?;
                 let mut row = unsafe { rkyv::access_unchecked_mut::<<#row_ident as rkyv::Archive>::Archived>(&mut bytes[..]).unseal_unchecked() };
-                let link = TableIndex::peek(&self.0.pk_map, &pk).ok_or(WorkTableError::NotFound)?;
-                let id = self.0.data.with_ref(link, |archived| {
-                    archived.is_locked()
-                }).map_err(WorkTableError::PagesError)?;
-                if let Some(id) = id {
-                    if let Some(lock) = self.0.lock_map.get(&(id.into())) {
-                        lock.as_ref().await
-                    }
-                }
-                unsafe { self.0.data.with_mut_ref(link, |archived| {
-                    archived.lock = op_id.into();
-                }).map_err(WorkTableError::PagesError)? };
+                // Row's primary key could be changed by `update_pk` while
+                // this operation waited for the lock, then it's not found.
+                let link = #row_lock;
+                lock_guard.on_release(link, move || #row_unlock);
+                let versions = self.0.versions.begin_write();
+                #cleared_check
+                #wal_begin
+                #save_version
+                #select_old
                 unsafe { self.0.data.with_mut_ref(link, move |archived| {
                     #(#row_updates)*
                 }).map_err(WorkTableError::PagesError)? };
-                unsafe { self.0.data.with_mut_ref(link, |archived| {
-                    unsafe {
-                        archived.lock = 0u16.into();
+                #select_new
+                #wal_update
+                #wal_commit
+                drop(lock_guard);
+                core::result::Result::Ok(#res)
+            }
+        }
+    }
+
+    /// Generates `update_pk` method that changes row's primary key using
+    /// [`WorkTable::rekey_row`].
+    ///
+    /// [`WorkTable::rekey_row`]: worktable::WorkTable::rekey_row
+    fn gen_pk_change_update(&self) -> TokenStream {
+        let row_ident = self.row_name.as_ref().unwrap();
+        let pk = self.pk.as_ref().unwrap();
+        let pk_ident = &pk.ident;
+        let pk_updates = self
+            .columns
+            .primary_keys
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let i = syn::Index::from(i);
+                quote! {
+                    new_row.#field = new.#i.clone();
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let wal_begin = self.gen_wal_begin();
+        let wal_commit = self.gen_wal_commit();
+        let wal_pk_change = if self.is_persist {
+            quote! {
+                if let (core::result::Result::Ok(_), Some(wal)) = (&res, wal.as_mut()) {
+                    let row = self.0.select(new.clone()).ok_or(WorkTableError::NotFound)?;
+                    wal.push(WalOperation::Delete, &old)?;
+                    wal.push(WalOperation::Insert, &row)?;
+                }
+            }
+        } else {
+            quote! {}
+        };
+        let row_lock = Self::gen_find_row_lock(
+            quote! { TableIndex::peek(&self.0.pk_map, &old) },
+            quote! { archived.is_locked() },
+            quote! { archived.lock = op_id.into(); },
+        );
+        let row_unlock =
+            Self::gen_row_unlock(quote! { link }, quote! { archived.lock = 0u64.into(); });
+        let cleared_check = Self::gen_cleared_check();
+
+        quote! {
+            pub async fn update_pk(&self, old: #pk_ident, new: #pk_ident) -> core::result::Result<(), WorkTableError> {
+                if old == new {
+                    return TableIndex::peek(&self.0.pk_map, &old)
+                        .map(|_| ())
+                        .ok_or(WorkTableError::NotFound);
+                }
+                let mut lock_guard = LockGuard::new(&self.0.lock_map);
+                let op_id = lock_guard.id();
+
+                let link = #row_lock;
+                lock_guard.on_release(link, move || #row_unlock);
+
+                let versions = self.0.versions.begin_write();
+                #cleared_check
+                #wal_begin
+                let res = (|| -> core::result::Result<(), WorkTableError> {
+                    let row = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
+                    let mut new_row = row.clone();
+                    #(#pk_updates)*
+
+                    versions.record_with(|| {
+                        TableIndex::peek(&self.0.pk_map, &new).is_none().then(|| (new.clone(), None))
+                    });
+                    versions.record_with(|| Some((old.clone(), Some(row.clone()))));
+
+                    let moved = self.0.rekey_row::<{ #row_ident::ROW_SIZE }>(&old, new.clone(), row, new_row, link)?;
+                    if moved {
+                        lock_guard.release();
+                        self.0.data.delete(link).map_err(WorkTableError::PagesError)?;
                     }
-                }).map_err(WorkTableError::PagesError)? };
-                lock.unlock();
-                self.0.lock_map.remove(&op_id.into());
-                core::result::Result::Ok(())
+
+                    core::result::Result::Ok(())
+                })();
+                #wal_pk_change
+                #wal_commit
+
+                drop(versions);
+                drop(lock_guard);
+
+                res
             }
         }
     }
 
-    fn gen_custom_updates(&mut self, updates: HashMap<Ident, Operation>) -> TokenStream {
+    fn gen_custom_updates(
+        &self,
+        updates: HashMap<Ident, Operation>,
+        returning: bool,
+    ) -> TokenStream {
         let defs = updates
             .iter()
             .map(|(name, op)| {
@@ -86,33 +237,65 @@ impl Generator {
                     .to_string()
                     .from_case(Case::Pascal)
                     .to_case(Case::Snake);
-                let index = self
-                    .columns
-                    .indexes
-                    .values()
-                    .find(|idx| idx.field.to_string() == op.by.to_string());
-
                 let idents = &op.columns;
-                if let Some(index) = index {
+                if let Some(index) = self.get_by_index(&op.by) {
                     let index_name = &index.name;
 
                     if index.is_unique {
-                        self.gen_unique_update(snake_case_name, name, index_name, idents)
+                        self.gen_unique_update(snake_case_name, name, index_name, idents, returning)
                     } else {
-                        self.gen_non_unique_update(snake_case_name, name, index_name, idents)
+                        let links = quote! {
+                            TableIndex::peek(&self.0.indexes.#index_name, &by)
+                                .ok_or(WorkTableError::NotFound)?
+                                .iter()
+                                .map(|link| *link.as_ref())
+                                .collect::<Vec<_>>()
+                        };
+                        // Link from the index can be freed and reused by
+                        // other row while update waits for its lock.
+                        let condition = Self::gen_by_condition(&op.by);
+                        self.gen_non_unique_update(
+                            snake_case_name,
+                            name,
+                            links,
+                            condition,
+                            idents,
+                            returning,
+                        )
                     }
+                } else if self.is_by_primary_key(&op.by) {
+                    self.gen_pk_update(snake_case_name, name, idents, returning)
                 } else {
-                    if self.columns.primary_keys.0.len() == 1 {
-                        if self.columns.primary_keys.0.first().unwrap().to_string()
-                            == op.by.to_string()
+                    let condition = Self::gen_by_condition(&op.by);
+                    let links = quote! {
                         {
-                            self.gen_pk_update(snake_case_name, name, idents)
-                        } else {
-                            todo!()
+                            let pks = std::cell::RefCell::new(vec![]);
+                            self.iter_with(|row| {
+                                if #condition {
+                                    pks.borrow_mut().push(row.get_primary_key());
+                                }
+                                core::result::Result::Ok(())
+                            })?;
+                            let guard = Guard::new();
+                            let links = pks
+                                .into_inner()
+                                .into_iter()
+                                .filter_map(|pk| TableIndex::peek(&self.0.pk_map, &pk))
+                                .collect::<Vec<_>>();
+                            if links.is_empty() {
+                                return Err(WorkTableError::NotFound);
+                            }
+                            links
                         }
-                    } else {
-                        todo!()
-                    }
+                    };
+                    self.gen_non_unique_update(
+                        snake_case_name,
+                        name,
+                        links,
+                        condition,
+                        idents,
+                        returning,
+                    )
                 }
             })
             .collect::<Vec<_>>();
@@ -127,10 +310,18 @@ impl Generator {
         snake_case_name: String,
         name: &Ident,
         idents: &Vec<Ident>,
+        returning: bool,
     ) -> TokenStream {
         let pk_ident = &self.pk.as_ref().unwrap().ident;
+        let (suffix, res_type, select_old, select_new, res) = self.gen_returning_parts(returning);
+        let save_version = Self::gen_save_version(quote! { link });
+        let (wal_begin, wal_update, wal_commit) = (
+            self.gen_wal_begin(),
+            self.gen_wal_update(quote! { link }),
+            self.gen_wal_commit(),
+        );
         let method_ident = Ident::new(
-            format!("update_{snake_case_name}").as_str(),
+            format!("update_{snake_case_name}{suffix}").as_str(),
             Span::mixed_site(),
         );
 
@@ -148,10 +339,14 @@ impl Generator {
             format!("unlock_{snake_case_name}").as_str(),
             Span::mixed_site(),
         );
-        let verify_ident = Ident::new(
-            format!("verify_{snake_case_name}_lock").as_str(),
-            Span::mixed_site(),
+        let row_lock = Self::gen_find_row_lock(
+            quote! { TableIndex::peek(&self.0.pk_map, &by) },
+            quote! { archived.#check_ident() },
+            quote! { archived.#lock_ident(op_id); },
         );
+        let row_unlock =
+            Self::gen_row_unlock(quote! { link }, quote! { archived.#unlock_ident(); });
+        let cleared_check = Self::gen_cleared_check();
         let row_updates = idents
             .iter()
             .map(|i| {
@@ -162,60 +357,82 @@ impl Generator {
             .collect::<Vec<_>>();
 
         quote! {
-            pub async fn #method_ident(&self, row: #query_ident, by: #pk_ident) -> core::result::Result<(), WorkTableError> {
-                let op_id = self.0.lock_map.next_id();
-                let lock = std::sync::Arc::new(Lock::new());
-
-                self.0.lock_map.insert(op_id.into(), lock.clone());
+            pub async fn #method_ident(&self, row: #query_ident, by: #pk_ident) -> core::result::Result<#res_type, WorkTableError> {
+                let mut lock_guard = LockGuard::new(&self.0.lock_map);
+                let op_id = lock_guard.id();
 
                 let mut bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&row).map_err(|_| WorkTableError::SerializeError)?;
                 let mut row = unsafe { rkyv::access_unchecked_mut::<<#query_ident as rkyv::Archive>::Archived>(&mut bytes[..]).unseal_unchecked() };
-                let link = {
-                    let guard = Guard::new();
-                    TableIndex::peek(&self.0.pk_map, &by).ok_or(WorkTableError::NotFound)?
-                };
-                let id = self.0.data.with_ref(link, |archived| {
-                    archived.#check_ident()
-                }).map_err(WorkTableError::PagesError)?;
-                if let Some(id) = id {
-                    if let Some(lock) = self.0.lock_map.get(&(id.into())) {
-                        lock.as_ref().await
-                    }
-                }
-                unsafe { self.0.data.with_mut_ref(link, |archived| {
-                    while !archived.#verify_ident(op_id) {
-                        unsafe {
-                            archived.#lock_ident(op_id)
-                        }
-                    }
-                }).map_err(WorkTableError::PagesError)? };
-
+                let link = #row_lock;
+                lock_guard.on_release(link, move || #row_unlock);
+
+                let versions = self.0.versions.begin_write();
+                #cleared_check
+                #wal_begin
+                #save_version
+                #select_old
                 unsafe { self.0.data.with_mut_ref(link, |archived| {
                     #(#row_updates)*
                 }).map_err(WorkTableError::PagesError)? };
+                #select_new
+                #wal_update
+                #wal_commit
 
-                unsafe { self.0.data.with_mut_ref(link, |archived| {
-                    unsafe {
-                        archived.#unlock_ident()
-                    }
-                }).map_err(WorkTableError::PagesError)? };
-                lock.unlock();
-                self.0.lock_map.remove(&op_id.into());
+                drop(lock_guard);
 
-                core::result::Result::Ok(())
+                core::result::Result::Ok(#res)
             }
         }
     }
 
+    /// Generates update of the rows with `links`. Rows are found again after
+    /// they are locked: row is updated only if its `Link` is still in the
+    /// primary index and it matches `condition`, so rows changed, moved or
+    /// deleted meanwhile by other operations are not updated.
     fn gen_non_unique_update(
         &self,
         snake_case_name: String,
         name: &Ident,
-        index: &Ident,
+        links: TokenStream,
+        condition: TokenStream,
         idents: &Vec<Ident>,
+        returning: bool,
     ) -> TokenStream {
+        let row_ident = self.row_name.as_ref().unwrap();
+        let save_version = Self::gen_save_version(quote! { *link });
+        let (wal_begin, wal_update, wal_commit) = (
+            self.gen_wal_begin(),
+            self.gen_wal_update(quote! { *link }),
+            self.gen_wal_commit(),
+        );
+        let (suffix, res_type, rows_init, select_old, select_new, res) = if returning {
+            (
+                "_returning",
+                quote! { Vec<(#row_ident, #row_ident)> },
+                quote! {
+                    let mut old_rows = vec![];
+                    let mut new_rows = vec![];
+                },
+                quote! {
+                    old_rows.push(self.0.data.select(*link).map_err(WorkTableError::PagesError)?);
+                },
+                quote! {
+                    new_rows.push(self.0.data.select(*link).map_err(WorkTableError::PagesError)?);
+                },
+                quote! { old_rows.into_iter().zip(new_rows).collect() },
+            )
+        } else {
+            (
+                "",
+                quote! { () },
+                quote! {},
+                quote! {},
+                quote! {},
+                quote! { () },
+            )
+        };
         let method_ident = Ident::new(
-            format!("update_{snake_case_name}").as_str(),
+            format!("update_{snake_case_name}{suffix}").as_str(),
             Span::mixed_site(),
         );
 
@@ -234,10 +451,14 @@ impl Generator {
             format!("unlock_{snake_case_name}").as_str(),
             Span::mixed_site(),
         );
-        let verify_ident = Ident::new(
-            format!("verify_{snake_case_name}_lock").as_str(),
-            Span::mixed_site(),
+        let row_lock = Self::gen_row_lock(
+            quote! { *link },
+            quote! { archived.#check_ident() },
+            quote! { archived.#lock_ident(op_id); },
         );
+        let row_unlock =
+            Self::gen_row_unlock(quote! { *link }, quote! { archived.#unlock_ident(); });
+        let cleared_check = Self::gen_cleared_check();
         let row_updates = idents
             .iter()
             .map(|i| {
@@ -246,52 +467,59 @@ impl Generator {
                 }
             })
             .collect::<Vec<_>>();
+        let recheck = quote! {
+            let rows_to_update = rows_to_update
+                .iter()
+                .copied()
+                .filter(|link| {
+                    let Ok(row) = self.0.data.select(*link) else {
+                        return false;
+                    };
+                    let pk = row.get_primary_key();
+                    TableIndex::peek(&self.0.pk_map, &pk) == Some(*link) && #condition
+                })
+                .collect::<Vec<_>>();
+            if rows_to_update.is_empty() {
+                return Err(WorkTableError::NotFound);
+            }
+        };
 
         quote! {
-            pub async fn #method_ident(&self, row: #query_ident, by: #by_ident) -> core::result::Result<(), WorkTableError> {
-                let op_id = self.0.lock_map.next_id();
-                let lock = std::sync::Arc::new(Lock::new());
+            pub async fn #method_ident(&self, row: #query_ident, by: #by_ident) -> core::result::Result<#res_type, WorkTableError> {
+                let mut rows_to_update = #links;
+                // Rows are always locked in `Link` order, so operations that
+                // lock overlapping rows can't wait for each other.
+                rows_to_update.sort();
 
-                self.0.lock_map.insert(op_id.into(), lock.clone());
+                let mut lock_guard = LockGuard::new(&self.0.lock_map);
+                let op_id = lock_guard.id();
 
-                let rows_to_update = TableIndex::peek(&self.0.indexes.#index, &by).ok_or(WorkTableError::NotFound)?;
                 for link in rows_to_update.iter() {
-                    let id = self.0.data.with_ref(*link.as_ref(), |archived| {
-                        archived.#check_ident()
-                    }).map_err(WorkTableError::PagesError)?;
-                    if let Some(id) = id {
-                        if let Some(lock) = self.0.lock_map.get(&(id.into())) {
-                            lock.as_ref().await
-                        }
-                    }
-                    unsafe { self.0.data.with_mut_ref(*link.as_ref(), |archived| {
-                        while !archived.#verify_ident(op_id) {
-                            unsafe {
-                                archived.#lock_ident(op_id)
-                            }
-                        }
-                    }).map_err(WorkTableError::PagesError)? };
+                    #row_lock
+                    lock_guard.on_release(*link, move || #row_unlock);
                 }
+                #recheck
 
+                #rows_init
+                let versions = self.0.versions.begin_write();
+                #cleared_check
+                #wal_begin
                 for link in rows_to_update.iter() {
                     let mut bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&row).map_err(|_| WorkTableError::SerializeError)?;
                     let mut row = unsafe { rkyv::access_unchecked_mut::<<#query_ident as rkyv::Archive>::Archived>(&mut bytes[..]).unseal_unchecked() };
-                    unsafe { self.0.data.with_mut_ref(*link.as_ref(), |archived| {
+                    #save_version
+                    #select_old
+                    unsafe { self.0.data.with_mut_ref(*link, |archived| {
                         #(#row_updates)*
                     }).map_err(WorkTableError::PagesError)? };
+                    #select_new
+                    #wal_update
                 }
+                #wal_commit
 
-                for link in rows_to_update.iter() {
-                    unsafe { self.0.data.with_mut_ref(*link.as_ref(), |archived| {
-                        unsafe {
-                            archived.#unlock_ident()
-                        }
-                    }).map_err(WorkTableError::PagesError)? };
-                }
-                lock.unlock();
-                self.0.lock_map.remove(&op_id.into());
+                drop(lock_guard);
 
-                core::result::Result::Ok(())
+                core::result::Result::Ok(#res)
             }
         }
     }
@@ -302,9 +530,17 @@ impl Generator {
         name: &Ident,
         index: &Ident,
         idents: &Vec<Ident>,
+        returning: bool,
     ) -> TokenStream {
+        let (suffix, res_type, select_old, select_new, res) = self.gen_returning_parts(returning);
+        let save_version = Self::gen_save_version(quote! { link });
+        let (wal_begin, wal_update, wal_commit) = (
+            self.gen_wal_begin(),
+            self.gen_wal_update(quote! { link }),
+            self.gen_wal_commit(),
+        );
         let method_ident = Ident::new(
-            format!("update_{snake_case_name}").as_str(),
+            format!("update_{snake_case_name}{suffix}").as_str(),
             Span::mixed_site(),
         );
 
@@ -323,10 +559,14 @@ impl Generator {
             format!("unlock_{snake_case_name}").as_str(),
             Span::mixed_site(),
         );
-        let verify_ident = Ident::new(
-            format!("verify_{snake_case_name}_lock").as_str(),
-            Span::mixed_site(),
+        let row_lock = Self::gen_find_row_lock(
+            quote! { TableIndex::peek(&self.0.indexes.#index, &by) },
+            quote! { archived.#check_ident() },
+            quote! { archived.#lock_ident(op_id); },
         );
+        let row_unlock =
+            Self::gen_row_unlock(quote! { link }, quote! { archived.#unlock_ident(); });
+        let cleared_check = Self::gen_cleared_check();
         let row_updates = idents
             .iter()
             .map(|i| {
@@ -337,44 +577,30 @@ impl Generator {
             .collect::<Vec<_>>();
 
         quote! {
-            pub async fn #method_ident(&self, row: #query_ident, by: #by_ident) -> core::result::Result<(), WorkTableError> {
-                let op_id = self.0.lock_map.next_id();
-                let lock = std::sync::Arc::new(Lock::new());
-
-                self.0.lock_map.insert(op_id.into(), lock.clone());
+            pub async fn #method_ident(&self, row: #query_ident, by: #by_ident) -> core::result::Result<#res_type, WorkTableError> {
+                let mut lock_guard = LockGuard::new(&self.0.lock_map);
+                let op_id = lock_guard.id();
 
                 let mut bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&row).map_err(|_| WorkTableError::SerializeError)?;
                 let mut row = unsafe { rkyv::access_unchecked_mut::<<#query_ident as rkyv::Archive>::Archived>(&mut bytes[..]).unseal_unchecked() };
-                let link = TableIndex::peek(&self.0.indexes.#index, &by).ok_or(WorkTableError::NotFound)?;
-                let id = self.0.data.with_ref(link, |archived| {
-                    archived.#check_ident()
-                }).map_err(WorkTableError::PagesError)?;
-                if let Some(id) = id {
-                    if let Some(lock) = self.0.lock_map.get(&(id.into())) {
-                        lock.as_ref().await
-                    }
-                }
-                unsafe { self.0.data.with_mut_ref(link, |archived| {
-                    while !archived.#verify_ident(op_id) {
-                        unsafe {
-                            archived.#lock_ident(op_id)
-                        }
-                    }
-                }).map_err(WorkTableError::PagesError)? };
-
+                let link = #row_lock;
+                lock_guard.on_release(link, move || #row_unlock);
+
+                let versions = self.0.versions.begin_write();
+                #cleared_check
+                #wal_begin
+                #save_version
+                #select_old
                 unsafe { self.0.data.with_mut_ref(link, |archived| {
                     #(#row_updates)*
                 }).map_err(WorkTableError::PagesError)? };
+                #select_new
+                #wal_update
+                #wal_commit
 
-                unsafe { self.0.data.with_mut_ref(link, |archived| {
-                    unsafe {
-                        archived.#unlock_ident()
-                    }
-                }).map_err(WorkTableError::PagesError)? };
-                lock.unlock();
-                self.0.lock_map.remove(&op_id.into());;
+                drop(lock_guard);
 
-                core::result::Result::Ok(())
+                core::result::Result::Ok(#res)
             }
         }
     }
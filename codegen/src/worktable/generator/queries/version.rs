@@ -0,0 +1,16 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::worktable::generator::Generator;
+
+impl Generator {
+    /// Generates saving of the row's current version for the live snapshots.
+    /// Must be placed inside of `versions` write right before row is changed.
+    pub fn gen_save_version(link: TokenStream) -> TokenStream {
+        quote! {
+            versions.record_with(|| {
+                self.0.data.select(#link).ok().map(|row| (row.get_primary_key(), Some(row)))
+            });
+        }
+    }
+}
@@ -19,9 +19,11 @@ impl Generator {
                 self.#pk_field.clone().into()
             }
         } else {
-            let vals = pk
-                .vals
-                .keys()
+            let vals = self
+                .columns
+                .primary_keys
+                .0
+                .iter()
                 .map(|i| {
                     quote! {
                         self.#i.clone()
@@ -52,10 +54,13 @@ impl Generator {
             })
             .collect();
 
+        let serde_derive = self.gen_serde_derive();
+
         self.row_name = Some(ident);
         quote! {
             #[derive(rkyv::Archive, Debug, rkyv::Deserialize, Clone, rkyv::Serialize, PartialEq)]
             #[rkyv(derive(Debug))]
+            #serde_derive
             #[repr(C)]
             #struct_def {
                 #(#rows)*
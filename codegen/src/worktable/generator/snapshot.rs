@@ -0,0 +1,153 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+use crate::worktable::generator::Generator;
+
+impl Generator {
+    /// Generates read view type returned by table's `snapshot` method. It has
+    /// same select methods as table, but they see rows as they were when
+    /// snapshot was created. Rows are resolved one by one, so `select_all`
+    /// scans the whole table. `select_by_*` use the table's index to find rows
+    /// that have the value now and also check all rows changed after snapshot
+    /// was created, so their cost grows with the count of the changes.
+    pub fn gen_snapshot_def(&self) -> syn::Result<TokenStream> {
+        let table_ident = self.table_name.as_ref().unwrap();
+        let row_ident = self.row_name.as_ref().unwrap();
+        let pk_ident = &self.pk.as_ref().unwrap().ident;
+        let snapshot_ident = Ident::new(
+            format!("{}Snapshot", self.name).as_str(),
+            Span::mixed_site(),
+        );
+
+        let select_by = self
+            .columns
+            .indexes
+            .iter()
+            .map(|(i, idx)| {
+                let type_ = self
+                    .columns
+                    .columns_map
+                    .get(i)
+                    .ok_or(syn::Error::new(i.span(), "Row not found"))?;
+                let fn_name = Ident::new(format!("select_by_{i}").as_str(), Span::mixed_site());
+                let index_ident = &idx.name;
+                Ok(if idx.is_unique {
+                    quote! {
+                        pub fn #fn_name(&self, by: #type_) -> Option<#row_ident> {
+                            let link = TableIndex::peek(&self.table.0.indexes.#index_ident, &by);
+                            self.rows_at(link).into_iter().find(|row| row.#i == by)
+                        }
+                    }
+                } else {
+                    quote! {
+                        pub fn #fn_name(&self, by: #type_) -> core::result::Result<SelectResult<#row_ident, #table_ident>, WorkTableError> {
+                            let links = TableIndex::peek(&self.table.0.indexes.#index_ident, &by)
+                                .map(|links| links.iter().map(|l| *l.as_ref()).collect::<Vec<_>>())
+                                .unwrap_or_default();
+                            let rows = self
+                                .rows_at(links)
+                                .into_iter()
+                                .filter(|row| row.#i == by)
+                                .collect::<Vec<_>>();
+                            if rows.is_empty() {
+                                return Err(WorkTableError::NotFound);
+                            }
+                            core::result::Result::Ok(SelectResult::<#row_ident, #table_ident>::new(rows))
+                        }
+                    }
+                })
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        Ok(quote! {
+            pub struct #snapshot_ident<'a> {
+                table: &'a #table_ident,
+                snapshot: Snapshot<'a, #pk_ident, #row_ident>,
+            }
+
+            impl #table_ident {
+                pub fn snapshot(&self) -> #snapshot_ident<'_> {
+                    #snapshot_ident {
+                        table: self,
+                        snapshot: self.0.snapshot(),
+                    }
+                }
+            }
+
+            impl<'a> #snapshot_ident<'a> {
+                pub fn ts(&self) -> u64 {
+                    self.snapshot.ts()
+                }
+
+                pub fn select(&self, pk: #pk_ident) -> Option<#row_ident> {
+                    let live = self.table.select(pk.clone());
+                    self.snapshot.resolve(&pk, live)
+                }
+
+                pub fn rows(&self) -> Vec<#row_ident> {
+                    let live = {
+                        let guard = Guard::new();
+                        TableIndex::iter(&self.table.0.pk_map)
+                            .map(|(k, l)| (k.clone(), *l))
+                            .collect::<Vec<_>>()
+                    };
+                    let mut rows = std::collections::BTreeMap::new();
+                    for (pk, link) in live {
+                        let row = self.table.0.data.select(link).ok();
+                        let row = self.snapshot.resolve(&pk, row);
+                        rows.insert(pk, row);
+                    }
+                    for pk in self.snapshot.changed_keys() {
+                        if !rows.contains_key(&pk) {
+                            let row = self.select(pk.clone());
+                            rows.insert(pk, row);
+                        }
+                    }
+
+                    rows.into_values().flatten().collect()
+                }
+
+                /// Returns rows seen by snapshot among the rows that are at
+                /// `links` now and the rows changed after snapshot was
+                /// created.
+                fn rows_at(&self, links: impl IntoIterator<Item = Link>) -> Vec<#row_ident> {
+                    let mut rows = std::collections::BTreeMap::new();
+                    for link in links {
+                        if let Ok(row) = self.table.0.data.select(link) {
+                            let pk = row.get_primary_key();
+                            let row = self.snapshot.resolve(&pk, Some(row));
+                            rows.insert(pk, row);
+                        }
+                    }
+                    for pk in self.snapshot.changed_keys() {
+                        if !rows.contains_key(&pk) {
+                            let row = self.select(pk.clone());
+                            rows.insert(pk, row);
+                        }
+                    }
+
+                    rows.into_values().flatten().collect()
+                }
+
+                pub fn select_all<'b>(&'b self) -> SelectQueryBuilder<'b, #row_ident, Self> {
+                    SelectQueryBuilder::new(self)
+                }
+
+                pub fn iter_with<F: Fn(#row_ident) -> core::result::Result<(), WorkTableError>>(&self, f: F) -> core::result::Result<(), WorkTableError> {
+                    for row in self.rows() {
+                        f(row)?;
+                    }
+                    core::result::Result::Ok(())
+                }
+
+                #(#select_by)*
+            }
+
+            impl SelectQueryExecutor<'_, #row_ident> for #snapshot_ident<'_> {
+                fn execute(&self, q: SelectQueryBuilder<#row_ident, Self>) -> Result<Vec<#row_ident>, WorkTableError> {
+                    core::result::Result::Ok(SelectResult::<_, #table_ident>::new(self.rows()).with_params(q.params).execute())
+                }
+            }
+        })
+    }
+}
@@ -1,10 +1,9 @@
-use std::collections::HashMap;
-
+use indexmap::IndexMap;
 use proc_macro2::{Ident, Literal, Span, TokenStream};
 use quote::quote;
 
 use crate::worktable::generator::Generator;
-use crate::worktable::model::{GeneratorType, Index};
+use crate::worktable::model::{GeneratorType, Index, WalSync};
 
 impl Generator {
     /// Generates type alias for new [`WorkTable`].
@@ -33,6 +32,8 @@ impl Generator {
         };
 
         let primary_index_type = &self.columns.primary_keys.1;
+        let insert_fns = self.gen_insert_fns();
+        let wal_replay = self.gen_wal_replay();
         let iter_with = Self::gen_iter_with(row_type);
         let iter_with_async = Self::gen_iter_with_async(row_type);
         let select_executor = self.gen_select_executor();
@@ -46,6 +47,40 @@ impl Generator {
             format!("{}_INNER_SIZE", name.to_string().to_uppercase()).as_str(),
             Span::mixed_site(),
         );
+        let lock_timeout_const_name = Ident::new(
+            format!("{}_LOCK_TIMEOUT", name.to_string().to_uppercase()).as_str(),
+            Span::mixed_site(),
+        );
+        let lock_timeout = if let Some(ms) = self.config.as_ref().and_then(|c| c.lock_timeout_ms) {
+            let ms = Literal::u64_unsuffixed(ms);
+            quote! {
+                const #lock_timeout_const_name: Option<std::time::Duration> = Some(std::time::Duration::from_millis(#ms));
+            }
+        } else {
+            quote! {
+                const #lock_timeout_const_name: Option<std::time::Duration> = None;
+            }
+        };
+        let wal_sync = if self.is_persist {
+            let wal_sync_const_name = Ident::new(
+                format!("{}_WAL_SYNC", name.to_string().to_uppercase()).as_str(),
+                Span::mixed_site(),
+            );
+            let policy = match self.config.as_ref().and_then(|c| c.wal_sync) {
+                Some(WalSync::Always) => quote! { Some(WalSyncPolicy::Always) },
+                Some(WalSync::EveryN(n)) => {
+                    let n = Literal::u32_unsuffixed(n);
+                    quote! { Some(WalSyncPolicy::EveryN(#n)) }
+                }
+                Some(WalSync::Never) => quote! { Some(WalSyncPolicy::Never) },
+                None => quote! { None },
+            };
+            quote! {
+                const #wal_sync_const_name: Option<WalSyncPolicy> = #policy;
+            }
+        } else {
+            quote! {}
+        };
         let persist_type_part = if self.is_persist {
             quote! {
                 , std::sync::Arc<DatabaseManager>
@@ -59,6 +94,7 @@ impl Generator {
                     pub fn new(manager:  std::sync::Arc<DatabaseManager>) -> Self {
                         let mut inner = WorkTable::default();
                         inner.table_name = #table_name_lit;
+                        inner.lock_timeout = #lock_timeout_const_name;
                         Self(inner, manager)
                     }
                 }
@@ -69,6 +105,7 @@ impl Generator {
                     fn default() -> Self {
                         let mut inner = WorkTable::default();
                         inner.table_name = #table_name_lit;
+                        inner.lock_timeout = #lock_timeout_const_name;
                         Self(inner)
                     }
                 }
@@ -122,6 +159,8 @@ impl Generator {
         };
 
         quote! {
+            #lock_timeout
+            #wal_sync
             #table
 
             #new_impl
@@ -135,8 +174,18 @@ impl Generator {
                     self.0.select(pk)
                 }
 
-                pub fn insert(&self, row: #row_type) -> core::result::Result<#pk_type, WorkTableError> {
-                    self.0.insert::<{ #row_type::ROW_SIZE }>(row)
+                #insert_fns
+
+                pub fn set_lock_timeout(&mut self, timeout: Option<std::time::Duration>) {
+                    self.0.lock_timeout = timeout;
+                }
+
+                pub fn lock_metrics(&self) -> LockMetricsSnapshot {
+                    self.0.lock_map.metrics().snapshot()
+                }
+
+                pub fn held_locks(&self) -> Vec<LockInfo> {
+                    self.0.lock_map.held_locks()
                 }
 
                 pub async fn upsert(&self, row: #row_type) -> core::result::Result<(), WorkTableError> {
@@ -157,11 +206,17 @@ impl Generator {
                     core::result::Result::Ok(())
                 }
 
+                pub fn upsert_blocking(&self, row: #row_type) -> core::result::Result<(), WorkTableError> {
+                    block_on(self.upsert(row))
+                }
+
                 #get_next
 
                 #iter_with
 
                 #iter_with_async
+
+                #wal_replay
             }
 
             #select_executor
@@ -240,7 +295,7 @@ impl Generator {
 
         let columns = self.columns.columns_map.iter().map(|(name, _)| {
             let lit = Literal::string(name.to_string().as_str());
-            if let Some(index) = self.columns.indexes.get(&name) {
+            if let Some(index) = self.columns.indexes.get(name) {
                 let idx_name = &index.name;
                 if index.is_unique {
                     quote! {
@@ -386,11 +441,11 @@ impl Generator {
     fn gen_unique_index_fn(
         i: &Ident,
         idx: &Index,
-        columns_map: &HashMap<Ident, TokenStream>,
+        columns_map: &IndexMap<Ident, TokenStream>,
         row_ident: Ident,
     ) -> syn::Result<TokenStream> {
         let type_ = columns_map
-            .get(&i)
+            .get(i)
             .ok_or(syn::Error::new(i.span(), "Row not found"))?;
         let fn_name = Ident::new(format!("select_by_{i}").as_str(), Span::mixed_site());
         let field_ident = &idx.name;
@@ -407,11 +462,11 @@ impl Generator {
     fn gen_non_unique_index_fn(
         i: &Ident,
         idx: &Index,
-        columns_map: &HashMap<Ident, TokenStream>,
+        columns_map: &IndexMap<Ident, TokenStream>,
         row_ident: Ident,
     ) -> syn::Result<TokenStream> {
         let type_ = columns_map
-            .get(&i)
+            .get(i)
             .ok_or(syn::Error::new(i.span(), "Row not found"))?;
         let fn_name = Ident::new(format!("select_by_{i}").as_str(), Span::mixed_site());
         let field_ident = &idx.name;
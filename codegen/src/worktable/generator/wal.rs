@@ -0,0 +1,162 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::worktable::generator::Generator;
+use crate::worktable::model::GeneratorType;
+
+impl Generator {
+    /// Generates start of the table's WAL write. Must be placed right before
+    /// row is changed, so changes are logged in the order they are applied.
+    /// Generates nothing for the tables that are not persisted.
+    pub fn gen_wal_begin(&self) -> TokenStream {
+        if !self.is_persist {
+            return quote! {};
+        }
+        quote! {
+            let mut wal = self.0.wal.as_ref().map(Wal::begin);
+        }
+    }
+
+    /// Generates logging of the row's new value after it was updated.
+    pub fn gen_wal_update(&self, link: TokenStream) -> TokenStream {
+        if !self.is_persist {
+            return quote! {};
+        }
+        quote! {
+            if let Some(wal) = wal.as_mut() {
+                let row = self.0.data.select(#link).map_err(WorkTableError::PagesError)?;
+                wal.push(WalOperation::Update, &row)?;
+            }
+        }
+    }
+
+    /// Generates logging of the row's deletion by its primary key.
+    pub fn gen_wal_delete(&self, pk: TokenStream) -> TokenStream {
+        if !self.is_persist {
+            return quote! {};
+        }
+        quote! {
+            if let Some(wal) = wal.as_mut() {
+                wal.push(WalOperation::Delete, &#pk)?;
+            }
+        }
+    }
+
+    /// Generates append of the logged changes to the WAL.
+    pub fn gen_wal_commit(&self) -> TokenStream {
+        if !self.is_persist {
+            return quote! {};
+        }
+        quote! {
+            if let Some(wal) = wal {
+                wal.commit()?;
+            }
+        }
+    }
+}
+
+impl Generator {
+    /// Generates `insert`, `insert_many`, `bulk_load` and `truncate` methods.
+    /// Inserted rows are logged to the WAL by `WorkTable` itself. For
+    /// persisted tables truncation is logged too, so `truncate` can fail.
+    pub fn gen_insert_fns(&self) -> TokenStream {
+        let row_type = self.row_name.as_ref().unwrap();
+        let pk_type = &self.pk.as_ref().unwrap().ident;
+
+        let pk_gen_reset = match self.columns.generator_type {
+            GeneratorType::None => quote! {
+                let _ = reset_pk_gen;
+            },
+            GeneratorType::Autoincrement | GeneratorType::Custom => quote! {
+                if reset_pk_gen {
+                    PrimaryKeyGenerator::<#pk_type>::reset(&self.0.pk_gen);
+                }
+            },
+        };
+        let truncate = if self.is_persist {
+            quote! {
+                pub fn truncate(&self, reset_pk_gen: bool) -> core::result::Result<(), WorkTableError> {
+                    // Writes are paused before truncation is logged, so
+                    // writes logged after it are applied after it too.
+                    let pause = self.0.versions.pause_writes();
+                    if let Some(wal) = &self.0.wal {
+                        let mut wal = wal.begin();
+                        wal.push_bytes(WalOperation::Truncate, &[]);
+                        wal.commit()?;
+                    }
+                    self.0.truncate_paused(&pause);
+                    #pk_gen_reset
+                    core::result::Result::Ok(())
+                }
+            }
+        } else {
+            quote! {
+                pub fn truncate(&self, reset_pk_gen: bool) {
+                    self.0.truncate();
+                    #pk_gen_reset
+                }
+            }
+        };
+
+        quote! {
+            pub fn insert(&self, row: #row_type) -> core::result::Result<#pk_type, WorkTableError> {
+                self.0.insert::<{ #row_type::ROW_SIZE }>(row)
+            }
+
+            pub fn insert_many(&self, rows: Vec<#row_type>) -> core::result::Result<Vec<#pk_type>, WorkTableError> {
+                self.0.insert_many::<{ #row_type::ROW_SIZE }>(rows)
+            }
+
+            pub fn bulk_load(&self, rows: Vec<#row_type>) -> Vec<core::result::Result<#pk_type, WorkTableError>> {
+                self.0.bulk_load::<{ #row_type::ROW_SIZE }>(rows)
+            }
+
+            #truncate
+        }
+    }
+
+    /// Generates `replay_wal` method that applies records read from the WAL
+    /// to the table loaded from the last persisted state. Inserts and updates
+    /// are applied as upserts and deletes of missing rows are skipped, so
+    /// records that are already in the persisted state can be replayed again.
+    pub fn gen_wal_replay(&self) -> TokenStream {
+        if !self.is_persist {
+            return quote! {};
+        }
+        let row_type = self.row_name.as_ref().unwrap();
+
+        // Rows inserted by replay must not get their primary keys again.
+        let pk_gen_update = match self.columns.generator_type {
+            GeneratorType::Autoincrement => {
+                let pk_field = self.columns.primary_keys.0.first().unwrap();
+                quote! {
+                    let next = row.#pk_field.saturating_add(1);
+                    if PrimaryKeyGeneratorState::get_state(&self.0.pk_gen) < next {
+                        self.0.pk_gen = PrimaryKeyGeneratorState::from_state(next);
+                    }
+                }
+            }
+            GeneratorType::None | GeneratorType::Custom => quote! {},
+        };
+
+        quote! {
+            fn replay_wal(&mut self, records: Vec<WalRecord>) -> eyre::Result<()> {
+                for record in records {
+                    match record.operation {
+                        WalOperation::Insert | WalOperation::Update => {
+                            let row: #row_type = record.value()?;
+                            #pk_gen_update
+                            self.upsert_blocking(row)?;
+                        }
+                        WalOperation::Delete => match self.delete_blocking(record.value()?) {
+                            core::result::Result::Ok(()) | Err(WorkTableError::NotFound) => {}
+                            Err(e) => return Err(e.into()),
+                        },
+                        WalOperation::Truncate => self.0.truncate(),
+                    }
+                }
+                core::result::Result::Ok(())
+            }
+        }
+    }
+}
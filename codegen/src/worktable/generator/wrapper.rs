@@ -14,7 +14,7 @@ impl Generator {
             .map(|(i, _)| {
                 let name = Ident::new(format!("{i}_lock").as_str(), Span::mixed_site());
                 quote! {
-                    #name: u16,
+                    #name: u64,
                 }
             })
             .collect::<Vec<_>>();
@@ -29,7 +29,7 @@ impl Generator {
 
                 is_deleted: bool,
 
-                lock: u16,
+                lock: u64,
 
                 #(#row_locks)*
             }
@@ -66,7 +66,7 @@ impl Generator {
         );
         let archived_impl = quote! {
             impl ArchivedRow for #archived_wrapper {
-                fn is_locked(&self) -> Option<u16> {
+                fn is_locked(&self) -> Option<u64> {
                     if self.lock != 0 {
                         return Some(self.lock.into());
                     }
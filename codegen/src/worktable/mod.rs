@@ -14,9 +14,11 @@ pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
     let mut queries = None;
     let mut indexes = None;
     let mut config = None;
+    let mut migrations = None;
 
     let name = parser.parse_name()?;
     let is_persist = parser.parse_persist()?;
+    let is_serde = parser.parse_serde()?;
     while let Some(ident) = parser.peek_next() {
         match ident.to_string().as_str() {
             "columns" => {
@@ -35,6 +37,10 @@ pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
                 let res = parser.parse_configs()?;
                 config = Some(res)
             }
+            "migrations" => {
+                let res = parser.parse_migrations()?;
+                migrations = Some(res)
+            }
             _ => return Err(syn::Error::new(ident.span(), "Unexpected identifier")),
         }
     }
@@ -44,11 +50,14 @@ pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
         columns.indexes = i
     }
     let mut generator = Generator::new(name, is_persist, columns);
+    generator.is_serde = is_serde;
     generator.queries = queries;
     generator.config = config;
+    generator.migrations = migrations.unwrap_or_default();
 
     let pk_def = generator.gen_pk_def()?;
     let row_def = generator.gen_row_def();
+    let migrations_def = generator.gen_migrations_def()?;
     let wrapper_def = generator.gen_wrapper_def();
     let wrapper_impl = generator.gen_wrapper_impl();
     let index_def = generator.gen_index_def();
@@ -59,10 +68,20 @@ pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
     let select_impls = generator.gen_query_select_impl()?;
     let update_impls = generator.gen_query_update_impl()?;
     let delete_impls = generator.gen_query_delete_impl()?;
+    let increment_impls = generator.gen_query_increment_impl()?;
+    let snapshot_def = generator.gen_snapshot_def()?;
+    let import_impl = generator.gen_import_impl();
+    let csv_impl = generator.gen_csv_impl()?;
+    let json_impl = generator.gen_json_impl();
+    let arrow_impl = generator.gen_arrow_impl();
+    let update_blocking_impls = generator.gen_blocking_impl(&update_impls)?;
+    let delete_blocking_impls = generator.gen_blocking_impl(&delete_impls)?;
+    let increment_blocking_impls = generator.gen_blocking_impl(&increment_impls)?;
 
     Ok(TokenStream::from(quote! {
         #pk_def
         #row_def
+        #migrations_def
         #wrapper_def
         #wrapper_impl
         #index_def
@@ -73,6 +92,15 @@ pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
         #select_impls
         #update_impls
         #delete_impls
+        #increment_impls
+        #snapshot_def
+        #import_impl
+        #csv_impl
+        #json_impl
+        #arrow_impl
+        #update_blocking_impls
+        #delete_blocking_impls
+        #increment_blocking_impls
     }))
 }
 
@@ -98,4 +126,26 @@ mod test {
 
         let _ = expand(tokens).unwrap();
     }
+
+    #[test]
+    fn increment_of_indexed_column_is_rejected() {
+        let tokens = quote! {
+            name: Test,
+            columns: {
+                id: u64 primary_key,
+                test: i64,
+                another: u64,
+            },
+            indexes: {
+                test_idx: test,
+            },
+            queries: {
+                increment: {
+                    Test(test) by id,
+                }
+            }
+        };
+
+        assert!(expand(tokens).is_err());
+    }
 }
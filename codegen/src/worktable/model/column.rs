@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 use crate::worktable::model::index::Index;
 use crate::worktable::model::GeneratorType;
@@ -8,8 +8,12 @@ use syn::spanned::Spanned;
 
 #[derive(Debug, Clone)]
 pub struct Columns {
-    pub columns_map: HashMap<Ident, TokenStream>,
-    pub indexes: HashMap<Ident, Index>,
+    /// Columns in the declaration order, which is the order of the row's
+    /// fields.
+    pub columns_map: IndexMap<Ident, TokenStream>,
+    /// Indexes in the declaration order, which is the order of the index
+    /// struct's fields.
+    pub indexes: IndexMap<Ident, Index>,
     pub primary_keys: (Vec<Ident>, Ident),
     pub generator_type: GeneratorType,
 }
@@ -26,7 +30,7 @@ pub struct Row {
 
 impl Columns {
     pub fn try_from_rows(rows: Vec<Row>, input: &TokenStream) -> syn::Result<Self> {
-        let mut columns_map = HashMap::new();
+        let mut columns_map = IndexMap::new();
         let mut pk = vec![];
         let mut gen_type = None;
         let mut index_type = None;
@@ -1,4 +1,14 @@
 #[derive(Debug, Default)]
 pub struct Config {
     pub page_size: Option<u32>,
+    pub lock_timeout_ms: Option<u64>,
+    pub wal_sync: Option<WalSync>,
+}
+
+/// `fsync` policy of the table's WAL declared as `wal_sync` config option.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WalSync {
+    Always,
+    EveryN(u32),
+    Never,
 }
@@ -0,0 +1,32 @@
+use proc_macro2::{Ident, TokenStream};
+
+/// Migration declared in `migrations` section. Migration `n` converts rows of
+/// schema version `n - 1` to version `n`.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub version: u32,
+    pub changes: Vec<ColumnChange>,
+}
+
+/// Change of the row's columns made by a migration.
+#[derive(Debug, Clone)]
+pub enum ColumnChange {
+    /// Column is added with the `default` expression's value.
+    Add {
+        name: Ident,
+        type_: TokenStream,
+        default: TokenStream,
+    },
+    /// Column is removed.
+    Drop { name: Ident, type_: TokenStream },
+    /// Column `from` is renamed to `to`.
+    Rename { from: Ident, to: Ident },
+    /// Column's type is changed to the type that implements `From<from>`.
+    /// Optional column stays optional.
+    Widen {
+        name: Ident,
+        from: TokenStream,
+        to: TokenStream,
+        optional: bool,
+    },
+}
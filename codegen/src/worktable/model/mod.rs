@@ -1,13 +1,15 @@
 mod column;
 mod config;
 mod index;
+mod migration;
 pub mod operation;
 mod primary_key;
 mod queries;
 
 pub use column::{Columns, Row};
-pub use config::Config;
+pub use config::{Config, WalSync};
 pub use index::Index;
+pub use migration::{ColumnChange, Migration};
 pub use operation::Operation;
 pub use primary_key::{GeneratorType, PrimaryKey};
 pub use queries::Queries;
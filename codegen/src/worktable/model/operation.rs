@@ -4,5 +4,5 @@ use proc_macro2::Ident;
 pub struct Operation {
     pub name: Ident,
     pub columns: Vec<Ident>,
-    pub by: Ident,
+    pub by: Vec<Ident>,
 }
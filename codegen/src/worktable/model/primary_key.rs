@@ -1,10 +1,12 @@
+use indexmap::IndexMap;
 use proc_macro2::{Ident, TokenStream};
-use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 pub struct PrimaryKey {
     pub ident: Ident,
-    pub vals: HashMap<Ident, TokenStream>,
+    /// Primary key columns in the declaration order, which is the order of
+    /// the composite key's fields.
+    pub vals: IndexMap<Ident, TokenStream>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
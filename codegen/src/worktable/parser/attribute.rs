@@ -3,9 +3,18 @@ use syn::spanned::Spanned as _;
 
 use crate::worktable::parser::Parser;
 
-// TODO: Move this to separate attributes section because now it only parses persist.
+// TODO: Move this to separate attributes section because now it only parses flags.
 impl Parser {
     pub fn parse_persist(&mut self) -> syn::Result<bool> {
+        self.parse_flag("persist")
+    }
+
+    pub fn parse_serde(&mut self) -> syn::Result<bool> {
+        self.parse_flag("serde")
+    }
+
+    /// Parses optional `name: true` flag. Returns `false` if flag is not set.
+    fn parse_flag(&mut self, name: &str) -> syn::Result<bool> {
         let Some(ident) = self.input_iter.peek().cloned() else {
             return Ok(false);
         };
@@ -16,7 +25,7 @@ impl Parser {
             ));
         };
 
-        if ident.to_string().as_str() == "persist" {
+        if ident.to_string().as_str() == name {
             let _ = self.input_iter.next();
             self.parse_colon()?;
             let bool = self
@@ -91,4 +100,13 @@ mod tests {
         assert!(name.is_ok());
         assert!(!name.unwrap());
     }
+
+    #[test]
+    fn test_serde() {
+        let tokens = TokenStream::from(quote! {serde: true,});
+        let mut parser = Parser::new(tokens);
+        let serde = parser.parse_serde();
+        assert!(serde.is_ok());
+        assert!(serde.unwrap());
+    }
 }
@@ -3,7 +3,7 @@ use std::str::FromStr;
 use proc_macro2::{Delimiter, TokenTree};
 use syn::spanned::Spanned;
 
-use crate::worktable::model::Config;
+use crate::worktable::model::{Config, WalSync};
 use crate::worktable::Parser;
 
 const CONFIG_FIELD_NAME: &str = "config";
@@ -51,7 +51,9 @@ impl Parser {
 
         let mut parser = Parser::new(tt);
         let mut config = Config::default();
-        parser.parse_config(&mut config)?;
+        while parser.parse_config(&mut config)?.is_some() {
+            parser.try_parse_comma()?;
+        }
 
         Ok(config)
     }
@@ -85,6 +87,50 @@ impl Parser {
 
                 config.page_size = Some(u32::from_str(value.as_str()).unwrap())
             }
+            "lock_timeout_ms" => {
+                let value = self.input_iter.next().ok_or(syn::Error::new(
+                    self.input.span(),
+                    "Expected lock timeout value in declaration",
+                ))?;
+                let literal = if let TokenTree::Literal(value) = value {
+                    value
+                } else {
+                    return Err(syn::Error::new(value.span(), "Expected literal."));
+                };
+                let value = literal.to_string().replace("_", "");
+
+                config.lock_timeout_ms =
+                    Some(u64::from_str(value.as_str()).map_err(|_| {
+                        syn::Error::new(literal.span(), "Expected integer literal.")
+                    })?)
+            }
+            "wal_sync" => {
+                let value = self.input_iter.next().ok_or(syn::Error::new(
+                    self.input.span(),
+                    "Expected WAL sync policy in declaration",
+                ))?;
+                config.wal_sync = Some(match value {
+                    TokenTree::Ident(ident) if ident == "always" => WalSync::Always,
+                    TokenTree::Ident(ident) if ident == "never" => WalSync::Never,
+                    TokenTree::Literal(literal) => {
+                        let value = literal.to_string().replace("_", "");
+                        let n = u32::from_str(value.as_str())
+                            .ok()
+                            .filter(|n| *n != 0)
+                            .ok_or(syn::Error::new(
+                                literal.span(),
+                                "Expected positive integer literal.",
+                            ))?;
+                        WalSync::EveryN(n)
+                    }
+                    value => {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "Expected `always`, `never` or count of writes between syncs.",
+                        ))
+                    }
+                })
+            }
             _ => return Err(syn::Error::new(name.span(), "Unexpected identifier")),
         }
 
@@ -94,6 +140,7 @@ impl Parser {
 
 #[cfg(test)]
 mod tests {
+    use crate::worktable::model::WalSync;
     use crate::worktable::Parser;
 
     use proc_macro2::TokenStream;
@@ -110,4 +157,40 @@ mod tests {
         assert!(configs.is_ok());
         let columns = configs.unwrap();
     }
+
+    #[test]
+    fn test_lock_timeout_parse() {
+        let tokens = TokenStream::from(quote! {config: {
+            page_size: 16_000,
+            lock_timeout_ms: 1_000,
+        }});
+        let mut parser = Parser::new(tokens);
+        let config = parser.parse_configs().unwrap();
+
+        assert_eq!(config.page_size, Some(16_000));
+        assert_eq!(config.lock_timeout_ms, Some(1_000));
+    }
+
+    #[test]
+    fn test_wal_sync_parse() {
+        let tokens = TokenStream::from(quote! {config: {
+            wal_sync: 1_000,
+        }});
+        let mut parser = Parser::new(tokens);
+        let config = parser.parse_configs().unwrap();
+        assert_eq!(config.wal_sync, Some(WalSync::EveryN(1_000)));
+
+        let tokens = TokenStream::from(quote! {config: {
+            wal_sync: never
+        }});
+        let mut parser = Parser::new(tokens);
+        let config = parser.parse_configs().unwrap();
+        assert_eq!(config.wal_sync, Some(WalSync::Never));
+
+        let tokens = TokenStream::from(quote! {config: {
+            wal_sync: sometimes
+        }});
+        let mut parser = Parser::new(tokens);
+        assert!(parser.parse_configs().is_err());
+    }
 }
@@ -0,0 +1,274 @@
+use std::str::FromStr;
+
+use proc_macro2::{Delimiter, Ident, TokenStream, TokenTree};
+use quote::quote;
+use syn::spanned::Spanned;
+
+use crate::worktable::model::{ColumnChange, Migration};
+use crate::worktable::Parser;
+
+const MIGRATIONS_FIELD_NAME: &str = "migrations";
+
+impl Parser {
+    pub fn parse_migrations(&mut self) -> syn::Result<Vec<Migration>> {
+        let ident = self.input_iter.next().ok_or(syn::Error::new(
+            self.input.span(),
+            format!("Expected `{}` field in declaration", MIGRATIONS_FIELD_NAME),
+        ))?;
+
+        if let TokenTree::Ident(ident) = ident {
+            if ident.to_string().as_str() != MIGRATIONS_FIELD_NAME {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("Expected `{}` field in declaration", MIGRATIONS_FIELD_NAME),
+                ));
+            }
+        } else {
+            return Err(syn::Error::new(
+                ident.span(),
+                "Expected field name identifier.",
+            ));
+        };
+
+        self.parse_colon()?;
+
+        let mut parser = Parser::new(self.parse_braced(MIGRATIONS_FIELD_NAME)?);
+        let mut migrations: Vec<Migration> = vec![];
+        while parser.has_next() {
+            let span = parser.input_iter.peek().unwrap().span();
+            let migration = parser.parse_migration()?;
+            if migration.version as usize != migrations.len() + 1 {
+                return Err(syn::Error::new(
+                    span,
+                    "Migrations must be numbered in order starting from 1.",
+                ));
+            }
+            migrations.push(migration);
+            parser.try_parse_comma()?;
+        }
+
+        self.try_parse_comma()?;
+
+        Ok(migrations)
+    }
+
+    fn parse_migration(&mut self) -> syn::Result<Migration> {
+        let version = self.input_iter.next().unwrap();
+        let version = if let TokenTree::Literal(literal) = version {
+            let value = literal.to_string().replace("_", "");
+            u32::from_str(value.as_str())
+                .map_err(|_| syn::Error::new(literal.span(), "Expected integer literal."))?
+        } else {
+            return Err(syn::Error::new(
+                version.span(),
+                "Expected migration number.",
+            ));
+        };
+
+        self.parse_colon()?;
+
+        let mut parser = Parser::new(self.parse_braced("migration")?);
+        let mut changes = vec![];
+        while parser.has_next() {
+            changes.push(parser.parse_column_change()?);
+            parser.try_parse_comma()?;
+        }
+
+        Ok(Migration { version, changes })
+    }
+
+    fn parse_column_change(&mut self) -> syn::Result<ColumnChange> {
+        let kind = self.parse_migration_ident()?;
+        let change = match kind.to_string().as_str() {
+            "add" => {
+                let name = self.parse_migration_ident()?;
+                self.parse_colon()?;
+                let (type_, _) = self.parse_migration_type()?;
+                self.parse_punct('=')?;
+                let mut default = vec![];
+                while let Some(tt) = self.input_iter.peek() {
+                    if matches!(tt, TokenTree::Punct(p) if p.as_char() == ',') {
+                        break;
+                    }
+                    default.push(self.input_iter.next().unwrap());
+                }
+                if default.is_empty() {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        "Expected default value of the added column.",
+                    ));
+                }
+                ColumnChange::Add {
+                    name,
+                    type_,
+                    default: default.into_iter().collect(),
+                }
+            }
+            "drop" => {
+                let name = self.parse_migration_ident()?;
+                self.parse_colon()?;
+                let (type_, _) = self.parse_migration_type()?;
+                ColumnChange::Drop { name, type_ }
+            }
+            "rename" => {
+                let from = self.parse_migration_ident()?;
+                self.parse_arrow()?;
+                let to = self.parse_migration_ident()?;
+                ColumnChange::Rename { from, to }
+            }
+            "widen" => {
+                let name = self.parse_migration_ident()?;
+                self.parse_colon()?;
+                let (from, optional) = self.parse_migration_type()?;
+                self.parse_arrow()?;
+                let (to, to_optional) = self.parse_migration_type()?;
+                if optional != to_optional {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        "Widened column must stay optional or required.",
+                    ));
+                }
+                ColumnChange::Widen {
+                    name,
+                    from,
+                    to,
+                    optional,
+                }
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    kind.span(),
+                    "Expected `add`, `drop`, `rename` or `widen`.",
+                ))
+            }
+        };
+
+        Ok(change)
+    }
+
+    /// Parses column type declared like in `columns` section. Returns the
+    /// type and whether column is optional.
+    fn parse_migration_type(&mut self) -> syn::Result<(TokenStream, bool)> {
+        let type_ = self.parse_migration_ident()?;
+        if let Some(TokenTree::Ident(option)) = self.input_iter.peek() {
+            if option.to_string().as_str() == "optional" {
+                self.input_iter.next();
+                return Ok((quote! { core::option::Option<#type_> }, true));
+            }
+        }
+
+        Ok((quote! { #type_ }, false))
+    }
+
+    fn parse_migration_ident(&mut self) -> syn::Result<Ident> {
+        let ident = self
+            .input_iter
+            .next()
+            .ok_or(syn::Error::new(self.input.span(), "Expected identifier."))?;
+        if let TokenTree::Ident(ident) = ident {
+            Ok(ident)
+        } else {
+            Err(syn::Error::new(ident.span(), "Expected identifier."))
+        }
+    }
+
+    fn parse_braced(&mut self, name: &str) -> syn::Result<TokenStream> {
+        let group = self.input_iter.next().ok_or(syn::Error::new(
+            self.input.span(),
+            format!("Expected `{}` declarations", name),
+        ))?;
+        if let TokenTree::Group(group) = group {
+            if group.delimiter() != Delimiter::Brace {
+                return Err(syn::Error::new(group.span(), "Expected brace"));
+            }
+            Ok(group.stream())
+        } else {
+            Err(syn::Error::new(
+                group.span(),
+                format!("Expected `{}` declarations", name),
+            ))
+        }
+    }
+
+    fn parse_arrow(&mut self) -> syn::Result<()> {
+        self.parse_punct('-')?;
+        self.parse_punct('>')
+    }
+
+    fn parse_punct(&mut self, c: char) -> syn::Result<()> {
+        let punct = self
+            .input_iter
+            .next()
+            .ok_or(syn::Error::new(self.input.span(), "Expected token."))?;
+        match punct {
+            TokenTree::Punct(punct) if punct.as_char() == c => Ok(()),
+            tt => Err(syn::Error::new(tt.span(), format!("Expected `{}`.", c))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::worktable::model::ColumnChange;
+    use crate::worktable::Parser;
+
+    use proc_macro2::TokenStream;
+    use quote::quote;
+
+    #[test]
+    fn test_migrations_parse() {
+        let tokens = TokenStream::from(quote! {migrations: {
+            1: {
+                add value: u64 = 0,
+                add name: String optional = Some("name".to_string()),
+            },
+            2: {
+                rename another -> other,
+                widen other: u32 -> u64,
+                drop legacy: String,
+            }
+        }});
+        let mut parser = Parser::new(tokens);
+        let migrations = parser.parse_migrations().unwrap();
+
+        assert_eq!(migrations.len(), 2);
+        assert_eq!(migrations[0].version, 1);
+        let ColumnChange::Add {
+            name,
+            type_,
+            default,
+        } = &migrations[0].changes[1]
+        else {
+            panic!("expected add")
+        };
+        assert_eq!(name.to_string(), "name");
+        assert_eq!(
+            type_.to_string(),
+            quote! { core::option::Option<String> }.to_string()
+        );
+        assert_eq!(
+            default.to_string(),
+            quote! { Some("name".to_string()) }.to_string()
+        );
+        assert_eq!(migrations[1].changes.len(), 3);
+        assert!(matches!(
+            &migrations[1].changes[0],
+            ColumnChange::Rename { from, to } if from == "another" && to == "other"
+        ));
+        assert!(matches!(
+            &migrations[1].changes[1],
+            ColumnChange::Widen { from, .. } if from.to_string() == "u32"
+        ));
+    }
+
+    #[test]
+    fn test_migrations_order() {
+        let tokens = TokenStream::from(quote! {migrations: {
+            2: {
+                add value: u64 = 0,
+            }
+        }});
+        let mut parser = Parser::new(tokens);
+        assert!(parser.parse_migrations().is_err());
+    }
+}
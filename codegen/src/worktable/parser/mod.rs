@@ -2,6 +2,7 @@ mod attribute;
 mod columns;
 mod config;
 mod index;
+mod migrations;
 mod name;
 mod punct;
 pub mod queries;
@@ -31,7 +31,9 @@ impl Parser {
         ))?;
         if let TokenTree::Group(ops) = ops {
             let mut parser = Parser::new(ops.stream());
-            parser.parse_operations()
+            let ops = parser.parse_operations();
+            self.try_parse_comma()?;
+            ops
         } else {
             Err(syn::Error::new(
                 ops.span(),
@@ -68,6 +70,6 @@ mod tests {
         assert_eq!(op.columns.len(), 2);
         assert_eq!(op.columns[0], "id");
         assert_eq!(op.columns[1], "test");
-        assert_eq!(op.by, "name");
+        assert_eq!(op.by, vec!["name"]);
     }
 }
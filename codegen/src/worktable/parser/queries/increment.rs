@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use proc_macro2::{Ident, TokenTree};
+use syn::spanned::Spanned;
+
+use crate::worktable::model::Operation;
+use crate::worktable::Parser;
+
+impl Parser {
+    pub fn parse_increments(&mut self) -> syn::Result<HashMap<Ident, Operation>> {
+        let ident = self.input_iter.next().ok_or(syn::Error::new(
+            self.input.span(),
+            "Expected `increment` field in declaration",
+        ))?;
+        if let TokenTree::Ident(ident) = ident {
+            if ident.to_string().as_str() != "increment" {
+                return Err(syn::Error::new(ident.span(), "Expected `increment` field"));
+            }
+        } else {
+            return Err(syn::Error::new(
+                ident.span(),
+                "Expected field name identifier.",
+            ));
+        };
+
+        self.parse_colon()?;
+
+        let ops = self.input_iter.next().ok_or(syn::Error::new(
+            self.input.span(),
+            "Expected operation declarations",
+        ))?;
+        if let TokenTree::Group(ops) = ops {
+            let mut parser = Parser::new(ops.stream());
+            let ops = parser.parse_operations();
+            self.try_parse_comma()?;
+            ops
+        } else {
+            Err(syn::Error::new(
+                ops.span(),
+                "Expected operation declarations",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::{Ident, Span};
+    use quote::quote;
+
+    use crate::worktable::Parser;
+
+    #[test]
+    fn test_increment() {
+        let tokens = quote! {
+            increment: {
+                AddQty(bids_qty, asks_qty) by id,
+            }
+        };
+        let mut parser = Parser::new(tokens);
+        let ops = parser.parse_increments().unwrap();
+
+        assert_eq!(ops.len(), 1);
+        let op = ops.get(&Ident::new("AddQty", Span::mixed_site())).unwrap();
+
+        assert_eq!(op.name, "AddQty");
+        assert_eq!(op.columns.len(), 2);
+        assert_eq!(op.columns[0], "bids_qty");
+        assert_eq!(op.columns[1], "asks_qty");
+        assert_eq!(op.by, vec!["id"]);
+    }
+}
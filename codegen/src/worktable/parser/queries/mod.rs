@@ -1,4 +1,5 @@
 mod delete;
+mod increment;
 mod operation;
 mod select;
 mod update;
@@ -48,6 +49,10 @@ impl Parser {
                         let deletes = parser.parse_deletes()?;
                         queries.deletes = deletes;
                     }
+                    "increment" => {
+                        let increments = parser.parse_increments()?;
+                        queries.increments = increments;
+                    }
                     _ => return Err(syn::Error::new(ident.span(), "Unexpected identifier")),
                 }
             }
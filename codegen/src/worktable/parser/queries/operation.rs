@@ -65,23 +65,40 @@ impl Parser {
             return Err(syn::Error::new(by.span(), "Expected `by` identifier."));
         };
 
-        let ident = self.input_iter.next().ok_or(syn::Error::new(
+        let by_columns = self.input_iter.next().ok_or(syn::Error::new(
             self.input.span(),
-            "Expected operation name in declaration",
+            "Expected by column identifiers in declaration",
         ))?;
-        let by_name = if let TokenTree::Ident(ident) = ident {
-            ident
-        } else {
-            return Err(syn::Error::new(
-                ident.span(),
-                "Expected by name identifier.",
-            ));
+        let by_columns = match by_columns {
+            TokenTree::Ident(ident) => vec![ident],
+            TokenTree::Group(columns) => {
+                let mut parser = Parser::new(columns.stream());
+                let mut by_columns = Vec::new();
+                while parser.has_next() {
+                    let column = parser.parse_column_ident()?;
+                    by_columns.push(column);
+                    parser.try_parse_comma()?;
+                }
+                if by_columns.is_empty() {
+                    return Err(syn::Error::new(
+                        columns.span(),
+                        "Expected at least one by column identifier.",
+                    ));
+                }
+                by_columns
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    by_columns.span(),
+                    "Expected by name identifier.",
+                ))
+            }
         };
 
         Ok(Operation {
             name,
             columns,
-            by: by_name,
+            by: by_columns,
         })
     }
 
@@ -119,6 +136,20 @@ mod tests {
         assert_eq!(op.columns.len(), 2);
         assert_eq!(op.columns[0], "id".to_string());
         assert_eq!(op.columns[1], "test".to_string());
-        assert_eq!(op.by.to_string(), "name".to_string());
+        assert_eq!(op.by.len(), 1);
+        assert_eq!(op.by[0].to_string(), "name".to_string());
+    }
+
+    #[test]
+    fn test_operation_multiple_by() {
+        let tokens = quote! {
+            TestQuery(id) by (name, test),
+        };
+
+        let mut parser = Parser::new(tokens);
+        let op = parser.parse_operation().unwrap();
+        assert_eq!(op.by.len(), 2);
+        assert_eq!(op.by[0].to_string(), "name".to_string());
+        assert_eq!(op.by[1].to_string(), "test".to_string());
     }
 }
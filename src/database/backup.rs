@@ -0,0 +1,276 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::database::checksum::crc32;
+use crate::database::{Checkpointable, DatabaseManager};
+use crate::table::snapshot::WritesPause;
+
+/// Name of the backup's manifest file.
+pub const BACKUP_MANIFEST: &str = "manifest.json";
+
+/// Version of the manifest's format.
+const MANIFEST_VERSION: u32 = 1;
+
+/// Table that can be backed up by [`DatabaseManager::backup`]. It's
+/// implemented for all persisted tables.
+///
+/// [`DatabaseManager::backup`]: crate::prelude::DatabaseManager::backup
+pub trait Backupable: Checkpointable {
+    /// Returns name of the table's `.wt` file.
+    fn file_name(&self) -> &str;
+
+    /// Returns table's schema version.
+    fn schema_version(&self) -> u32;
+
+    /// Waits for the table's writes that are in progress and doesn't let new
+    /// writes start until returned [`WritesPause`] is dropped.
+    fn pause_writes(&self) -> WritesPause<'_>;
+
+    /// Captures table's current state to be written to the `dir`. Called
+    /// while table's writes are paused.
+    fn capture(&self, dir: &str) -> eyre::Result<Box<dyn CapturedSpace>>;
+}
+
+/// Table that can be loaded from the backup restored by
+/// [`DatabaseManager::restore`]. It's implemented for all persisted tables.
+pub trait Restorable: Backupable + Sized {
+    /// Name of the table's `.wt` file.
+    const FILE_NAME: &'static str;
+
+    /// Loads table from the `database_files_dir` of the `manager`.
+    fn load(manager: Arc<DatabaseManager>) -> eyre::Result<Self>;
+}
+
+/// Table's state captured by [`Backupable::capture`].
+pub trait CapturedSpace {
+    /// Writes captured state as the table's `.wt` file.
+    fn persist(&mut self) -> eyre::Result<()>;
+}
+
+/// Description of the backup written to its [`BACKUP_MANIFEST`] file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Version of the manifest's format.
+    pub version: u32,
+    /// Unix time in milliseconds at which tables' state was captured.
+    pub created_at: u64,
+    pub tables: Vec<BackupEntry>,
+}
+
+/// Table's file in the backup.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub table: String,
+    /// Name of the file in the backup's directory.
+    pub file: String,
+    pub schema_version: u32,
+    /// Count of the table's writes included in the backup.
+    pub writes: u64,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// CRC-32 checksum of the file.
+    pub crc32: u32,
+}
+
+impl BackupEntry {
+    /// Creates entry of the table's file written to the `dir`.
+    pub(crate) fn new(
+        table: &dyn Backupable,
+        writes: u64,
+        dir: impl AsRef<Path>,
+    ) -> eyre::Result<Self> {
+        let bytes = std::fs::read(dir.as_ref().join(table.file_name()))?;
+
+        Ok(Self {
+            table: table.table_name().to_string(),
+            file: table.file_name().to_string(),
+            schema_version: table.schema_version(),
+            writes,
+            size: bytes.len() as u64,
+            crc32: crc32(&bytes),
+        })
+    }
+
+    /// Returns name of the table's log file.
+    pub fn wal_file(&self) -> String {
+        let stem = self.file.strip_suffix(".wt").unwrap_or(self.file.as_str());
+        format!("{}.wal", stem)
+    }
+
+    /// Fails if the entry's file in the `dir` is missing or its size or
+    /// checksum differ from the entry's ones.
+    fn verify(&self, dir: &Path) -> eyre::Result<()> {
+        // Only files in the backup's directory are restored.
+        if Path::new(&self.file).file_name() != Some(self.file.as_ref()) {
+            eyre::bail!("backup's file name {} is not a plain file name", self.file)
+        }
+        let bytes = std::fs::read(dir.join(&self.file))
+            .map_err(|e| eyre::eyre!("backup's file {} can't be read: {}", self.file, e))?;
+        if bytes.len() as u64 != self.size {
+            eyre::bail!(
+                "backup's file {} has size {} instead of {}",
+                self.file,
+                bytes.len(),
+                self.size
+            )
+        }
+        let checksum = crc32(&bytes);
+        if checksum != self.crc32 {
+            eyre::bail!(
+                "backup's file {} has checksum {:#010x} instead of {:#010x}",
+                self.file,
+                checksum,
+                self.crc32
+            )
+        }
+
+        Ok(())
+    }
+}
+
+impl BackupManifest {
+    pub(crate) fn new(created_at: SystemTime, tables: Vec<BackupEntry>) -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            created_at: created_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            tables,
+        }
+    }
+
+    /// Reads manifest of the backup in the `dir`.
+    pub fn read(dir: impl AsRef<Path>) -> eyre::Result<Self> {
+        let path = dir.as_ref().join(BACKUP_MANIFEST);
+        let bytes = std::fs::read(&path).map_err(|e| {
+            eyre::eyre!("backup's manifest {} can't be read: {}", path.display(), e)
+        })?;
+        let manifest: Self = serde_json::from_slice(&bytes)?;
+        if manifest.version > MANIFEST_VERSION {
+            eyre::bail!(
+                "backup's manifest has version {}, which is newer than the supported version {}",
+                manifest.version,
+                MANIFEST_VERSION
+            )
+        }
+
+        Ok(manifest)
+    }
+
+    /// Writes manifest to the `dir`. Manifest is written after the tables'
+    /// files, so backup without it is incomplete.
+    pub(crate) fn write(&self, dir: impl AsRef<Path>) -> eyre::Result<()> {
+        let dir = dir.as_ref();
+        let path = dir.join(BACKUP_MANIFEST);
+        let temp_path = dir.join(format!("{}.tmp", BACKUP_MANIFEST));
+        let mut file = File::create(&temp_path)?;
+        file.write_all(&serde_json::to_vec_pretty(self)?)?;
+        file.sync_all()?;
+        drop(file);
+        std::fs::rename(temp_path, path)?;
+        #[cfg(unix)]
+        File::open(dir)?.sync_all()?;
+
+        Ok(())
+    }
+
+    /// Fails if some of the backup's files in the `dir` are missing or
+    /// differ from the ones described by the manifest.
+    pub fn verify(&self, dir: impl AsRef<Path>) -> eyre::Result<()> {
+        let dir = dir.as_ref();
+        for (i, entry) in self.tables.iter().enumerate() {
+            if self.tables[..i].iter().any(|e| e.file == entry.file) {
+                eyre::bail!("backup's file {} is listed twice", entry.file)
+            }
+            entry.verify(dir)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Backup restored by [`DatabaseManager::restore`]. Its tables' files are
+/// already installed and checked against the manifest.
+#[derive(Debug)]
+pub struct RestoredBackup {
+    pub manifest: BackupManifest,
+    manager: Arc<DatabaseManager>,
+}
+
+impl RestoredBackup {
+    pub(crate) fn new(manifest: BackupManifest, manager: Arc<DatabaseManager>) -> Self {
+        Self { manifest, manager }
+    }
+
+    /// Loads restored table and registers it in the manager, so it's
+    /// included in the next backups. Fails if the table is not in the backup
+    /// or its file can't be loaded.
+    pub fn load<T: Restorable + 'static>(&self) -> eyre::Result<Arc<T>> {
+        let entry = self
+            .manifest
+            .tables
+            .iter()
+            .find(|e| e.file == T::FILE_NAME)
+            .ok_or_else(|| eyre::eyre!("backup doesn't contain file {}", T::FILE_NAME))?;
+        let table =
+            Arc::new(T::load(self.manager.clone()).map_err(|e| {
+                eyre::eyre!("restored table {} can't be loaded: {}", entry.table, e)
+            })?);
+        self.manager.register(&table);
+
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use crate::database::backup::{BackupEntry, BackupManifest};
+
+    fn entry(dir: &std::path::Path, file: &str, bytes: &[u8]) -> BackupEntry {
+        std::fs::write(dir.join(file), bytes).unwrap();
+        BackupEntry {
+            table: "Test".to_string(),
+            file: file.to_string(),
+            schema_version: 0,
+            writes: 0,
+            size: bytes.len() as u64,
+            crc32: crate::database::checksum::crc32(bytes),
+        }
+    }
+
+    #[test]
+    fn manifest_verify() {
+        let dir = std::env::temp_dir().join(format!("worktable_backup_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut manifest = BackupManifest::new(
+            SystemTime::now(),
+            vec![
+                entry(&dir, "test.wt", b"test"),
+                entry(&dir, "other.wt", b"other"),
+            ],
+        );
+        manifest.write(&dir).unwrap();
+        assert_eq!(BackupManifest::read(&dir).unwrap(), manifest);
+        manifest.verify(&dir).unwrap();
+        assert_eq!(manifest.tables[0].wal_file(), "test.wal");
+
+        std::fs::write(dir.join("other.wt"), b"othex").unwrap();
+        assert!(manifest.verify(&dir).is_err());
+        std::fs::write(dir.join("other.wt"), b"other").unwrap();
+
+        manifest.tables[1].file = "../other.wt".to_string();
+        assert!(manifest.verify(&dir).is_err());
+        manifest.tables[1].file = "test.wt".to_string();
+        assert!(manifest.verify(&dir).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+use std::sync::{Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::database::manager::TableRegistry;
+use crate::database::Backupable;
+
+/// Time after which failed checkpoint is retried.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Table that can be persisted by the [`CheckpointScheduler`]. It's
+/// implemented for all persisted tables.
+pub trait Checkpointable: Send + Sync {
+    /// Returns name of the table used in reports.
+    fn table_name(&self) -> &str;
+
+    /// Returns count of the writes made to the table.
+    fn writes_count(&self) -> u64;
+
+    /// Persists table's current state.
+    fn checkpoint(&self) -> eyre::Result<()>;
+}
+
+/// Defines when [`CheckpointScheduler`] persists registered tables. Table is
+/// persisted when any of the set conditions is met.
+#[derive(Clone, Copy, Debug)]
+pub struct CheckpointConfig {
+    /// Table is persisted when this time passed since its last checkpoint
+    /// and table was changed.
+    pub interval: Option<Duration>,
+
+    /// Table is persisted after this count of writes since its last
+    /// checkpoint.
+    pub writes: Option<u64>,
+
+    /// How often conditions are checked.
+    pub check_interval: Duration,
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            interval: Some(Duration::from_secs(60)),
+            writes: None,
+            check_interval: Duration::from_millis(100),
+        }
+    }
+}
+
+struct RegisteredTable {
+    table: Weak<dyn Backupable>,
+    /// Count of the table's writes when its last checkpoint was started.
+    writes: u64,
+    /// Time when table's last checkpoint was finished.
+    checkpointed_at: Instant,
+    /// Set if table's last checkpoint failed.
+    retry_at: Option<Instant>,
+}
+
+impl RegisteredTable {
+    fn new(table: &Weak<dyn Backupable>) -> Option<Self> {
+        let writes = table.upgrade()?.writes_count();
+        Some(Self {
+            table: table.clone(),
+            writes,
+            checkpointed_at: Instant::now(),
+            retry_at: None,
+        })
+    }
+
+    fn is_due(&self, table: &dyn Backupable, config: &CheckpointConfig) -> bool {
+        if self.retry_at.is_some_and(|t| Instant::now() < t) {
+            return false;
+        }
+        let writes = table.writes_count() - self.writes;
+        config
+            .interval
+            .is_some_and(|i| writes != 0 && self.checkpointed_at.elapsed() >= i)
+            || config.writes.is_some_and(|w| writes >= w)
+    }
+}
+
+/// Background task that persists tables registered in the
+/// [`DatabaseManager`] according to the [`CheckpointConfig`]. Tables are
+/// persisted one by one on the blocking threads of the tokio runtime. Results
+/// and durations of the checkpoints are reported via `tracing`.
+///
+/// Task is stopped when scheduler is dropped.
+///
+/// [`DatabaseManager`]: crate::prelude::DatabaseManager
+pub struct CheckpointScheduler {
+    stop: watch::Sender<bool>,
+    handle: JoinHandle<()>,
+}
+
+impl CheckpointScheduler {
+    /// Starts scheduler on the current tokio runtime. Scheduler checks
+    /// `registry` on each check, so tables registered after the start are
+    /// persisted too. Writes of the table are counted from the moment
+    /// scheduler found it in the `registry`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of the tokio runtime.
+    pub(crate) fn start(config: CheckpointConfig, registry: TableRegistry) -> Self {
+        let mut tables = Vec::new();
+        Self::sync(&registry, &mut tables);
+        let (stop, stopped) = watch::channel(false);
+        let handle = tokio::spawn(Self::run(config, registry, tables, stopped));
+
+        Self { stop, handle }
+    }
+
+    /// Stops scheduler and waits until checkpoint in progress is finished.
+    pub async fn stop(self) {
+        let _ = self.stop.send(true);
+        let _ = self.handle.await;
+    }
+
+    /// Adds tables that were registered since the last call and removes
+    /// tables that were dropped.
+    fn sync(registry: &Mutex<Vec<Weak<dyn Backupable>>>, tables: &mut Vec<RegisteredTable>) {
+        let registry = registry.lock().unwrap();
+        tables
+            .retain(|t| t.table.strong_count() != 0 && registry.iter().any(|r| r.ptr_eq(&t.table)));
+        for table in registry.iter() {
+            if !tables.iter().any(|t| t.table.ptr_eq(table)) {
+                tables.extend(RegisteredTable::new(table));
+            }
+        }
+    }
+
+    async fn run(
+        config: CheckpointConfig,
+        registry: TableRegistry,
+        mut tables: Vec<RegisteredTable>,
+        mut stopped: watch::Receiver<bool>,
+    ) {
+        loop {
+            tokio::select! {
+                _ = stopped.changed() => break,
+                _ = tokio::time::sleep(config.check_interval) => {}
+            }
+
+            Self::sync(&registry, &mut tables);
+            let due = tables
+                .iter()
+                .enumerate()
+                .filter_map(|(i, t)| {
+                    let table = t.table.upgrade()?;
+                    t.is_due(table.as_ref(), &config).then_some((i, table))
+                })
+                .collect::<Vec<_>>();
+            for (i, table) in due {
+                let writes = table.writes_count();
+                let started = Instant::now();
+                let name = table.table_name().to_string();
+                let res = tokio::task::spawn_blocking(move || table.checkpoint()).await;
+                let elapsed = started.elapsed();
+                let registered = &mut tables[i];
+                registered.checkpointed_at = Instant::now();
+                registered.retry_at = None;
+                match res {
+                    Ok(Ok(())) => {
+                        tracing::info!(
+                            table = %name,
+                            writes,
+                            elapsed_ms = elapsed.as_millis() as u64,
+                            "checkpoint finished"
+                        );
+                        registered.writes = writes;
+                    }
+                    Ok(Err(e)) => {
+                        tracing::error!(
+                            table = %name,
+                            elapsed_ms = elapsed.as_millis() as u64,
+                            error = %e,
+                            "checkpoint failed"
+                        );
+                        registered.retry_at = Some(Instant::now() + RETRY_DELAY);
+                    }
+                    Err(e) => {
+                        tracing::error!(table = %name, error = %e, "checkpoint panicked");
+                        registered.retry_at = Some(Instant::now() + RETRY_DELAY);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::File;
+
+use data_bucket::{parse_data_page, Link, PageType, GENERAL_HEADER_SIZE};
+
+use crate::database::{PageChecksums, SpaceSchema};
+
+/// Table whose `.wt` files can be inspected. Implemented for tables that
+/// derive `PersistTable`.
+pub trait InspectSpace {
+    type Row;
+
+    /// Name of the table's file without extension.
+    const FILE_NAME: &'static str;
+
+    /// Returns report of the file's structure, including its space info and
+    /// index contents.
+    fn inspect(file: &mut File) -> eyre::Result<SpaceReport>;
+
+    /// Returns rows stored in the file in primary key order.
+    fn read_rows(file: &mut File) -> eyre::Result<Vec<Self::Row>>;
+}
+
+/// Page of the `.wt` file described by its header.
+#[derive(Clone, Debug)]
+pub struct PageSummary {
+    pub page_id: u32,
+    /// Id written to the page's header, which must match its place in the
+    /// file.
+    pub header_page_id: u32,
+    pub page_type: PageType,
+    pub previous_id: u32,
+    pub next_id: u32,
+    pub data_length: u32,
+    /// `None` if the file has no checksums.
+    pub checksum_valid: Option<bool>,
+}
+
+impl PageSummary {
+    /// Returns used part of the page in percents.
+    pub fn fill(&self, page_size: usize) -> f64 {
+        (GENERAL_HEADER_SIZE + self.data_length as usize) as f64 * 100.0 / page_size as f64
+    }
+}
+
+/// Sections of the `.wt` file listed in its space info page as inclusive
+/// intervals of page ids.
+#[derive(Clone, Debug, Default)]
+pub struct SpaceSections {
+    pub primary_key: Vec<(usize, usize)>,
+    pub secondary_indexes: BTreeMap<String, Vec<(usize, usize)>>,
+    pub data: Vec<(usize, usize)>,
+}
+
+/// Entries of the table's index with keys formatted by `Debug`.
+#[derive(Clone, Debug)]
+pub struct IndexEntries {
+    pub name: String,
+    pub entries: Vec<(String, Vec<Link>)>,
+}
+
+/// Report of the `.wt` file's structure.
+///
+/// Pages are described by their headers, so report can be read without the
+/// table's type. Space info, sections and index contents are added only if
+/// the table's type is known.
+#[derive(Clone, Debug, Default)]
+pub struct SpaceReport {
+    pub page_size: usize,
+    pub schema: Option<SpaceSchema>,
+    pub pages: Vec<PageSummary>,
+    /// `Debug` representation of the space info page's data.
+    pub info: Option<String>,
+    pub sections: Option<SpaceSections>,
+    pub empty_links: Vec<Link>,
+    pub indexes: Vec<IndexEntries>,
+    /// Problems found in the file's structure.
+    pub problems: Vec<String>,
+}
+
+impl SpaceReport {
+    /// Reads headers of the file's pages, its schema and checksums.
+    pub fn read<const PAGE_SIZE: usize, const INNER_SIZE: usize>(
+        file: &mut File,
+    ) -> eyre::Result<Self> {
+        let mut report = Self {
+            page_size: PAGE_SIZE,
+            ..Default::default()
+        };
+        let checksums = match PageChecksums::read(file, PAGE_SIZE) {
+            Ok(checksums) => checksums,
+            Err(e) => {
+                report.problems.push(e.to_string());
+                None
+            }
+        };
+        match SpaceSchema::read(file, PAGE_SIZE) {
+            Ok(schema) => report.schema = schema,
+            Err(e) => report.problems.push(e.to_string()),
+        }
+
+        let pages_length = match &checksums {
+            Some(checksums) => checksums.pages_length(),
+            None => file.metadata()?.len(),
+        };
+        for page_id in 0..pages_length.div_ceil(PAGE_SIZE as u64) as u32 {
+            let checksum_valid = match &checksums {
+                Some(checksums) => Some(checksums.is_valid(file, page_id)?),
+                None => None,
+            };
+            if checksum_valid == Some(false) {
+                report
+                    .problems
+                    .push(format!("page {} doesn't match its checksum", page_id));
+            }
+            let page = match parse_data_page::<PAGE_SIZE, INNER_SIZE>(file, page_id) {
+                Ok(page) => page,
+                Err(e) => {
+                    report
+                        .problems
+                        .push(format!("header of page {} can't be read: {}", page_id, e));
+                    continue;
+                }
+            };
+            let header = page.header;
+            let header_page_id: usize = header.page_id.into();
+            let previous_id: usize = header.previous_id.into();
+            let next_id: usize = header.next_id.into();
+            report.pages.push(PageSummary {
+                page_id,
+                header_page_id: header_page_id as u32,
+                page_type: header.page_type,
+                previous_id: previous_id as u32,
+                next_id: next_id as u32,
+                data_length: header.data_length,
+                checksum_valid,
+            });
+        }
+        for page in &report.pages {
+            if page.header_page_id != page.page_id {
+                report.problems.push(format!(
+                    "page {} has id {} in its header",
+                    page.page_id, page.header_page_id
+                ));
+            }
+        }
+        match report.pages.iter().find(|p| p.page_id == 0) {
+            Some(page) if page.page_type != PageType::SpaceInfo => {
+                report.problems.push(format!(
+                    "page 0 has type {:?} instead of SpaceInfo",
+                    page.page_type
+                ));
+            }
+            Some(_) => {}
+            None => report
+                .problems
+                .push("space info page can't be read".to_string()),
+        }
+
+        Ok(report)
+    }
+
+    /// Sets sections of the file and checks that they point to the pages of
+    /// the expected types and don't overlap.
+    pub fn set_sections(&mut self, sections: SpaceSections) {
+        let mut section_names = vec![
+            (
+                "primary key".to_string(),
+                PageType::Index,
+                &sections.primary_key,
+            ),
+            ("data".to_string(), PageType::Data, &sections.data),
+        ];
+        for (name, intervals) in &sections.secondary_indexes {
+            section_names.push((format!("index {}", name), PageType::Index, intervals));
+        }
+
+        let mut owners: HashMap<usize, String> = HashMap::new();
+        for (name, page_type, intervals) in section_names {
+            for (start, end) in intervals {
+                for page_id in *start..=*end {
+                    if let Some(owner) = owners.insert(page_id, name.clone()) {
+                        self.problems.push(format!(
+                            "page {} belongs to both {} and {}",
+                            page_id, owner, name
+                        ));
+                    }
+                    match self.pages.iter().find(|p| p.page_id as usize == page_id) {
+                        Some(page) if page.page_type != page_type => {
+                            self.problems.push(format!(
+                                "page {} of {} has type {:?} instead of {:?}",
+                                page_id, name, page.page_type, page_type
+                            ));
+                        }
+                        Some(_) => {}
+                        None => self
+                            .problems
+                            .push(format!("page {} of {} is not in the file", page_id, name)),
+                    }
+                }
+            }
+        }
+        self.sections = Some(sections);
+    }
+
+    /// Returns pages that don't belong to any section. Such pages are freed
+    /// by shrunk sections and are reused by the next persists.
+    pub fn free_pages(&self) -> Vec<u32> {
+        let Some(sections) = &self.sections else {
+            return vec![];
+        };
+        let intervals = sections
+            .primary_key
+            .iter()
+            .chain(&sections.data)
+            .chain(sections.secondary_indexes.values().flatten())
+            .collect::<Vec<_>>();
+        self.pages
+            .iter()
+            .map(|p| p.page_id)
+            .filter(|&id| id != 0)
+            .filter(|&id| {
+                !intervals
+                    .iter()
+                    .any(|(start, end)| (*start..=*end).contains(&(id as usize)))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for SpaceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "page size: {}", self.page_size)?;
+        match &self.schema {
+            Some(schema) => {
+                writeln!(f, "schema version: {}", schema.version)?;
+                let columns = schema
+                    .columns
+                    .iter()
+                    .map(|(name, type_)| format!("{}: {}", name, type_))
+                    .collect::<Vec<_>>();
+                writeln!(f, "columns: {}", columns.join(", "))?;
+            }
+            None => writeln!(f, "schema: none")?,
+        }
+        if let Some(info) = &self.info {
+            writeln!(f, "space info: {}", info)?;
+        }
+        if let Some(sections) = &self.sections {
+            let format = |intervals: &[(usize, usize)]| {
+                intervals
+                    .iter()
+                    .map(|(start, end)| format!("{}..={}", start, end))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            writeln!(f, "sections:")?;
+            writeln!(f, "  primary key: {}", format(&sections.primary_key))?;
+            for (name, intervals) in &sections.secondary_indexes {
+                writeln!(f, "  index {}: {}", name, format(intervals))?;
+            }
+            writeln!(f, "  data: {}", format(&sections.data))?;
+            writeln!(f, "free pages: {:?}", self.free_pages())?;
+            writeln!(f, "empty links: {:?}", self.empty_links)?;
+        }
+
+        writeln!(f, "pages:")?;
+        for page in &self.pages {
+            let checksum = match page.checksum_valid {
+                Some(true) => "ok",
+                Some(false) => "corrupted",
+                None => "none",
+            };
+            writeln!(
+                f,
+                "  {:>6} {:<10} previous {:>6} next {:>6} length {:>6} fill {:>5.1}% checksum {}",
+                page.page_id,
+                format!("{:?}", page.page_type),
+                page.previous_id,
+                page.next_id,
+                page.data_length,
+                page.fill(self.page_size),
+                checksum
+            )?;
+        }
+
+        for index in &self.indexes {
+            writeln!(f, "index {} ({} keys):", index.name, index.entries.len())?;
+            for (key, links) in &index.entries {
+                writeln!(f, "  {} -> {:?}", key, links)?;
+            }
+        }
+
+        if self.problems.is_empty() {
+            writeln!(f, "no problems found")?;
+        } else {
+            writeln!(f, "problems:")?;
+            for problem in &self.problems {
+                writeln!(f, "  {}", problem)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,3 +1,24 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::SystemTime;
+
+use once_cell::sync::Lazy;
+
+use crate::database::backup::{
+    BackupEntry, BackupManifest, Backupable, RestoredBackup, BACKUP_MANIFEST,
+};
+use crate::database::checkpoint::{CheckpointConfig, CheckpointScheduler};
+use crate::database::{SpaceFile, Wal};
+
+/// Tables registered in the managers, by the managers' `database_files_dir`.
+pub(crate) type TableRegistry = Arc<Mutex<Vec<Weak<dyn Backupable>>>>;
+
+/// Registered tables are kept outside of the [`DatabaseManager`], so it stays
+/// two strings and managers with the same `database_files_dir` share them.
+/// Tables hold the manager, so they are not kept alive by it.
+static REGISTRIES: Lazy<Mutex<HashMap<String, TableRegistry>>> = Lazy::new(Default::default);
+
 // This manager is used to share common table information.
 #[derive(Debug, Clone)]
 pub struct DatabaseManager {
@@ -12,4 +33,133 @@ impl DatabaseManager {
             database_files_dir,
         }
     }
+
+    /// Returns tables registered for backups and checkpoints.
+    fn tables(&self) -> TableRegistry {
+        REGISTRIES
+            .lock()
+            .unwrap()
+            .entry(self.database_files_dir.clone())
+            .or_default()
+            .clone()
+    }
+
+    /// Starts [`CheckpointScheduler`] that persists tables registered with
+    /// [`DatabaseManager::register`], including ones registered after the
+    /// start.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of the tokio runtime.
+    pub fn start_checkpoints(&self, config: CheckpointConfig) -> CheckpointScheduler {
+        CheckpointScheduler::start(config, self.tables())
+    }
+
+    /// Adds table to the ones included in backups and persisted by
+    /// checkpoints. Dropped tables are removed from the registered ones.
+    /// Tables are registered for the `database_files_dir`, so they are seen
+    /// by all managers with it.
+    pub fn register<T: Backupable + 'static>(&self, table: &Arc<T>) {
+        let table: Weak<dyn Backupable> = Arc::downgrade(table) as Weak<dyn Backupable>;
+        let tables = self.tables();
+        let mut tables = tables.lock().unwrap();
+        if !tables.iter().any(|t| t.ptr_eq(&table)) {
+            tables.push(table);
+        }
+    }
+
+    /// Returns live registered tables.
+    fn registered_tables(&self) -> Vec<Arc<dyn Backupable>> {
+        let tables = self.tables();
+        let mut tables = tables.lock().unwrap();
+        tables.retain(|t| t.strong_count() != 0);
+        tables.iter().filter_map(Weak::upgrade).collect()
+    }
+
+    /// Writes files of all registered tables and their [`BackupManifest`] to
+    /// the `dest` directory.
+    ///
+    /// Writes of all tables are paused together only while their state is
+    /// captured, so backup has state of all tables at one moment and writes
+    /// continue while it's written. Tables' logs are not needed to load the
+    /// backup. Fails if `dest` already contains backup.
+    pub fn backup(&self, dest: impl AsRef<Path>) -> eyre::Result<BackupManifest> {
+        let dest = dest.as_ref();
+        if dest.join(BACKUP_MANIFEST).exists() {
+            eyre::bail!("{} already contains backup", dest.display())
+        }
+        let tables = self.registered_tables();
+        for (i, table) in tables.iter().enumerate() {
+            if tables[..i]
+                .iter()
+                .any(|t| t.file_name() == table.file_name())
+            {
+                eyre::bail!(
+                    "more than one registered table has file {}",
+                    table.file_name()
+                )
+            }
+        }
+        std::fs::create_dir_all(dest)?;
+
+        let dir = dest.to_string_lossy().to_string();
+        let (created_at, captured) = {
+            // Tables are always paused in the registration order, so
+            // concurrent backups can't deadlock.
+            let _pauses = tables.iter().map(|t| t.pause_writes()).collect::<Vec<_>>();
+            let created_at = SystemTime::now();
+            let captured = tables
+                .iter()
+                .map(|t| Ok((t.capture(dir.as_str())?, t.writes_count())))
+                .collect::<eyre::Result<Vec<_>>>()?;
+            (created_at, captured)
+        };
+
+        let mut entries = Vec::with_capacity(tables.len());
+        for (table, (mut space, writes)) in tables.iter().zip(captured) {
+            space.persist()?;
+            // File left by the incomplete backup to the same directory.
+            let space_file = SpaceFile::new(dest.join(table.file_name()));
+            if space_file.previous_path().exists() {
+                std::fs::remove_file(space_file.previous_path())?;
+            }
+            entries.push(BackupEntry::new(table.as_ref(), writes, dest)?);
+        }
+        let manifest = BackupManifest::new(created_at, entries);
+        manifest.write(dest)?;
+
+        Ok(manifest)
+    }
+
+    /// Verifies the backup in the `src` directory and copies its files to
+    /// the `database_files_dir`, replacing tables' files and removing their
+    /// logs. Restored tables are loaded with the returned [`RestoredBackup`].
+    ///
+    /// Nothing is copied if backup fails verification. Fails if some of the
+    /// backup's tables is registered, as live table would overwrite the
+    /// restored file.
+    pub fn restore(self: &Arc<Self>, src: impl AsRef<Path>) -> eyre::Result<RestoredBackup> {
+        let src = src.as_ref();
+        let manifest = BackupManifest::read(src)?;
+        manifest.verify(src)?;
+        let tables = self.registered_tables();
+        if let Some(entry) = manifest
+            .tables
+            .iter()
+            .find(|e| tables.iter().any(|t| t.file_name() == e.file))
+        {
+            eyre::bail!(
+                "table {} can't be restored while it's registered",
+                entry.table
+            )
+        }
+
+        let dir = Path::new(self.database_files_dir.as_str());
+        for entry in &manifest.tables {
+            SpaceFile::new(dir.join(&entry.file)).replace_with(src.join(&entry.file))?;
+            Wal::remove(dir.join(entry.wal_file()))?;
+        }
+
+        Ok(RestoredBackup::new(manifest, self.clone()))
+    }
 }
@@ -1,4 +1,28 @@
+mod backup;
+mod checkpoint;
+mod checksum;
 mod config;
+mod inspect;
 mod manager;
+mod page_checksums;
+mod page_journal;
+mod page_source;
+mod space_file;
+mod space_layout;
+mod space_schema;
+mod wal;
 
+pub use backup::{
+    BackupEntry, BackupManifest, Backupable, CapturedSpace, Restorable, RestoredBackup,
+    BACKUP_MANIFEST,
+};
+pub use checkpoint::{CheckpointConfig, CheckpointScheduler, Checkpointable};
+pub use inspect::{IndexEntries, InspectSpace, PageSummary, SpaceReport, SpaceSections};
 pub use manager::DatabaseManager;
+pub use page_checksums::{CorruptedPage, PageChecksums, PageVerifier};
+pub use page_journal::PageJournal;
+pub use page_source::FilePageSource;
+pub use space_file::{FileGeneration, SpaceFile};
+pub use space_layout::SpaceLayout;
+pub use space_schema::SpaceSchema;
+pub use wal::{Wal, WalOperation, WalRecord, WalSyncPolicy, WalWrite};
@@ -0,0 +1,294 @@
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use data_bucket::page::PageId;
+use data_bucket::{GeneralPage, PageType};
+
+use crate::database::checksum::crc32;
+
+/// Magic bytes that end the checksums footer.
+const FOOTER_MAGIC: [u8; 8] = *b"WTCRC001";
+
+/// Size of the footer's fixed part: pages count, checksum of the footer and
+/// magic bytes.
+const TRAILER_SIZE: usize = 16;
+
+/// CRC-32 checksums of the `.wt` file's pages.
+///
+/// Checksums are written to the footer after the pages as
+/// `[crc32: u32; count][count: u32][crc32 of checksums and count: u32][magic]`.
+/// Page's checksum covers its bytes in the file, so any change of them is
+/// detected when the page is verified. Files written without footer are not
+/// verified.
+#[derive(Clone, Debug)]
+pub struct PageChecksums {
+    page_size: usize,
+    checksums: Vec<u32>,
+    /// Length of the file's part with pages.
+    pages_length: u64,
+}
+
+impl PageChecksums {
+    /// Reads checksums from the file's footer. Returns `None` if the file has
+    /// no footer.
+    pub fn read(file: &mut File, page_size: usize) -> eyre::Result<Option<Self>> {
+        let Some(footer_length) = Self::footer_length(file)? else {
+            return Ok(None);
+        };
+        let length = file.metadata()?.len();
+        let mut footer = vec![0; footer_length as usize];
+        file.seek(SeekFrom::Start(length - footer_length))?;
+        file.read_exact(&mut footer)?;
+
+        let checked = footer.len() - TRAILER_SIZE + 4;
+        let checksum = u32::from_le_bytes(footer[checked..checked + 4].try_into().unwrap());
+        if crc32(&footer[..checked]) != checksum {
+            eyre::bail!("checksums of the table's pages are corrupted")
+        }
+        let checksums = footer[..checked - 4]
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+
+        Ok(Some(Self {
+            page_size,
+            checksums,
+            pages_length: length - footer_length,
+        }))
+    }
+
+    /// Calculates checksums of the file's pages and appends footer with them
+    /// to the file. File must have no footer.
+    pub fn write(file: &mut File, page_size: usize) -> eyre::Result<()> {
+        let pages_length = file.metadata()?.len();
+        let mut checksums = Self {
+            page_size,
+            checksums: vec![],
+            pages_length,
+        };
+        checksums.checksums = (0..pages_length.div_ceil(page_size as u64) as u32)
+            .map(|page_id| checksums.page_checksum(file, page_id))
+            .collect::<eyre::Result<_>>()?;
+
+        checksums.append(file)
+    }
+
+    /// Updates checksums of the file whose footer was removed and whose
+    /// `pages` were written to their places, then appends footer with them
+    /// to the file. File's part with pages now has `pages_length`, which
+    /// can't be less than before. Pages are full pages. Checksums of the
+    /// other pages added or changed by the file's extension are calculated
+    /// from the file.
+    pub fn update(
+        &mut self,
+        file: &mut File,
+        pages_length: u64,
+        pages: &[(u32, Vec<u8>)],
+    ) -> eyre::Result<()> {
+        if pages_length < self.pages_length {
+            eyre::bail!("file's pages can't be shrunk by update of the checksums")
+        }
+        let page_size = self.page_size as u64;
+        let count = pages_length.div_ceil(page_size);
+        let mut stale = (self.checksums.len() as u32..count as u32).collect::<BTreeSet<_>>();
+        if !self.pages_length.is_multiple_of(page_size) && pages_length > self.pages_length {
+            // Partly written last page is padded now.
+            stale.insert((self.pages_length / page_size) as u32);
+        }
+        self.pages_length = pages_length;
+        self.checksums.resize(count as usize, 0);
+        for (page_id, page) in pages {
+            self.checksums[*page_id as usize] = crc32(page);
+            stale.remove(page_id);
+        }
+        for page_id in stale {
+            self.checksums[page_id as usize] = self.page_checksum(file, page_id)?;
+        }
+
+        self.append(file)
+    }
+
+    /// Writes footer with checksums right after the file's pages.
+    fn append(&self, file: &mut File) -> eyre::Result<()> {
+        let mut footer = Vec::with_capacity(self.checksums.len() * 4 + TRAILER_SIZE);
+        for checksum in &self.checksums {
+            footer.extend_from_slice(&checksum.to_le_bytes());
+        }
+        footer.extend_from_slice(&(self.checksums.len() as u32).to_le_bytes());
+        footer.extend_from_slice(&crc32(&footer).to_le_bytes());
+        footer.extend_from_slice(&FOOTER_MAGIC);
+        file.seek(SeekFrom::Start(self.pages_length))?;
+        file.write_all(&footer)?;
+
+        Ok(())
+    }
+
+    /// Calculates checksum of the page's bytes in the file.
+    fn page_checksum(&self, file: &mut File, page_id: u32) -> eyre::Result<u32> {
+        let start = page_id as u64 * self.page_size as u64;
+        let page_length = (self.pages_length - start).min(self.page_size as u64) as usize;
+        let mut page = vec![0; page_length];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut page)?;
+
+        Ok(crc32(&page))
+    }
+
+    /// Removes footer from the file, so pages can be written to it.
+    pub fn remove(file: &mut File) -> eyre::Result<()> {
+        if let Some(footer_length) = Self::footer_length(file)? {
+            let length = file.metadata()?.len();
+            file.set_len(length - footer_length)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns length of the file's footer or `None` if it has no footer.
+    fn footer_length(file: &mut File) -> eyre::Result<Option<u64>> {
+        let length = file.metadata()?.len();
+        if length < TRAILER_SIZE as u64 {
+            return Ok(None);
+        }
+        let mut trailer = [0; TRAILER_SIZE];
+        file.seek(SeekFrom::Start(length - TRAILER_SIZE as u64))?;
+        file.read_exact(&mut trailer)?;
+        if trailer[8..] != FOOTER_MAGIC {
+            return Ok(None);
+        }
+        let count = u32::from_le_bytes(trailer[..4].try_into().unwrap()) as u64;
+        let footer_length = count * 4 + TRAILER_SIZE as u64;
+        if footer_length > length {
+            eyre::bail!("checksums of the table's pages are corrupted")
+        }
+
+        Ok(Some(footer_length))
+    }
+
+    /// Returns length of the file's part with pages.
+    pub fn pages_length(&self) -> u64 {
+        self.pages_length
+    }
+
+    /// Returns `true` if the page's bytes match its checksum.
+    pub fn is_valid(&self, file: &mut File, page_id: u32) -> eyre::Result<bool> {
+        let Some(checksum) = self.checksums.get(page_id as usize) else {
+            return Ok(false);
+        };
+
+        Ok(self.page_checksum(file, page_id)? == *checksum)
+    }
+}
+
+/// Page of the `.wt` file that doesn't match its checksum.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CorruptedPage {
+    pub page_id: u32,
+    pub page_type: PageType,
+}
+
+impl CorruptedPage {
+    /// Returns in-memory ids of the corrupted data pages. Data pages are
+    /// numbered in the order of the file's data intervals, so `data` must
+    /// contain all of them, with corrupted ones replaced by empty pages.
+    pub fn lost_data_pages<T>(corrupted: &[CorruptedPage], data: &[GeneralPage<T>]) -> Vec<PageId> {
+        data.iter()
+            .enumerate()
+            .filter(|(_, page)| {
+                let page_id: usize = page.header.page_id.into();
+                corrupted
+                    .iter()
+                    .any(|c| c.page_type == PageType::Data && c.page_id as usize == page_id)
+            })
+            .map(|(i, _)| (i as u32).into())
+            .collect()
+    }
+}
+
+/// Verifies pages of the `.wt` file while it's parsed.
+#[derive(Debug)]
+pub struct PageVerifier {
+    checksums: Option<PageChecksums>,
+    /// If set, corrupted pages are recorded instead of failing the parse.
+    skip_corrupted: bool,
+    corrupted: Vec<CorruptedPage>,
+}
+
+impl PageVerifier {
+    pub fn new(file: &mut File, page_size: usize, skip_corrupted: bool) -> eyre::Result<Self> {
+        Ok(Self {
+            checksums: PageChecksums::read(file, page_size)?,
+            skip_corrupted,
+            corrupted: vec![],
+        })
+    }
+
+    /// Verifies page before it's parsed. Corrupted page is an error or, if
+    /// corrupted pages are skipped, it's reported via `tracing` and `false`
+    /// is returned.
+    pub fn check(
+        &mut self,
+        file: &mut File,
+        page_id: u32,
+        page_type: PageType,
+    ) -> eyre::Result<bool> {
+        let Some(checksums) = &self.checksums else {
+            return Ok(true);
+        };
+        if checksums.is_valid(file, page_id)? {
+            return Ok(true);
+        }
+        if !self.skip_corrupted {
+            eyre::bail!("page {} of type {:?} is corrupted", page_id, page_type)
+        }
+        tracing::warn!(page_id, page_type = ?page_type, "corrupted page is skipped");
+        self.corrupted.push(CorruptedPage { page_id, page_type });
+
+        Ok(false)
+    }
+
+    /// Returns pages that were skipped.
+    pub fn into_corrupted(self) -> Vec<CorruptedPage> {
+        self.corrupted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Seek, SeekFrom, Write};
+
+    use crate::database::page_checksums::PageChecksums;
+
+    #[test]
+    fn changed_page_is_detected() {
+        let path = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(&[1; 40]).unwrap();
+        assert!(PageChecksums::read(&mut file, 16).unwrap().is_none());
+
+        PageChecksums::write(&mut file, 16).unwrap();
+        let checksums = PageChecksums::read(&mut file, 16).unwrap().unwrap();
+        assert!((0..3).all(|i| checksums.is_valid(&mut file, i).unwrap()));
+        assert!(!checksums.is_valid(&mut file, 3).unwrap());
+
+        file.seek(SeekFrom::Start(20)).unwrap();
+        file.write_all(&[2]).unwrap();
+        let checksums = PageChecksums::read(&mut file, 16).unwrap().unwrap();
+        assert!(checksums.is_valid(&mut file, 0).unwrap());
+        assert!(!checksums.is_valid(&mut file, 1).unwrap());
+        assert!(checksums.is_valid(&mut file, 2).unwrap());
+
+        PageChecksums::remove(&mut file).unwrap();
+        assert_eq!(file.metadata().unwrap().len(), 40);
+        assert!(PageChecksums::read(&mut file, 16).unwrap().is_none());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use data_bucket::{persist_page, GeneralPage, Persistable};
+
+use crate::database::checksum::crc32;
+use crate::database::SpaceSchema;
+
+/// Magic bytes that end the sealed journal.
+const JOURNAL_MAGIC: [u8; 8] = *b"WTJRN001";
+
+/// Size of the journal's trailer: length of the file's pages, pages count,
+/// page size, checksum of the journal and magic bytes.
+const TRAILER_SIZE: usize = 28;
+
+/// Pages of the incremental persist that are written to the journal before
+/// they are written to their places in the `.wt` file.
+///
+/// Journal is `[page; count][page_id: u32; count][pages_length: u64]
+/// [count: u32][page_size: u32][crc32 of all previous bytes: u32][magic]`,
+/// where `pages_length` is length of the file's part with pages after the
+/// journal is applied. Journal is sealed by its trailer and flushed before
+/// its pages are written to the file, so crash while they are written leaves
+/// the journal that is applied again on load. Journal that is not sealed is
+/// discarded, as the file wasn't changed yet.
+#[derive(Debug)]
+pub struct PageJournal {
+    file: File,
+    page_size: usize,
+    /// Ids of the file pages in the journal's pages order.
+    page_ids: Vec<u32>,
+    /// Indexes of the written pages in the journal by their file page ids.
+    slots: HashMap<u32, usize>,
+}
+
+/// Pages of the sealed [`PageJournal`].
+#[derive(Debug)]
+pub(crate) struct JournalPages {
+    pub page_size: usize,
+    /// Length of the file's part with pages after the journal is applied.
+    pub pages_length: u64,
+    /// File page ids with pages' bytes.
+    pub pages: Vec<(u32, Vec<u8>)>,
+}
+
+impl PageJournal {
+    /// Creates empty journal at `path`, replacing the existing one.
+    pub(crate) fn create(path: &Path, page_size: usize) -> eyre::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(path)?;
+
+        Ok(Self {
+            file,
+            page_size,
+            page_ids: vec![],
+            slots: HashMap::new(),
+        })
+    }
+
+    /// Writes page to the journal. Page written again replaces its previous
+    /// version.
+    pub fn write_page<T: Persistable>(&mut self, page: &mut GeneralPage<T>) -> eyre::Result<()> {
+        let page_id: usize = page.header.page_id.into();
+        let page_id = page_id as u32;
+        let slot = *self.slots.entry(page_id).or_insert_with(|| {
+            self.page_ids.push(page_id);
+            self.page_ids.len() - 1
+        });
+        self.file
+            .seek(SeekFrom::Start(slot as u64 * self.page_size as u64))?;
+        persist_page(page, &mut self.file)?;
+
+        Ok(())
+    }
+
+    /// Writes schema to the end of the space info page. Space info page must
+    /// be the first page written to the journal.
+    pub fn write_schema(&mut self, schema: &SpaceSchema, info_length: usize) -> eyre::Result<()> {
+        if self.page_ids.first() != Some(&0) {
+            eyre::bail!("space info page must be written to the journal before schema")
+        }
+        schema.write(&mut self.file, self.page_size, info_length)
+    }
+
+    /// Appends trailer to the journal and flushes it. `pages_length` is the
+    /// current length of the file's part with pages, it's extended to fit
+    /// the journal's pages.
+    pub(crate) fn seal(&mut self, pages_length: u64) -> eyre::Result<()> {
+        let page_size = self.page_size as u64;
+        // Last page can be written partly, so it's padded.
+        self.file.set_len(self.page_ids.len() as u64 * page_size)?;
+        let pages_length = self
+            .page_ids
+            .iter()
+            .map(|id| (*id as u64 + 1) * page_size)
+            .fold(pages_length, u64::max);
+
+        let mut bytes = vec![];
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.read_to_end(&mut bytes)?;
+        let pages_end = bytes.len();
+        for page_id in &self.page_ids {
+            bytes.extend_from_slice(&page_id.to_le_bytes());
+        }
+        bytes.extend_from_slice(&pages_length.to_le_bytes());
+        bytes.extend_from_slice(&(self.page_ids.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.page_size as u32).to_le_bytes());
+        let checksum = crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes.extend_from_slice(&JOURNAL_MAGIC);
+        self.file.write_all(&bytes[pages_end..])?;
+        self.file.sync_all()?;
+
+        Ok(())
+    }
+
+    /// Reads sealed journal at `path`. Returns `None` if there is no journal
+    /// or it's not sealed.
+    pub(crate) fn read(path: &Path) -> eyre::Result<Option<JournalPages>> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if bytes.len() < TRAILER_SIZE || bytes[bytes.len() - 8..] != JOURNAL_MAGIC {
+            return Ok(None);
+        }
+        let trailer = &bytes[bytes.len() - TRAILER_SIZE..];
+        let checked = bytes.len() - 12;
+        let checksum = u32::from_le_bytes(trailer[16..20].try_into().unwrap());
+        if crc32(&bytes[..checked]) != checksum {
+            return Ok(None);
+        }
+        let pages_length = u64::from_le_bytes(trailer[..8].try_into().unwrap());
+        let count = u32::from_le_bytes(trailer[8..12].try_into().unwrap()) as usize;
+        let page_size = u32::from_le_bytes(trailer[12..16].try_into().unwrap()) as usize;
+        let pages_end = count * page_size;
+        if page_size == 0 || pages_end + count * 4 + TRAILER_SIZE != bytes.len() {
+            return Ok(None);
+        }
+        let pages = bytes[pages_end..pages_end + count * 4]
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+            .zip(
+                bytes[..pages_end]
+                    .chunks_exact(page_size)
+                    .map(<[u8]>::to_vec),
+            )
+            .collect();
+
+        Ok(Some(JournalPages {
+            page_size,
+            pages_length,
+            pages,
+        }))
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+}
@@ -0,0 +1,75 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use data_bucket::{parse_data_page, DataPage, GeneralPage, PageType};
+
+use crate::database::PageChecksums;
+use crate::in_memory::PageSource;
+
+/// Data pages of the table's `.wt` file that are read on first access.
+///
+/// Pages are verified against the file's [`PageChecksums`] when they are
+/// read, so corrupted page fails the access instead of being loaded.
+#[derive(Debug)]
+pub struct FilePageSource<const PAGE_SIZE: usize, const DATA_LENGTH: usize> {
+    path: PathBuf,
+    file: Mutex<File>,
+    checksums: Option<PageChecksums>,
+    /// Ids of the file pages in the in-memory data pages order.
+    page_ids: Vec<u32>,
+}
+
+impl<const PAGE_SIZE: usize, const DATA_LENGTH: usize> FilePageSource<PAGE_SIZE, DATA_LENGTH> {
+    /// Opens the file with data pages placed to the `page_ids` pages.
+    pub fn open(path: impl AsRef<Path>, page_ids: Vec<u32>) -> eyre::Result<Self> {
+        let mut file = File::open(path.as_ref())?;
+        let checksums = PageChecksums::read(&mut file, PAGE_SIZE)?;
+
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            file: Mutex::new(file),
+            checksums,
+            page_ids,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    pub fn page_ids(&self) -> &[u32] {
+        self.page_ids.as_slice()
+    }
+}
+
+impl<const PAGE_SIZE: usize, const DATA_LENGTH: usize> PageSource<DATA_LENGTH>
+    for FilePageSource<PAGE_SIZE, DATA_LENGTH>
+{
+    fn read_page(&self, index: usize) -> eyre::Result<GeneralPage<DataPage<DATA_LENGTH>>> {
+        let Some(page_id) = self.page_ids.get(index).copied() else {
+            eyre::bail!("{} has no data page {}", self.path.display(), index)
+        };
+        let mut file = self.file.lock().unwrap();
+        if let Some(checksums) = &self.checksums {
+            if !checksums.is_valid(&mut file, page_id)? {
+                eyre::bail!(
+                    "page {} of {} doesn't match its checksum",
+                    page_id,
+                    self.path.display()
+                )
+            }
+        }
+        let page = parse_data_page::<PAGE_SIZE, DATA_LENGTH>(&mut file, page_id)?;
+        if page.header.page_type != PageType::Data {
+            eyre::bail!(
+                "page {} of {} is {:?} page instead of data page",
+                page_id,
+                self.path.display(),
+                page.header.page_type
+            )
+        }
+
+        Ok(page)
+    }
+}
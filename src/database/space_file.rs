@@ -0,0 +1,401 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::database::page_journal::PageJournal;
+use crate::database::PageChecksums;
+
+/// Generation of the table's `.wt` file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FileGeneration {
+    /// Generation written by the last persist.
+    Current,
+    /// Generation replaced by the last persist.
+    Previous,
+}
+
+/// Generations of the table's `.wt` file.
+///
+/// When the whole file is written, new generation is written to the
+/// temporary file, flushed and renamed to the file's path, so crash during
+/// persist never leaves partly written file in its place. Replaced
+/// generation is kept and is loaded if the current one fails validation.
+///
+/// Pages changed since the last persist are written to their places in the
+/// current generation through the [`PageJournal`] instead, so the file is
+/// not copied. Crash while they are written leaves sealed journal, which is
+/// applied by [`SpaceFile::recover`]. Each generation ends with
+/// [`PageChecksums`] of its pages.
+///
+/// Guarantee of the incremental persist is weaker: previous generation is
+/// removed, as it doesn't have the pages persisted since it was replaced.
+/// So if the current generation written incrementally fails validation,
+/// there is no generation to load instead and only its valid pages can be
+/// loaded, skipping the corrupted ones.
+#[derive(Clone, Debug)]
+pub struct SpaceFile {
+    path: PathBuf,
+}
+
+impl SpaceFile {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// Returns path of the previous generation.
+    pub fn previous_path(&self) -> PathBuf {
+        self.with_suffix(".prev")
+    }
+
+    /// Returns path to which current generation that failed validation is
+    /// moved.
+    pub fn invalid_path(&self) -> PathBuf {
+        self.with_suffix(".invalid")
+    }
+
+    /// Returns path of the [`PageJournal`] of the incremental persist.
+    pub fn journal_path(&self) -> PathBuf {
+        self.with_suffix(".journal")
+    }
+
+    fn temp_path(&self) -> PathBuf {
+        self.with_suffix(".tmp")
+    }
+
+    fn with_suffix(&self, suffix: &str) -> PathBuf {
+        let mut path = self.path.as_os_str().to_owned();
+        path.push(suffix);
+        PathBuf::from(path)
+    }
+
+    /// Creates empty temporary file for the new generation.
+    pub fn create_temp(&self) -> eyre::Result<File> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(self.temp_path())?)
+    }
+
+    /// Writes checksums of the temporary file's pages, flushes it and makes
+    /// it the current generation. Replaced generation becomes the previous
+    /// one.
+    pub fn commit(&self, mut file: File, page_size: usize) -> eyre::Result<()> {
+        PageChecksums::write(&mut file, page_size)?;
+        file.sync_all()?;
+        drop(file);
+        // Journal of the failed incremental persist must not be applied to
+        // the new generation.
+        remove_if_exists(self.journal_path())?;
+        // Crash between renames leaves only the previous generation, which
+        // is loaded then.
+        if self.path.exists() {
+            std::fs::rename(&self.path, self.previous_path())?;
+        }
+        std::fs::rename(self.temp_path(), &self.path)?;
+        self.sync_dir()
+    }
+
+    /// Creates empty [`PageJournal`] for the pages changed since the last
+    /// persist.
+    pub fn begin_journal(&self, page_size: usize) -> eyre::Result<PageJournal> {
+        PageJournal::create(&self.journal_path(), page_size)
+    }
+
+    /// Seals and flushes the journal, then writes its pages to their places
+    /// in the current generation and updates its checksums. Previous
+    /// generation is removed, as the log retired by this persist doesn't have
+    /// the changes that were persisted after it.
+    pub fn commit_journal(&self, mut journal: PageJournal) -> eyre::Result<()> {
+        let pages_length = {
+            let mut file = File::open(&self.path)?;
+            match PageChecksums::read(&mut file, journal.page_size())? {
+                Some(checksums) => checksums.pages_length(),
+                None => file.metadata()?.len(),
+            }
+        };
+        journal.seal(pages_length)?;
+        drop(journal);
+        self.sync_dir()?;
+        self.apply_journal()
+    }
+
+    /// Applies journal left by the incremental persist that was interrupted
+    /// after the journal was sealed, so the current generation has all pages
+    /// of that persist. Journal that wasn't sealed is removed, as the file
+    /// wasn't changed by it. Must be called before the file is read.
+    pub fn recover(&self) -> eyre::Result<()> {
+        self.apply_journal()
+    }
+
+    fn apply_journal(&self) -> eyre::Result<()> {
+        let journal_path = self.journal_path();
+        let journal = match PageJournal::read(&journal_path)? {
+            Some(journal) if self.path.exists() => journal,
+            _ => return remove_if_exists(journal_path),
+        };
+        let mut file = OpenOptions::new().read(true).write(true).open(&self.path)?;
+        // Footer is removed or partly written if the previous apply was
+        // interrupted, so checksums of all pages are calculated then.
+        let checksums = PageChecksums::read(&mut file, journal.page_size)
+            .ok()
+            .flatten()
+            .filter(|c| c.pages_length() <= journal.pages_length);
+        if let Some(checksums) = &checksums {
+            file.set_len(checksums.pages_length())?;
+        }
+        file.set_len(journal.pages_length)?;
+        for (page_id, page) in &journal.pages {
+            file.seek(SeekFrom::Start(*page_id as u64 * journal.page_size as u64))?;
+            file.write_all(page)?;
+        }
+        match checksums {
+            Some(mut checksums) => {
+                checksums.update(&mut file, journal.pages_length, &journal.pages)?
+            }
+            None => PageChecksums::write(&mut file, journal.page_size)?,
+        }
+        file.sync_all()?;
+        drop(file);
+
+        remove_if_exists(self.previous_path())?;
+        std::fs::remove_file(journal_path)?;
+        self.sync_dir()
+    }
+
+    fn sync_dir(&self) -> eyre::Result<()> {
+        #[cfg(unix)]
+        if let Some(dir) = self.path.parent() {
+            File::open(dir)?.sync_all()?;
+        }
+
+        Ok(())
+    }
+
+    /// Makes copy of the `source` file the current generation. Other
+    /// generations are removed, so replaced state is never loaded instead of
+    /// it.
+    pub fn replace_with(&self, source: impl AsRef<Path>) -> eyre::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::copy(source, self.temp_path())?;
+        File::open(self.temp_path())?.sync_all()?;
+        for path in [
+            self.previous_path(),
+            self.invalid_path(),
+            self.journal_path(),
+        ] {
+            remove_if_exists(path)?;
+        }
+        std::fs::rename(self.temp_path(), &self.path)?;
+        self.sync_dir()
+    }
+
+    /// Parses the newest generation that passes validation. Generations that
+    /// fail it are reported via `tracing`. Returns `None` if there is no
+    /// generation and the first generation's error if all of them fail.
+    ///
+    /// If the previous generation is loaded, invalid current one is moved to
+    /// [`SpaceFile::invalid_path`], so next persist doesn't make it the
+    /// previous generation. Journal of the interrupted persist is applied
+    /// first.
+    pub fn load<T>(
+        &self,
+        parse: impl Fn(&mut File) -> eyre::Result<T>,
+    ) -> eyre::Result<Option<(T, FileGeneration)>> {
+        self.recover()?;
+        let generations = [
+            (self.path.clone(), FileGeneration::Current),
+            (self.previous_path(), FileGeneration::Previous),
+        ];
+        let mut error = None;
+        for (path, generation) in generations {
+            let mut file = match File::open(&path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            match parse(&mut file) {
+                Ok(space) => {
+                    if generation == FileGeneration::Previous && self.path.exists() {
+                        std::fs::rename(&self.path, self.invalid_path())?;
+                    }
+                    return Ok(Some((space, generation)));
+                }
+                Err(e) => {
+                    tracing::warn!(file = %path.display(), error = %e, "table file failed validation");
+                    error.get_or_insert(e);
+                }
+            }
+        }
+
+        error.map_or(Ok(None), Err)
+    }
+}
+
+fn remove_if_exists(path: impl AsRef<Path>) -> eyre::Result<()> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{File, OpenOptions};
+    use std::io::{Read, Seek, SeekFrom, Write};
+
+    use data_bucket::{DataPage, GeneralHeader, GeneralPage, PageType, DATA_VERSION};
+
+    use crate::database::space_file::{FileGeneration, SpaceFile};
+    use crate::database::PageChecksums;
+
+    const PAGE_SIZE: usize = 128;
+
+    fn parse(file: &mut std::fs::File) -> eyre::Result<String> {
+        if PageChecksums::read(file, 4)?.is_none() {
+            eyre::bail!("file is truncated")
+        }
+        let mut content = vec![];
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_end(&mut content)?;
+        let end = content.iter().position(|b| *b == b'.').unwrap() + 1;
+        Ok(String::from_utf8(content[..end].to_vec())?)
+    }
+
+    #[test]
+    fn previous_generation_is_loaded_if_current_is_invalid() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let space_file = SpaceFile::new(dir.join("test.wt"));
+        assert!(space_file.load(parse).unwrap().is_none());
+
+        let mut file = space_file.create_temp().unwrap();
+        file.write_all(b"first.").unwrap();
+        space_file.commit(file, 4).unwrap();
+        let mut file = space_file.create_temp().unwrap();
+        file.write_all(b"second.").unwrap();
+        space_file.commit(file, 4).unwrap();
+        assert_eq!(
+            space_file.load(parse).unwrap(),
+            Some(("second.".to_string(), FileGeneration::Current))
+        );
+
+        // Truncated current generation.
+        std::fs::write(space_file.path(), b"sec").unwrap();
+        assert_eq!(
+            space_file.load(parse).unwrap(),
+            Some(("first.".to_string(), FileGeneration::Previous))
+        );
+        assert!(!space_file.path().exists());
+        assert_eq!(std::fs::read(space_file.invalid_path()).unwrap(), b"sec");
+
+        // Crash between renames of the commit.
+        assert_eq!(
+            space_file.load(parse).unwrap(),
+            Some(("first.".to_string(), FileGeneration::Previous))
+        );
+        let mut file = space_file.create_temp().unwrap();
+        file.write_all(b"third.").unwrap();
+        space_file.commit(file, 4).unwrap();
+        assert!(std::fs::read(space_file.previous_path())
+            .unwrap()
+            .starts_with(b"first."));
+
+        std::fs::write(space_file.path(), b"thi").unwrap();
+        std::fs::write(space_file.previous_path(), b"fir").unwrap();
+        assert!(space_file.load(parse).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn data_page(page_id: u32, byte: u8) -> GeneralPage<DataPage<16>> {
+        GeneralPage {
+            header: GeneralHeader {
+                data_version: DATA_VERSION,
+                page_id: page_id.into(),
+                previous_id: 0.into(),
+                next_id: 0.into(),
+                page_type: PageType::Data,
+                space_id: 0.into(),
+                data_length: 16,
+            },
+            inner: DataPage {
+                data: [byte; 16],
+                length: 16,
+            },
+        }
+    }
+
+    #[test]
+    fn journal_is_applied_after_crash() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let committed = SpaceFile::new(dir.join("committed.wt"));
+        let crashed = SpaceFile::new(dir.join("crashed.wt"));
+        for space_file in [&committed, &crashed] {
+            for _ in 0..2 {
+                let mut file = space_file.create_temp().unwrap();
+                file.write_all(&vec![1; PAGE_SIZE * 2 + 10]).unwrap();
+                space_file.commit(file, PAGE_SIZE).unwrap();
+            }
+        }
+        let write_journal = |space_file: &SpaceFile| {
+            let mut journal = space_file.begin_journal(PAGE_SIZE).unwrap();
+            journal.write_page(&mut data_page(1, 2)).unwrap();
+            journal.write_page(&mut data_page(3, 3)).unwrap();
+            journal
+        };
+        committed.commit_journal(write_journal(&committed)).unwrap();
+        assert!(!committed.previous_path().exists());
+        assert!(!committed.journal_path().exists());
+
+        let bytes = std::fs::read(committed.path()).unwrap();
+        assert_eq!(bytes[..PAGE_SIZE], [1; PAGE_SIZE]);
+        assert_eq!(bytes[PAGE_SIZE * 2..PAGE_SIZE * 2 + 10], [1; 10]);
+        let mut file = File::open(committed.path()).unwrap();
+        let checksums = PageChecksums::read(&mut file, PAGE_SIZE).unwrap().unwrap();
+        assert_eq!(checksums.pages_length(), PAGE_SIZE as u64 * 4);
+        for page_id in 0..4 {
+            assert!(checksums.is_valid(&mut file, page_id).unwrap());
+        }
+
+        // Crash after the journal is sealed and the file's footer is removed.
+        let mut journal = write_journal(&crashed);
+        journal.seal(PAGE_SIZE as u64 * 2 + 10).unwrap();
+        drop(journal);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(crashed.path())
+            .unwrap();
+        PageChecksums::remove(&mut file).unwrap();
+        drop(file);
+        assert_eq!(
+            crashed
+                .load(|file| Ok(PageChecksums::read(file, PAGE_SIZE)?.is_some()))
+                .unwrap(),
+            Some((true, FileGeneration::Current))
+        );
+        assert_eq!(std::fs::read(crashed.path()).unwrap(), bytes);
+        assert!(!crashed.journal_path().exists());
+
+        // Journal that wasn't sealed doesn't change the file.
+        drop(write_journal(&crashed));
+        crashed.recover().unwrap();
+        assert_eq!(std::fs::read(crashed.path()).unwrap(), bytes);
+        assert!(!crashed.journal_path().exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
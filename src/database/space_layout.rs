@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use data_bucket::{
+    DataPage, GeneralHeader, GeneralPage, Interval, PageType, Persistable, DATA_VERSION,
+};
+
+use crate::database::checksum::crc32;
+use crate::in_memory::{DataPages, RowWrapper, StorableRow};
+
+/// Index page written to the file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct PersistedPage {
+    page_id: u32,
+    /// Checksum of the page's content and ids of its neighbours.
+    checksum: u32,
+}
+
+/// Placement of the table's pages in the `.wt` file.
+///
+/// Layout is created when all pages are written to the file or when the file
+/// is loaded. After that only dirty data pages and index pages with changed
+/// content are placed and written. Each page is written to its existing place in the
+/// file. New pages are placed to the pages freed by shrunk sections or to the
+/// end of the file.
+#[derive(Clone, Debug, Default)]
+pub struct SpaceLayout {
+    /// Count of the pages in the file, including space info page.
+    page_count: u32,
+    /// Pages that are not used by any section.
+    free_pages: Vec<u32>,
+    primary_index: Vec<PersistedPage>,
+    secondary_indexes: HashMap<String, Vec<PersistedPage>>,
+    /// Ids of the file pages in the in-memory data pages order.
+    data: Vec<u32>,
+}
+
+impl SpaceLayout {
+    /// Sets primary index pages as they are placed in the file.
+    pub fn set_primary_index<T: Persistable>(&mut self, pages: &[GeneralPage<T>]) {
+        self.primary_index = self.placed(pages);
+    }
+
+    /// Sets secondary index pages as they are placed in the file.
+    pub fn set_secondary_index<T: Persistable>(&mut self, name: &str, pages: &[GeneralPage<T>]) {
+        let placed = self.placed(pages);
+        self.secondary_indexes.insert(name.to_string(), placed);
+    }
+
+    /// Sets data pages as they are placed in the file.
+    pub fn set_data<T>(&mut self, pages: &[GeneralPage<T>]) {
+        let ids = pages.iter().map(|p| page_id(&p.header)).collect::<Vec<_>>();
+        self.set_data_pages(ids);
+    }
+
+    /// Sets ids of the file pages of data pages, which are not read.
+    pub fn set_data_pages(&mut self, ids: Vec<u32>) {
+        self.update_page_count(&ids);
+        self.data = ids;
+    }
+
+    /// Places primary index pages to the file's pages. Returns only pages
+    /// that changed since the last placement.
+    pub fn place_primary_index<T: Persistable>(
+        &mut self,
+        pages: Vec<GeneralPage<T>>,
+    ) -> Vec<GeneralPage<T>> {
+        let placed = std::mem::take(&mut self.primary_index);
+        let (placed, changed) = self.place_index(placed, pages);
+        self.primary_index = placed;
+        changed
+    }
+
+    /// Places secondary index pages to the file's pages. Returns only pages
+    /// that changed since the last placement.
+    pub fn place_secondary_index<T: Persistable>(
+        &mut self,
+        name: &str,
+        pages: Vec<GeneralPage<T>>,
+    ) -> Vec<GeneralPage<T>> {
+        let placed = self.secondary_indexes.remove(name).unwrap_or_default();
+        let (placed, changed) = self.place_index(placed, pages);
+        self.secondary_indexes.insert(name.to_string(), placed);
+        changed
+    }
+
+    /// Places data pages to the file's pages. Returns only pages that are
+    /// dirty.
+    pub fn place_data<Row, const DATA_LENGTH: usize>(
+        &mut self,
+        data: &DataPages<Row, DATA_LENGTH>,
+    ) -> Vec<GeneralPage<DataPage<DATA_LENGTH>>>
+    where
+        Row: StorableRow,
+        <Row as StorableRow>::WrappedRow: RowWrapper<Row>,
+    {
+        let (count, dirty) = data.get_dirty_bytes();
+        if self.data.len() > count {
+            let freed = self.data.split_off(count);
+            self.free_pages.extend(freed);
+        }
+        while self.data.len() < count {
+            let page_id = self.allocate();
+            self.data.push(page_id);
+        }
+
+        dirty
+            .into_iter()
+            .map(|(index, data, length)| {
+                let (previous_id, next_id) = neighbours(&self.data, index);
+                GeneralPage {
+                    header: GeneralHeader {
+                        data_version: DATA_VERSION,
+                        page_id: self.data[index].into(),
+                        previous_id: previous_id.into(),
+                        next_id: next_id.into(),
+                        page_type: PageType::Data,
+                        space_id: 0.into(),
+                        data_length: length,
+                    },
+                    inner: DataPage { data, length },
+                }
+            })
+            .collect()
+    }
+
+    pub fn page_count(&self) -> u32 {
+        self.page_count
+    }
+
+    pub fn primary_key_intervals(&self) -> Vec<Interval> {
+        intervals(self.primary_index.iter().map(|p| p.page_id))
+    }
+
+    pub fn secondary_index_intervals(&self) -> HashMap<String, Vec<Interval>> {
+        self.secondary_indexes
+            .iter()
+            .map(|(name, pages)| (name.clone(), intervals(pages.iter().map(|p| p.page_id))))
+            .collect()
+    }
+
+    /// Returns ids of the file pages in the in-memory data pages order.
+    pub fn data_pages(&self) -> &[u32] {
+        self.data.as_slice()
+    }
+
+    pub fn data_intervals(&self) -> Vec<Interval> {
+        intervals(self.data.iter().copied())
+    }
+
+    fn placed<T: Persistable>(&mut self, pages: &[GeneralPage<T>]) -> Vec<PersistedPage> {
+        let ids = pages.iter().map(|p| page_id(&p.header)).collect::<Vec<_>>();
+        self.update_page_count(&ids);
+        pages
+            .iter()
+            .enumerate()
+            .map(|(i, p)| PersistedPage {
+                page_id: ids[i],
+                checksum: checksum(p, &ids, i),
+            })
+            .collect()
+    }
+
+    fn place_index<T: Persistable>(
+        &mut self,
+        mut placed: Vec<PersistedPage>,
+        pages: Vec<GeneralPage<T>>,
+    ) -> (Vec<PersistedPage>, Vec<GeneralPage<T>>) {
+        if placed.len() > pages.len() {
+            let freed = placed.split_off(pages.len());
+            self.free_pages.extend(freed.into_iter().map(|p| p.page_id));
+        }
+        let mut ids = placed.iter().map(|p| p.page_id).collect::<Vec<_>>();
+        while ids.len() < pages.len() {
+            ids.push(self.allocate());
+        }
+
+        let mut res = Vec::with_capacity(pages.len());
+        let mut changed = vec![];
+        for (i, mut page) in pages.into_iter().enumerate() {
+            let (previous_id, next_id) = neighbours(&ids, i);
+            page.header.page_id = ids[i].into();
+            page.header.previous_id = previous_id.into();
+            page.header.next_id = next_id.into();
+
+            let checksum = checksum(&page, &ids, i);
+            res.push(PersistedPage {
+                page_id: ids[i],
+                checksum,
+            });
+            if placed.get(i).map(|p| p.checksum) != Some(checksum) {
+                changed.push(page);
+            }
+        }
+
+        (res, changed)
+    }
+
+    fn allocate(&mut self) -> u32 {
+        // Pages are taken from the start of the file first, so new sections
+        // stay mostly ordered.
+        if let Some((i, _)) = self
+            .free_pages
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, id)| **id)
+        {
+            return self.free_pages.swap_remove(i);
+        }
+        let page_id = self.page_count.max(1);
+        self.page_count = page_id + 1;
+        page_id
+    }
+
+    fn update_page_count(&mut self, ids: &[u32]) {
+        if let Some(max) = ids.iter().max() {
+            self.page_count = self.page_count.max(max + 1);
+        }
+    }
+}
+
+fn page_id(header: &GeneralHeader) -> u32 {
+    let page_id: usize = header.page_id.into();
+    page_id as u32
+}
+
+/// Returns ids of the previous and the next pages of the section. `0` is
+/// used if there is no such page.
+fn neighbours(ids: &[u32], i: usize) -> (u32, u32) {
+    let previous_id = if i > 0 { ids[i - 1] } else { 0 };
+    let next_id = ids.get(i + 1).copied().unwrap_or(0);
+    (previous_id, next_id)
+}
+
+fn checksum<T: Persistable>(page: &GeneralPage<T>, ids: &[u32], i: usize) -> u32 {
+    let (previous_id, next_id) = neighbours(ids, i);
+    let mut bytes = page.inner.as_bytes().as_ref().to_vec();
+    bytes.extend_from_slice(&previous_id.to_le_bytes());
+    bytes.extend_from_slice(&next_id.to_le_bytes());
+    crc32(&bytes)
+}
+
+/// Merges page ids into intervals of the consecutive pages.
+fn intervals(ids: impl Iterator<Item = u32>) -> Vec<Interval> {
+    let mut res: Vec<Interval> = vec![];
+    for id in ids {
+        let id = id as usize;
+        match res.last_mut() {
+            Some(interval) if interval.1 + 1 == id => interval.1 = id,
+            _ => res.push(Interval(id, id)),
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::space_layout::{intervals, SpaceLayout};
+
+    #[test]
+    fn pages_are_allocated_after_used() {
+        let mut layout = SpaceLayout {
+            page_count: 5,
+            ..Default::default()
+        };
+        assert_eq!(layout.allocate(), 5);
+        layout.free_pages = vec![4, 2];
+        assert_eq!(layout.allocate(), 2);
+        assert_eq!(layout.allocate(), 4);
+        assert_eq!(layout.allocate(), 6);
+        assert_eq!(layout.page_count(), 7);
+    }
+
+    #[test]
+    fn intervals_merge_consecutive_pages() {
+        let res = intervals([1, 2, 3, 7, 8, 4].into_iter())
+            .into_iter()
+            .map(|i| (i.0, i.1))
+            .collect::<Vec<_>>();
+        assert_eq!(res, vec![(1, 3), (7, 8), (4, 4)]);
+    }
+}
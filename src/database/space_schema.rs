@@ -0,0 +1,206 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::database::checksum::crc32;
+
+/// Magic bytes that end the schema block.
+const SCHEMA_MAGIC: [u8; 8] = *b"WTSCHEMA";
+
+/// Size of the block's fixed part: payload length, its checksum and magic
+/// bytes.
+const TRAILER_SIZE: usize = 16;
+
+/// Schema of the rows stored in the `.wt` file.
+///
+/// Schema is written to the end of the space info page as
+/// `[payload][payload length: u32][crc32 of payload: u32][magic]`, where
+/// payload is the schema version followed by the columns count and each
+/// column's name and type as length-prefixed strings. Files written without
+/// schema have version `0` and no recorded columns.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SpaceSchema {
+    /// Number of the last migration applied to the rows.
+    pub version: u32,
+    /// Names and types of the row's columns in the order of its fields, which
+    /// defines the layout of the archived rows.
+    pub columns: Vec<(String, String)>,
+}
+
+impl SpaceSchema {
+    pub fn new(version: u32, columns: &[(&str, &str)]) -> Self {
+        Self {
+            version,
+            columns: columns
+                .iter()
+                .map(|(name, type_)| (name.to_string(), type_.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Fails if the recorded columns differ from the `columns` of the row
+    /// type used to read the file. Files written without recorded columns
+    /// are not checked.
+    pub fn check_columns(&self, columns: &[(&str, &str)]) -> eyre::Result<()> {
+        let expected = Self::new(self.version, columns);
+        if self.columns.is_empty() || self.columns == expected.columns {
+            return Ok(());
+        }
+        eyre::bail!(
+            "table's file with schema version {} has columns ({}), but the row has columns ({})",
+            self.version,
+            self.format_columns(),
+            expected.format_columns(),
+        )
+    }
+
+    fn format_columns(&self) -> String {
+        self.columns
+            .iter()
+            .map(|(name, type_)| format!("{}: {}", name, type_))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Reads schema from the space info page. Returns `None` if the page has
+    /// no schema.
+    pub fn read(file: &mut File, page_size: usize) -> eyre::Result<Option<Self>> {
+        if file.metadata()?.len() < page_size as u64 {
+            return Ok(None);
+        }
+        let mut trailer = [0; TRAILER_SIZE];
+        file.seek(SeekFrom::Start((page_size - TRAILER_SIZE) as u64))?;
+        file.read_exact(&mut trailer)?;
+        if trailer[8..] != SCHEMA_MAGIC {
+            return Ok(None);
+        }
+        let length = u32::from_le_bytes(trailer[..4].try_into().unwrap()) as usize;
+        let checksum = u32::from_le_bytes(trailer[4..8].try_into().unwrap());
+        if length > page_size - TRAILER_SIZE {
+            eyre::bail!("schema of the table's file is corrupted")
+        }
+        let mut payload = vec![0; length];
+        file.seek(SeekFrom::Start((page_size - TRAILER_SIZE - length) as u64))?;
+        file.read_exact(&mut payload)?;
+        if crc32(&payload) != checksum {
+            eyre::bail!("schema of the table's file is corrupted")
+        }
+
+        Self::decode(&payload)
+            .map(Some)
+            .ok_or_else(|| eyre::eyre!("schema of the table's file is corrupted"))
+    }
+
+    fn decode(payload: &[u8]) -> Option<Self> {
+        let mut rest = payload;
+        let version = read_u32(&mut rest)?;
+        let mut columns = vec![];
+        for _ in 0..read_u32(&mut rest)? {
+            let name = read_string(&mut rest)?;
+            let type_ = read_string(&mut rest)?;
+            columns.push((name, type_));
+        }
+        if !rest.is_empty() {
+            return None;
+        }
+
+        Some(Self { version, columns })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        payload.extend_from_slice(&self.version.to_le_bytes());
+        payload.extend_from_slice(&(self.columns.len() as u32).to_le_bytes());
+        for (name, type_) in &self.columns {
+            for value in [name, type_] {
+                payload.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                payload.extend_from_slice(value.as_bytes());
+            }
+        }
+        payload
+    }
+
+    /// Writes schema to the end of the space info page, which must be
+    /// already written. `info_length` is length of the page's header and
+    /// data, which must not be overwritten.
+    pub fn write(&self, file: &mut File, page_size: usize, info_length: usize) -> eyre::Result<()> {
+        let payload = self.encode();
+        let mut block = Vec::with_capacity(payload.len() + TRAILER_SIZE);
+        block.extend_from_slice(&payload);
+        block.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        block.extend_from_slice(&crc32(&payload).to_le_bytes());
+        block.extend_from_slice(&SCHEMA_MAGIC);
+        if info_length + block.len() > page_size {
+            eyre::bail!("space info page has no room for the table's schema")
+        }
+        file.seek(SeekFrom::Start((page_size - block.len()) as u64))?;
+        file.write_all(&block)?;
+
+        Ok(())
+    }
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Option<u32> {
+    let (value, rest) = bytes.split_first_chunk::<4>()?;
+    *bytes = rest;
+    Some(u32::from_le_bytes(*value))
+}
+
+fn read_string(bytes: &mut &[u8]) -> Option<String> {
+    let length = read_u32(bytes)? as usize;
+    if bytes.len() < length {
+        return None;
+    }
+    let (value, rest) = bytes.split_at(length);
+    *bytes = rest;
+    String::from_utf8(value.to_vec()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Seek, SeekFrom, Write};
+
+    use crate::database::space_schema::SpaceSchema;
+
+    #[test]
+    fn schema_is_read_from_info_page() {
+        let path = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(&[1; 64]).unwrap();
+        file.write_all(&[0; 64]).unwrap();
+        assert_eq!(SpaceSchema::read(&mut file, 64).unwrap(), None);
+
+        let schema = SpaceSchema::new(3, &[("id", "u64"), ("value", "u32")]);
+        assert!(schema.write(&mut file, 64, 12).is_err());
+        schema.write(&mut file, 64, 11).unwrap();
+        assert_eq!(file.metadata().unwrap().len(), 128);
+        assert_eq!(SpaceSchema::read(&mut file, 64).unwrap(), Some(schema));
+
+        file.seek(SeekFrom::Start(20)).unwrap();
+        file.write_all(&[4]).unwrap();
+        assert!(SpaceSchema::read(&mut file, 64).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+    #[test]
+    fn columns_are_checked() {
+        let schema = SpaceSchema::new(1, &[("id", "u64"), ("value", "u32")]);
+        assert!(schema
+            .check_columns(&[("id", "u64"), ("value", "u32")])
+            .is_ok());
+        assert!(schema
+            .check_columns(&[("value", "u32"), ("id", "u64")])
+            .is_err());
+        assert!(schema
+            .check_columns(&[("id", "u64"), ("value", "u64")])
+            .is_err());
+        assert!(SpaceSchema::default()
+            .check_columns(&[("id", "u64")])
+            .is_ok());
+    }
+}
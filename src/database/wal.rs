@@ -0,0 +1,457 @@
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use rkyv::api::high::HighDeserializer;
+use rkyv::rancor::Strategy;
+use rkyv::ser::allocator::ArenaHandle;
+use rkyv::ser::sharing::Share;
+use rkyv::ser::Serializer;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::database::checksum::crc32;
+use crate::WorkTableError;
+
+/// Size of the record's header: payload length and payload checksum.
+const RECORD_HEADER_SIZE: usize = 8;
+
+/// Defines when records appended to the [`Wal`] are flushed to the disk.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WalSyncPolicy {
+    /// `fsync` after each committed write. Committed changes survive OS crash.
+    #[default]
+    Always,
+    /// `fsync` after each `n` committed writes.
+    EveryN(u32),
+    /// Never `fsync`, so committed changes survive only process crash.
+    Never,
+}
+
+/// Operation recorded in the [`Wal`].
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WalOperation {
+    /// Row was inserted. Record contains the row.
+    Insert = 1,
+    /// Row was updated. Record contains the new row.
+    Update = 2,
+    /// Row was deleted. Record contains the primary key.
+    Delete = 3,
+    /// All rows were removed. Record has no data.
+    Truncate = 4,
+}
+
+impl TryFrom<u8> for WalOperation {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(WalOperation::Insert),
+            2 => Ok(WalOperation::Update),
+            3 => Ok(WalOperation::Delete),
+            4 => Ok(WalOperation::Truncate),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Record read from the [`Wal`].
+#[derive(Clone, Debug)]
+pub struct WalRecord {
+    pub operation: WalOperation,
+    pub data: Vec<u8>,
+}
+
+impl WalRecord {
+    /// Deserializes value (row or primary key) stored in the record.
+    pub fn value<T>(&self) -> eyre::Result<T>
+    where
+        T: Archive,
+        <T as Archive>::Archived: Deserialize<T, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        let mut bytes = AlignedVec::<16>::with_capacity(self.data.len());
+        bytes.extend_from_slice(self.data.as_slice());
+        let archived = unsafe { rkyv::access_unchecked::<<T as Archive>::Archived>(&bytes[..]) };
+        Ok(rkyv::deserialize::<T, rkyv::rancor::Error>(archived)?)
+    }
+}
+
+#[derive(Debug)]
+struct WalFile {
+    file: File,
+    /// Count of the committed writes that are not flushed yet.
+    unsynced: u32,
+}
+
+/// Append-only write-ahead log of the table's changes.
+///
+/// Each record is written as `[length: u32][crc32: u32][operation: u8][data]`,
+/// where length and checksum cover operation and data. Log is read up to the
+/// first incomplete or corrupted record, so write torn by crash is dropped.
+#[derive(Debug)]
+pub struct Wal {
+    path: PathBuf,
+    policy: WalSyncPolicy,
+    file: Mutex<WalFile>,
+}
+
+impl Wal {
+    /// Opens log at `path` for appending, creating it if it doesn't exist.
+    /// Incomplete or corrupted tail of the log is cut off, so new records are
+    /// not appended after it.
+    pub fn open(path: impl AsRef<Path>, policy: WalSyncPolicy) -> eyre::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+        let (_, valid_length) = Self::parse(&bytes);
+        if valid_length < bytes.len() {
+            file.set_len(valid_length as u64)?;
+            file.sync_data()?;
+        }
+
+        Ok(Self {
+            path,
+            policy,
+            file: Mutex::new(WalFile { file, unsynced: 0 }),
+        })
+    }
+
+    /// Reads all valid records from log at `path`, starting with the records
+    /// of the log rotated by [`Wal::rotate`]. Missing log has no records.
+    pub fn read(path: impl AsRef<Path>) -> eyre::Result<Vec<WalRecord>> {
+        let path = path.as_ref();
+        let mut records = Self::parse(&Self::read_bytes(&Self::rotated_path(path))?).0;
+        records.extend(Self::parse(&Self::read_bytes(path)?).0);
+
+        Ok(records)
+    }
+
+    /// Moves records retired by [`Wal::retire_rotated`] back to the start of
+    /// the rotated log. Used when the previous generation of the table's file
+    /// is loaded, as retired records are not persisted in it.
+    pub fn restore_retired(path: impl AsRef<Path>) -> eyre::Result<()> {
+        let path = path.as_ref();
+        let retired = Self::retired_path(path);
+        let mut bytes = Self::read_bytes(&retired)?;
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let rotated = Self::rotated_path(path);
+        bytes.extend(Self::read_bytes(&rotated)?);
+        // Crash before retired log is removed leaves its records twice,
+        // which is fine as replay of the same records is idempotent.
+        let mut temp_path = rotated.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        let mut temp = File::create(&temp_path)?;
+        temp.write_all(&bytes)?;
+        temp.sync_all()?;
+        std::fs::rename(&temp_path, &rotated)?;
+        std::fs::remove_file(&retired)?;
+
+        Ok(())
+    }
+
+    /// Removes log at `path` with its rotated and retired parts. Used when
+    /// table's file is replaced, so records of the old state are not
+    /// replayed on top of it.
+    pub fn remove(path: impl AsRef<Path>) -> eyre::Result<()> {
+        let path = path.as_ref();
+        for path in [
+            path.to_path_buf(),
+            Self::rotated_path(path),
+            Self::retired_path(path),
+        ] {
+            match std::fs::remove_file(path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_bytes(path: &Path) -> eyre::Result<Vec<u8>> {
+        let mut bytes = vec![];
+        match File::open(path) {
+            Ok(mut file) => {
+                file.read_to_end(&mut bytes)?;
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        Ok(bytes)
+    }
+
+    /// Returns path of the log rotated by [`Wal::rotate`].
+    fn rotated_path(path: &Path) -> PathBuf {
+        let mut path = path.as_os_str().to_owned();
+        path.push(".old");
+        PathBuf::from(path)
+    }
+
+    /// Returns path of the log retired by [`Wal::retire_rotated`].
+    fn retired_path(path: &Path) -> PathBuf {
+        let mut path = path.as_os_str().to_owned();
+        path.push(".prev");
+        PathBuf::from(path)
+    }
+
+    /// Parses records up to the first incomplete or corrupted one. Returns
+    /// parsed records and length of the log's valid part.
+    fn parse(bytes: &[u8]) -> (Vec<WalRecord>, usize) {
+        let mut records = vec![];
+        let mut offset = 0;
+        while bytes.len() - offset >= RECORD_HEADER_SIZE {
+            let length = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+            let checksum = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().unwrap());
+            let start = offset + RECORD_HEADER_SIZE;
+            if length == 0 || bytes.len() - start < length {
+                break;
+            }
+            let payload = &bytes[start..start + length];
+            if crc32(payload) != checksum {
+                break;
+            }
+            let Ok(operation) = WalOperation::try_from(payload[0]) else {
+                break;
+            };
+            records.push(WalRecord {
+                operation,
+                data: payload[1..].to_vec(),
+            });
+            offset = start + length;
+        }
+
+        (records, offset)
+    }
+
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    pub fn policy(&self) -> WalSyncPolicy {
+        self.policy
+    }
+
+    /// Moves logged records to the separate log and starts new log. Used when
+    /// table's state is captured for persisting, so records of the changes
+    /// made after it are kept in the new log. Rotated log is read by
+    /// [`Wal::read`] until it's retired by [`Wal::retire_rotated`], so if
+    /// previous rotated log wasn't retired, records are appended to it.
+    ///
+    /// Must not be called while [`WalWrite`] of this log is alive.
+    pub fn rotate(&self) -> Result<(), WorkTableError> {
+        let mut file = self.file.lock().unwrap();
+        let rotated = Self::rotated_path(&self.path);
+        if rotated.exists() {
+            let mut bytes = vec![];
+            File::open(&self.path)?.read_to_end(&mut bytes)?;
+            let mut rotated = OpenOptions::new().append(true).open(&rotated)?;
+            rotated.write_all(&bytes)?;
+            rotated.sync_data()?;
+            file.file.set_len(0)?;
+            file.file.sync_data()?;
+        } else {
+            file.file.sync_data()?;
+            std::fs::rename(&self.path, &rotated)?;
+            file.file = OpenOptions::new()
+                .create(true)
+                .read(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        file.unsynced = 0;
+
+        Ok(())
+    }
+
+    /// Retires log rotated by [`Wal::rotate`], replacing the previously
+    /// retired one. Used when captured table's state is persisted, so
+    /// rotated records are needed only if the previous generation of the
+    /// table's file is loaded.
+    pub fn retire_rotated(&self) -> Result<(), WorkTableError> {
+        let retired = Self::retired_path(&self.path);
+        match std::fs::remove_file(&retired) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        match std::fs::rename(Self::rotated_path(&self.path), retired) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Starts write to the log. Other writes wait until returned [`WalWrite`]
+    /// is dropped, so records of the changes made while it's alive are
+    /// appended in the order in which changes became visible.
+    pub fn begin(&self) -> WalWrite<'_> {
+        WalWrite {
+            file: self.file.lock().unwrap(),
+            policy: self.policy,
+            buf: vec![],
+        }
+    }
+}
+
+/// Write started by [`Wal::begin`]. Records are buffered and appended to the
+/// log on [`WalWrite::commit`], so dropped write leaves log unchanged.
+pub struct WalWrite<'a> {
+    file: MutexGuard<'a, WalFile>,
+    policy: WalSyncPolicy,
+    buf: Vec<u8>,
+}
+
+impl WalWrite<'_> {
+    /// Adds record with serialized `value` (row or primary key).
+    pub fn push<T>(&mut self, operation: WalOperation, value: &T) -> Result<(), WorkTableError>
+    where
+        T: for<'a> Serialize<
+            Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+        >,
+    {
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(value)
+            .map_err(|_| WorkTableError::SerializeError)?;
+        self.push_bytes(operation, bytes.as_slice());
+        Ok(())
+    }
+
+    /// Adds record with already serialized data.
+    pub fn push_bytes(&mut self, operation: WalOperation, data: &[u8]) {
+        let mut payload = Vec::with_capacity(data.len() + 1);
+        payload.push(operation as u8);
+        payload.extend_from_slice(data);
+
+        self.buf
+            .extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        self.buf.extend_from_slice(&crc32(&payload).to_le_bytes());
+        self.buf.extend_from_slice(&payload);
+    }
+
+    /// Appends pushed records to the log and flushes it according to
+    /// [`WalSyncPolicy`].
+    pub fn commit(mut self) -> Result<(), WorkTableError> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        self.file.file.write_all(&self.buf)?;
+        match self.policy {
+            WalSyncPolicy::Always => self.file.file.sync_data()?,
+            WalSyncPolicy::EveryN(n) => {
+                self.file.unsynced += 1;
+                if self.file.unsynced >= n {
+                    self.file.file.sync_data()?;
+                    self.file.unsynced = 0;
+                }
+            }
+            WalSyncPolicy::Never => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    use crate::database::wal::{Wal, WalOperation, WalSyncPolicy};
+
+    #[test]
+    fn torn_record_is_dropped() {
+        let path = std::env::temp_dir().join(format!("{}.wal", uuid::Uuid::new_v4()));
+        let wal = Wal::open(&path, WalSyncPolicy::Always).unwrap();
+        let mut write = wal.begin();
+        write.push(WalOperation::Insert, &1u64).unwrap();
+        write.push_bytes(WalOperation::Truncate, &[]);
+        write.commit().unwrap();
+        wal.begin().commit().unwrap();
+
+        let records = Wal::read(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].operation, WalOperation::Insert);
+        assert_eq!(records[0].value::<u64>().unwrap(), 1);
+        assert_eq!(records[1].operation, WalOperation::Truncate);
+
+        let mut write = wal.begin();
+        write.push(WalOperation::Update, &3u64).unwrap();
+        write.commit().unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[10, 0, 0, 0, 1, 2]).unwrap();
+        let len = std::fs::metadata(&path).unwrap().len();
+        file.set_len(len - 8).unwrap();
+        assert_eq!(Wal::read(&path).unwrap().len(), 2);
+        drop(wal);
+
+        let wal = Wal::open(&path, WalSyncPolicy::Never).unwrap();
+        let mut write = wal.begin();
+        write.push(WalOperation::Delete, &4u64).unwrap();
+        write.commit().unwrap();
+        let records = Wal::read(&path).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[2].value::<u64>().unwrap(), 4);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rotated_log_is_read_until_retired() {
+        let path = std::env::temp_dir().join(format!("{}.wal", uuid::Uuid::new_v4()));
+        let wal = Wal::open(&path, WalSyncPolicy::Never).unwrap();
+        let push = |value: u64| {
+            let mut write = wal.begin();
+            write.push(WalOperation::Insert, &value).unwrap();
+            write.commit().unwrap();
+        };
+        let values = |wal_path: &std::path::Path| {
+            Wal::read(wal_path)
+                .unwrap()
+                .iter()
+                .map(|r| r.value::<u64>().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        push(1);
+        wal.rotate().unwrap();
+        push(2);
+        assert_eq!(values(&path), vec![1, 2]);
+
+        // Rotated log that wasn't retired is kept.
+        wal.rotate().unwrap();
+        push(3);
+        assert_eq!(values(&path), vec![1, 2, 3]);
+
+        wal.retire_rotated().unwrap();
+        assert_eq!(values(&path), vec![3]);
+        wal.rotate().unwrap();
+        push(4);
+        wal.retire_rotated().unwrap();
+        assert_eq!(values(&path), vec![4]);
+        drop(wal);
+
+        // Previous generation of the table's file is loaded.
+        Wal::restore_retired(&path).unwrap();
+        assert_eq!(values(&path), vec![3, 4]);
+        Wal::restore_retired(&path).unwrap();
+        assert_eq!(values(&path), vec![3, 4]);
+
+        let wal = Wal::open(&path, WalSyncPolicy::Never).unwrap();
+        wal.rotate().unwrap();
+        wal.retire_rotated().unwrap();
+        assert!(Wal::read(&path).unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(Wal::retired_path(&path)).unwrap();
+    }
+}
@@ -1,7 +1,7 @@
 use std::cell::UnsafeCell;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
 use data_bucket::page::PageId;
 use data_bucket::{DataPage, GeneralPage};
@@ -65,6 +65,20 @@ pub struct Data<Row, const DATA_LENGTH: usize = DATA_INNER_LENGTH> {
     #[rkyv(with = Unsafe)]
     inner_data: UnsafeCell<AlignedBytes<DATA_LENGTH>>,
 
+    /// Set when page is changed and unset when it's persisted.
+    #[rkyv(with = Skip)]
+    dirty: AtomicBool,
+
+    /// Set while page's content is the same as in the file it was read from.
+    /// Only such pages can be evicted from memory.
+    #[rkyv(with = Skip)]
+    in_source: AtomicBool,
+
+    /// Set when page is accessed and unset by the page cache, so recently
+    /// accessed pages are evicted last.
+    #[rkyv(with = Skip)]
+    accessed: AtomicBool,
+
     /// `Row` phantom data.
     _phantom: PhantomData<Row>,
 }
@@ -78,6 +92,9 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
             id,
             free_offset: AtomicU32::default(),
             inner_data: UnsafeCell::new(AlignedBytes::<DATA_LENGTH>([0; DATA_LENGTH])),
+            dirty: AtomicBool::new(true),
+            in_source: AtomicBool::new(false),
+            accessed: AtomicBool::new(false),
             _phantom: PhantomData,
         }
     }
@@ -87,6 +104,9 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
             id: page.header.page_id,
             free_offset: AtomicU32::from(page.header.data_length),
             inner_data: UnsafeCell::new(AlignedBytes::<DATA_LENGTH>(page.inner.data)),
+            dirty: AtomicBool::new(false),
+            in_source: AtomicBool::new(true),
+            accessed: AtomicBool::new(false),
             _phantom: PhantomData,
         }
     }
@@ -108,6 +128,11 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
     {
         let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(row)
             .map_err(|_| ExecutionError::SerializeError)?;
+        self.save_bytes(bytes.as_slice())
+    }
+
+    /// Saves already serialized row's `bytes` to the end of this page.
+    pub fn save_bytes(&self, bytes: &[u8]) -> Result<Link, ExecutionError> {
         let length = bytes.len() as u32;
         let offset = self.free_offset.fetch_add(length, Ordering::SeqCst);
         if offset > DATA_LENGTH as u32 - length {
@@ -118,7 +143,8 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
         }
 
         let inner_data = unsafe { &mut *self.inner_data.get() };
-        inner_data[offset as usize..][..length as usize].copy_from_slice(bytes.as_slice());
+        inner_data[offset as usize..][..length as usize].copy_from_slice(bytes);
+        self.mark_changed();
 
         let link = Link {
             page_id: self.id,
@@ -153,6 +179,7 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
         let inner_data = unsafe { &mut *self.inner_data.get() };
         inner_data[link.offset as usize..][..link.length as usize]
             .copy_from_slice(bytes.as_slice());
+        self.mark_changed();
 
         Ok(link)
     }
@@ -169,6 +196,7 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
             return Err(ExecutionError::DeserializeError);
         }
 
+        self.mark_changed();
         let inner_data = unsafe { &mut *self.inner_data.get() };
         let bytes = &mut inner_data[link.offset as usize..(link.offset + link.length) as usize];
         Ok(unsafe { rkyv::access_unchecked_mut::<<Row as Archive>::Archived>(&mut bytes[..]) })
@@ -209,6 +237,47 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
         let data = unsafe { &*self.inner_data.get() };
         data.0.clone()
     }
+
+    /// Returns `true` if page was changed since the last [`Data::take_dirty`]
+    /// call and unsets the flag. Page is dirty after creation, but not after
+    /// it's loaded from file.
+    pub fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::AcqRel)
+    }
+
+    /// Returns `true` if page was changed since the last
+    /// [`Data::take_dirty`] call.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if page wasn't changed since it was read from the file
+    /// or since [`Data::set_in_source`] call.
+    pub fn is_in_source(&self) -> bool {
+        self.in_source.load(Ordering::Acquire)
+    }
+
+    /// Marks page as having the same content as the file's page. Used after
+    /// page is written to the file.
+    pub fn set_in_source(&self) {
+        self.in_source.store(true, Ordering::Release)
+    }
+
+    /// Marks page as recently accessed.
+    pub fn touch(&self) {
+        self.accessed.store(true, Ordering::Relaxed)
+    }
+
+    /// Returns `true` if page was accessed since the last call and unsets
+    /// the flag.
+    pub fn take_accessed(&self) -> bool {
+        self.accessed.swap(false, Ordering::Relaxed)
+    }
+
+    fn mark_changed(&self) {
+        self.dirty.store(true, Ordering::Release);
+        self.in_source.store(false, Ordering::Release);
+    }
 }
 
 /// Error that can appear on [`Data`] page operations.
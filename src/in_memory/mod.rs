@@ -3,5 +3,5 @@ mod pages;
 mod row;
 
 pub use data::{Data, ExecutionError as DataExecutionError, DATA_INNER_LENGTH};
-pub use pages::{DataPages, ExecutionError as PagesExecutionError};
+pub use pages::{DataPages, ExecutionError as PagesExecutionError, PageSource};
 pub use row::{ArchivedRow, RowWrapper, StorableRow};
@@ -1,18 +1,23 @@
 use std::{
+    collections::VecDeque,
     fmt::Debug,
     sync::atomic::{AtomicU32, AtomicU64, Ordering},
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, RwLock},
 };
 
 use data_bucket::page::PageId;
+use data_bucket::{DataPage, GeneralPage};
 use derive_more::{Display, Error, From};
-use lockfree::stack::Stack;
 #[cfg(feature = "perf_measurements")]
 use performance_measurement_codegen::performance_measurement;
 use rkyv::{
-    api::high::HighDeserializer,
+    api::high::{to_bytes_in_with_alloc, HighDeserializer},
     rancor::Strategy,
-    ser::{allocator::ArenaHandle, sharing::Share, Serializer},
+    ser::{
+        allocator::{Arena, ArenaHandle},
+        sharing::Share,
+        Serializer,
+    },
     util::AlignedVec,
     Archive, Deserialize, Portable, Serialize,
 };
@@ -25,16 +30,45 @@ use crate::{
     prelude::Link,
 };
 
+type Page<Row, const DATA_LENGTH: usize> = Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>;
+
+/// Source of the data pages that are not kept in memory, like the table's
+/// `.wt` file.
+pub trait PageSource<const DATA_LENGTH: usize>: Debug + Send + Sync {
+    /// Reads data page with the in-memory `index`.
+    fn read_page(&self, index: usize) -> eyre::Result<GeneralPage<DataPage<DATA_LENGTH>>>;
+}
+
+/// Pages read from the [`PageSource`] that are kept in memory.
+#[derive(Debug, Default)]
+struct PageCache {
+    /// Count of the pages that are kept.
+    capacity: usize,
+
+    /// Indexes of the kept pages in the order they were read. Pages changed
+    /// after they were read are removed from it, as they can't be evicted
+    /// until they are persisted.
+    queue: VecDeque<usize>,
+}
+
 #[derive(Debug)]
 pub struct DataPages<Row, const DATA_LENGTH: usize = DATA_INNER_LENGTH>
 where
     Row: StorableRow,
 {
-    /// Pages vector. Currently, not lock free.
-    pages: RwLock<Vec<Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>>>,
+    /// Pages vector. Currently, not lock free. `None` is page that is not
+    /// read from the `source` yet or was evicted.
+    pages: RwLock<Vec<Option<Page<Row, DATA_LENGTH>>>>,
+
+    /// Source of the pages that are not in memory. Set only if pages are
+    /// loaded lazily.
+    source: RwLock<Option<Arc<dyn PageSource<DATA_LENGTH>>>>,
+
+    cache: Mutex<PageCache>,
 
     /// Stack with empty [`Link`]s. It stores [`Link`]s of rows that was deleted.
-    empty_links: Stack<Link>,
+    /// It's not lock free, so it can be read without taking the links out.
+    empty_links: Mutex<Vec<Link>>,
 
     /// Count of saved rows.
     row_count: AtomicU64,
@@ -51,8 +85,10 @@ where
 {
     pub fn new() -> Self {
         Self {
-            pages: RwLock::new(vec![Arc::new(Data::new(0.into()))]),
-            empty_links: Stack::new(),
+            pages: RwLock::new(vec![Some(Arc::new(Data::new(0.into())))]),
+            source: RwLock::new(None),
+            cache: Mutex::new(PageCache::default()),
+            empty_links: Mutex::new(vec![]),
             row_count: AtomicU64::new(0),
             last_page_id: AtomicU32::new(0),
             current_page_index: AtomicU32::new(0),
@@ -63,14 +99,167 @@ where
         // TODO: Add row_count persistence.
         let last_page_id = vec.len() - 1;
         Self {
-            pages: RwLock::new(vec),
-            empty_links: Stack::new(),
+            pages: RwLock::new(vec.into_iter().map(Some).collect()),
+            source: RwLock::new(None),
+            cache: Mutex::new(PageCache::default()),
+            empty_links: Mutex::new(vec![]),
             row_count: AtomicU64::new(0),
             last_page_id: AtomicU32::new(last_page_id as u32),
             current_page_index: AtomicU32::new(last_page_id as u32),
         }
     }
 
+    /// Creates `count` pages that are read from the `source` on first
+    /// access. At most `capacity` pages read from it are kept in memory, the
+    /// least recently used ones are evicted. Changed pages are kept until
+    /// they are persisted and the source is replaced by [`set_source`].
+    ///
+    /// [`set_source`]: DataPages::set_source
+    pub fn lazy(count: usize, source: Arc<dyn PageSource<DATA_LENGTH>>, capacity: usize) -> Self {
+        if count == 0 {
+            let pages = Self::new();
+            *pages.source.write().unwrap() = Some(source);
+            pages.cache.lock().unwrap().capacity = capacity;
+            return pages;
+        }
+        let last_page_id = count - 1;
+        Self {
+            pages: RwLock::new(vec![None; count]),
+            source: RwLock::new(Some(source)),
+            cache: Mutex::new(PageCache {
+                capacity,
+                queue: VecDeque::new(),
+            }),
+            empty_links: Mutex::new(vec![]),
+            row_count: AtomicU64::new(0),
+            last_page_id: AtomicU32::new(last_page_id as u32),
+            current_page_index: AtomicU32::new(last_page_id as u32),
+        }
+    }
+
+    /// Removes all pages, leaving one empty page like [`DataPages::new`].
+    /// Pages are not read from the source after it.
+    pub fn clear(&self) {
+        let mut source = self.source.write().unwrap();
+        let mut pages = self.pages.write().unwrap();
+        *pages = vec![Some(Arc::new(Data::new(0.into())))];
+        *source = None;
+        self.cache.lock().unwrap().queue.clear();
+        self.empty_links.lock().unwrap().clear();
+        self.row_count.store(0, Ordering::Relaxed);
+        self.last_page_id.store(0, Ordering::Relaxed);
+        self.current_page_index.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if pages are read from the [`PageSource`].
+    pub fn is_lazy(&self) -> bool {
+        self.source.read().unwrap().is_some()
+    }
+
+    /// Returns count of the pages that are in memory.
+    pub fn resident_pages(&self) -> usize {
+        self.pages.read().unwrap().iter().flatten().count()
+    }
+
+    /// Replaces source of the pages that are not in memory by the file they
+    /// were just persisted to. Pages that weren't changed since then are
+    /// marked as read from it, so they can be evicted. Does nothing if pages
+    /// are not loaded lazily.
+    pub fn set_source(&self, source: Arc<dyn PageSource<DATA_LENGTH>>) {
+        let mut current = self.source.write().unwrap();
+        if current.is_none() {
+            return;
+        }
+        let mut pages = self.pages.write().unwrap();
+        let mut cache = self.cache.lock().unwrap();
+        cache.queue.clear();
+        for (index, page) in pages.iter().enumerate() {
+            if let Some(page) = page {
+                if !page.is_dirty() {
+                    page.set_in_source();
+                    cache.queue.push_back(index);
+                }
+            }
+        }
+        *current = Some(source);
+        Self::evict(&mut pages, &mut cache);
+    }
+
+    /// Returns page with the `index`, reading it from the source if it's not
+    /// in memory.
+    fn page(&self, index: usize) -> Result<Page<Row, DATA_LENGTH>, ExecutionError> {
+        {
+            let pages = self.pages.read().unwrap();
+            match pages.get(index) {
+                Some(Some(page)) => {
+                    page.touch();
+                    return Ok(page.clone());
+                }
+                Some(None) => {}
+                None => return Err(ExecutionError::PageNotFound((index as u32).into())),
+            }
+        }
+
+        // Page is read without lock, so other pages are accessed meanwhile.
+        let page = Arc::new(self.read_page(index)?);
+        let mut pages = self.pages.write().unwrap();
+        if let Some(loaded) = &pages[index] {
+            loaded.touch();
+            return Ok(loaded.clone());
+        }
+        pages[index] = Some(page.clone());
+        let mut cache = self.cache.lock().unwrap();
+        cache.queue.push_back(index);
+        Self::evict(&mut pages, &mut cache);
+
+        Ok(page)
+    }
+
+    /// Reads page with the `index` from the source.
+    fn read_page(
+        &self,
+        index: usize,
+    ) -> Result<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>, ExecutionError> {
+        let page_id: PageId = (index as u32).into();
+        let source = self
+            .source
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or(ExecutionError::PageNotFound(page_id))?;
+        let page = source.read_page(index).map_err(|e| {
+            ExecutionError::PageLoadError(format!("page {} can't be read: {}", index, e))
+        })?;
+        let mut data = Data::from_data_page(page);
+        data.set_page_id(page_id);
+
+        Ok(data)
+    }
+
+    /// Evicts pages until count of the cached pages is not greater than
+    /// cache's capacity. Pages used by someone else and pages accessed since
+    /// the previous check are kept.
+    fn evict(pages: &mut [Option<Page<Row, DATA_LENGTH>>], cache: &mut PageCache) {
+        let mut checks = cache.queue.len() * 2;
+        while cache.queue.len() > cache.capacity && checks != 0 {
+            checks -= 1;
+            let index = cache.queue.pop_front().expect("queue is not empty");
+            let Some(page) = &pages[index] else {
+                continue;
+            };
+            // Page can be changed while the source is replaced, so dirty
+            // flag is checked too.
+            if !page.is_in_source() || page.is_dirty() {
+                continue;
+            }
+            if Arc::strong_count(page) != 1 || page.take_accessed() {
+                cache.queue.push_back(index);
+                continue;
+            }
+            pages[index] = None;
+        }
+    }
+
     #[cfg_attr(
         feature = "perf_measurements",
         performance_measurement(prefix_name = "DataPages")
@@ -88,15 +277,20 @@ where
     {
         let general_row = <Row as StorableRow>::WrappedRow::from_inner(row);
 
-        if let Some(link) = self.empty_links.pop() {
-            let pages = self.pages.read().unwrap();
-            let current_page: usize = link.page_id.into();
-            let page = &pages[current_page];
+        let link = self.empty_links.lock().unwrap().pop();
+        if let Some(link) = link {
+            let page = match self.page(link.page_id.into()) {
+                Ok(page) => page,
+                Err(e) => {
+                    self.empty_links.lock().unwrap().push(link);
+                    return Err(e);
+                }
+            };
 
             return if let Err(e) = unsafe { page.save_row_by_link::<N>(&general_row, link) } {
                 match e {
                     DataExecutionError::InvalidLink => {
-                        self.empty_links.push(link);
+                        self.empty_links.lock().unwrap().push(link);
                         self.retry_insert::<N>(general_row)
                     }
                     DataExecutionError::PageIsFull { .. }
@@ -109,9 +303,8 @@ where
         }
 
         let (link, tried_page) = {
-            let pages = self.pages.read().unwrap();
             let current_page = self.current_page_index.load(Ordering::Relaxed);
-            let page = &pages[current_page as usize];
+            let page = self.page(current_page as usize)?;
 
             (page.save_row::<N>(&general_row), current_page)
         };
@@ -135,6 +328,87 @@ where
         Ok(res)
     }
 
+    /// Inserts many rows at once. Unlike [`DataPages::insert`] empty [`Link`]s
+    /// are not reused, so rows are written sequentially to the end of the
+    /// last page and new pages are added as needed. Rows are serialized into
+    /// one reused buffer and arena instead of allocating them for each row.
+    /// Returns rows back with their results in the same order as rows were
+    /// passed.
+    #[cfg_attr(
+        feature = "perf_measurements",
+        performance_measurement(prefix_name = "DataPages")
+    )]
+    pub fn insert_many<const N: usize>(
+        &self,
+        rows: Vec<Row>,
+    ) -> Vec<(Row, Result<Link, ExecutionError>)>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+    {
+        let mut res = Vec::with_capacity(rows.len());
+        let mut page_index = self.current_page_index.load(Ordering::Relaxed);
+        let mut page = match self.page(page_index as usize) {
+            Ok(page) => page,
+            Err(e) => {
+                let e = e.to_string();
+                return rows
+                    .into_iter()
+                    .map(|row| (row, Err(ExecutionError::PageLoadError(e.clone()))))
+                    .collect();
+            }
+        };
+
+        let mut arena = Arena::new();
+        let mut bytes = AlignedVec::new();
+        for row in rows {
+            let general_row = <Row as StorableRow>::WrappedRow::from_inner(row);
+            bytes.clear();
+            bytes = match to_bytes_in_with_alloc::<_, _, rkyv::rancor::Error>(
+                &general_row,
+                bytes,
+                arena.acquire(),
+            ) {
+                Ok(bytes) => bytes,
+                Err(_) => {
+                    res.push((
+                        general_row.get_inner(),
+                        Err(DataExecutionError::SerializeError.into()),
+                    ));
+                    bytes = AlignedVec::new();
+                    continue;
+                }
+            };
+            let link = match page.save_bytes(bytes.as_slice()) {
+                Err(DataExecutionError::PageIsFull { .. }) => {
+                    self.add_next_page(page_index);
+                    page_index = self.current_page_index.load(Ordering::Relaxed);
+                    match self.page(page_index as usize) {
+                        Ok(next) => {
+                            page = next;
+                            page.save_bytes(bytes.as_slice())
+                                .map_err(ExecutionError::DataPageError)
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+                link => link.map_err(ExecutionError::DataPageError),
+            };
+            if link.is_ok() {
+                self.row_count.fetch_add(1, Ordering::Relaxed);
+            }
+            res.push((general_row.get_inner(), link));
+        }
+
+        res
+    }
+
     fn retry_insert<const N: usize>(
         &self,
         general_row: <Row as StorableRow>::WrappedRow,
@@ -149,9 +423,8 @@ where
                 Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
             >,
     {
-        let pages = self.pages.read().unwrap();
         let current_page = self.current_page_index.load(Ordering::Relaxed);
-        let page = &pages[current_page as usize];
+        let page = self.page(current_page as usize)?;
 
         let res = page
             .save_row::<N>(&general_row)
@@ -169,7 +442,7 @@ where
         if tried_page == self.current_page_index.load(Ordering::Relaxed) {
             let index = self.last_page_id.fetch_add(1, Ordering::Relaxed) + 1;
 
-            pages.push(Arc::new(Data::new(index.into())));
+            pages.push(Some(Arc::new(Data::new(index.into()))));
             self.current_page_index.fetch_add(1, Ordering::Relaxed);
         }
     }
@@ -187,10 +460,7 @@ where
         <<Row as StorableRow>::WrappedRow as Archive>::Archived: Portable
             + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
     {
-        let pages = self.pages.read().unwrap();
-        let page = pages
-            .get::<usize>(link.page_id.into())
-            .ok_or(ExecutionError::PageNotFound(link.page_id))?;
+        let page = self.page(link.page_id.into())?;
         let gen_row = page.get_row(link).map_err(ExecutionError::DataPageError)?;
         Ok(gen_row.get_inner())
     }
@@ -207,10 +477,7 @@ where
             >,
         Op: Fn(&<<Row as StorableRow>::WrappedRow as Archive>::Archived) -> Res,
     {
-        let pages = self.pages.read().unwrap();
-        let page = pages
-            .get::<usize>(link.page_id.into())
-            .ok_or(ExecutionError::PageNotFound(link.page_id))?;
+        let page = self.page(link.page_id.into())?;
         let gen_row = page
             .get_row_ref(link)
             .map_err(ExecutionError::DataPageError)?;
@@ -235,10 +502,7 @@ where
         <<Row as StorableRow>::WrappedRow as Archive>::Archived: Portable,
         Op: FnMut(&mut <<Row as StorableRow>::WrappedRow as Archive>::Archived) -> Res,
     {
-        let pages = self.pages.read().unwrap();
-        let page = pages
-            .get::<usize>(link.page_id.into())
-            .ok_or(ExecutionError::PageNotFound(link.page_id))?;
+        let page = self.page(link.page_id.into())?;
         let gen_row = page
             .get_mut_row_ref(link)
             .map_err(ExecutionError::DataPageError)?
@@ -259,43 +523,72 @@ where
                 Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
             >,
     {
-        let pages = self.pages.read().unwrap();
-        let page = pages
-            .get::<usize>(link.page_id.into())
-            .ok_or(ExecutionError::PageNotFound(link.page_id))?;
+        let page = self.page(link.page_id.into())?;
         let gen_row = <Row as StorableRow>::WrappedRow::from_inner(row);
         page.save_row_by_link::<N>(&gen_row, link)
             .map_err(ExecutionError::DataPageError)
     }
 
     pub fn delete(&self, link: Link) -> Result<(), ExecutionError> {
-        self.empty_links.push(link);
+        self.empty_links.lock().unwrap().push(link);
         Ok(())
     }
 
-    pub fn get_bytes(&self) -> Vec<([u8; DATA_LENGTH], u32)> {
-        let pages = self.pages.read().unwrap();
+    /// Returns bytes of all pages. Pages that are not in memory are read
+    /// from the source, but are not kept.
+    pub fn get_bytes(&self) -> Result<Vec<([u8; DATA_LENGTH], u32)>, ExecutionError> {
+        let pages = self.pages.read().unwrap().clone();
         pages
-            .iter()
-            .map(|p| (p.get_bytes(), p.free_offset.load(Ordering::Relaxed)))
+            .into_iter()
+            .enumerate()
+            .map(|(index, page)| {
+                let page = match page {
+                    Some(page) => page,
+                    None => Arc::new(self.read_page(index)?),
+                };
+                Ok((page.get_bytes(), page.free_offset.load(Ordering::Relaxed)))
+            })
             .collect()
     }
 
-    pub fn get_empty_links(&self) -> Vec<Link> {
-        let mut res = vec![];
-        for l in self.empty_links.pop_iter() {
-            res.push(l)
+    /// Returns count of the pages and bytes of the pages that were changed
+    /// since the last call with their indexes. Dirty flags of the returned
+    /// pages are unset. Pages that are not in memory are never dirty.
+    pub fn get_dirty_bytes(&self) -> (usize, Vec<(usize, [u8; DATA_LENGTH], u32)>) {
+        let pages = self.pages.read().unwrap();
+        let dirty = pages
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| p.as_ref().map(|p| (i, p)))
+            .filter(|(_, p)| p.take_dirty())
+            .map(|(i, p)| (i, p.get_bytes(), p.free_offset.load(Ordering::Relaxed)))
+            .collect();
+        (pages.len(), dirty)
+    }
+
+    /// Unsets dirty flags of all pages. Used before all pages are persisted.
+    pub fn clear_dirty(&self) {
+        let pages = self.pages.read().unwrap();
+        for p in pages.iter().flatten() {
+            p.take_dirty();
         }
+    }
 
-        res
+    /// Takes all empty [`Link`]s out, so they are not reused by inserts.
+    pub fn get_empty_links(&self) -> Vec<Link> {
+        std::mem::take(&mut *self.empty_links.lock().unwrap())
     }
 
-    pub fn with_empty_links(mut self, links: Vec<Link>) -> Self {
-        let stack = Stack::new();
-        for l in links {
-            stack.push(l)
-        }
-        self.empty_links = stack;
+    /// Returns copy of the empty [`Link`]s. Unlike [`get_empty_links`], links
+    /// are left in place, so it's used when table is persisted.
+    ///
+    /// [`get_empty_links`]: DataPages::get_empty_links
+    pub fn empty_links(&self) -> Vec<Link> {
+        self.empty_links.lock().unwrap().clone()
+    }
+
+    pub fn with_empty_links(self, links: Vec<Link>) -> Self {
+        *self.empty_links.lock().unwrap() = links;
 
         self
     }
@@ -307,21 +600,26 @@ pub enum ExecutionError {
 
     PageNotFound(#[error(not(source))] PageId),
 
+    /// Page that is not in memory can't be read from its source.
+    PageLoadError(#[error(not(source))] String),
+
     Locked,
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
-    use std::sync::atomic::Ordering;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::{Arc, RwLock};
     use std::thread;
     use std::time::Instant;
 
-    use crate::in_memory::pages::DataPages;
+    use data_bucket::{DataPage, GeneralHeader, GeneralPage, PageType, DATA_VERSION};
+    use rkyv::{Archive, Deserialize, Serialize};
+
+    use crate::in_memory::pages::{DataPages, ExecutionError, PageSource};
     use crate::in_memory::row::GeneralRow;
     use crate::in_memory::StorableRow;
-    use rkyv::{Archive, Deserialize, Serialize};
 
     #[derive(
         Archive, Copy, Clone, Deserialize, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
@@ -380,14 +678,28 @@ mod tests {
         let link = pages.insert::<24>(row).unwrap();
         pages.delete(link).unwrap();
 
-        assert_eq!(pages.empty_links.pop(), Some(link));
-        pages.empty_links.push(link);
+        assert_eq!(pages.empty_links(), vec![link]);
 
         let row = TestRow { a: 20, b: 20 };
         let new_link = pages.insert::<24>(row).unwrap();
         assert_eq!(new_link, link)
     }
 
+    #[test]
+    fn empty_links_are_not_taken() {
+        let pages = DataPages::<TestRow>::new();
+
+        let link = pages.insert::<24>(TestRow { a: 10, b: 20 }).unwrap();
+        pages.delete(link).unwrap();
+
+        assert_eq!(pages.empty_links(), vec![link]);
+        assert_eq!(pages.empty_links(), vec![link]);
+
+        let new_link = pages.insert::<24>(TestRow { a: 20, b: 20 }).unwrap();
+        assert_eq!(new_link, link);
+        assert_eq!(pages.empty_links(), vec![]);
+    }
+
     #[test]
     fn insert_full() {
         let pages = DataPages::<TestRow, 24>::new();
@@ -399,6 +711,161 @@ mod tests {
         assert!(res.is_ok())
     }
 
+    #[test]
+    fn insert_many() {
+        let pages = DataPages::<TestRow, 48>::new();
+
+        let rows = (0..3).map(|i| TestRow { a: i, b: i }).collect();
+        let inserted = pages.insert_many::<24>(rows);
+        assert_eq!(inserted[2].0, TestRow { a: 2, b: 2 });
+        let links = inserted
+            .into_iter()
+            .map(|(_, link)| link)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(links[0].page_id, 0.into());
+        assert_eq!(links[1].page_id, 0.into());
+        assert_eq!(links[1].offset, 24);
+        assert_eq!(links[2].page_id, 1.into());
+        assert_eq!(links[2].offset, 0);
+        assert_eq!(pages.select(links[2]).unwrap(), TestRow { a: 2, b: 2 });
+        assert_eq!(pages.row_count.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn dirty_pages() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let (count, dirty) = pages.get_dirty_bytes();
+        assert_eq!(count, 1);
+        assert_eq!(dirty.len(), 1);
+        assert!(pages.get_dirty_bytes().1.is_empty());
+
+        let rows = (0..3).map(|i| TestRow { a: i, b: i }).collect();
+        let inserted = pages.insert_many::<24>(rows);
+        assert_eq!(inserted[2].0, TestRow { a: 2, b: 2 });
+        let links = inserted
+            .into_iter()
+            .map(|(_, link)| link)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let (count, dirty) = pages.get_dirty_bytes();
+        assert_eq!(count, 2);
+        assert_eq!(
+            dirty.iter().map(|(i, _, _)| *i).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        assert_eq!(dirty[1].2, 24);
+
+        unsafe { pages.update::<24>(TestRow { a: 5, b: 5 }, links[2]) }.unwrap();
+        let (_, dirty) = pages.get_dirty_bytes();
+        assert_eq!(
+            dirty.iter().map(|(i, _, _)| *i).collect::<Vec<_>>(),
+            vec![1]
+        );
+
+        pages.delete(links[0]).unwrap();
+        assert!(pages.get_dirty_bytes().1.is_empty());
+    }
+
+    #[derive(Debug)]
+    struct TestSource {
+        pages: Vec<([u8; 48], u32)>,
+        reads: AtomicUsize,
+    }
+
+    impl TestSource {
+        fn new(pages: &DataPages<TestRow, 48>) -> Self {
+            Self {
+                pages: pages.get_bytes().unwrap(),
+                reads: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl PageSource<48> for TestSource {
+        fn read_page(&self, index: usize) -> eyre::Result<GeneralPage<DataPage<48>>> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            let Some((data, length)) = self.pages.get(index).copied() else {
+                eyre::bail!("no page {}", index)
+            };
+            Ok(GeneralPage {
+                header: GeneralHeader {
+                    data_version: DATA_VERSION,
+                    page_id: (index as u32).into(),
+                    previous_id: 0.into(),
+                    next_id: 0.into(),
+                    page_type: PageType::Data,
+                    space_id: 0.into(),
+                    data_length: length,
+                },
+                inner: DataPage { data, length },
+            })
+        }
+    }
+
+    #[test]
+    fn lazy_pages() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let rows = (0..10).map(|i| TestRow { a: i, b: i }).collect();
+        let inserted = pages.insert_many::<24>(rows);
+        assert_eq!(inserted[2].0, TestRow { a: 2, b: 2 });
+        let links = inserted
+            .into_iter()
+            .map(|(_, link)| link)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let source = Arc::new(TestSource::new(&pages));
+
+        let lazy = DataPages::<TestRow, 48>::lazy(5, source.clone(), 2);
+        assert!(lazy.is_lazy());
+        assert_eq!(lazy.resident_pages(), 0);
+        for (i, link) in links.iter().enumerate() {
+            let i = i as u64;
+            assert_eq!(lazy.select(*link).unwrap(), TestRow { a: i, b: i });
+            assert!(lazy.resident_pages() <= 2);
+        }
+        assert_eq!(source.reads.load(Ordering::Relaxed), 5);
+
+        // Changed page is kept until it's persisted.
+        unsafe { lazy.update::<24>(TestRow { a: 50, b: 50 }, links[0]) }.unwrap();
+        for link in &links[2..] {
+            lazy.select(*link).unwrap();
+        }
+        assert!(lazy.resident_pages() <= 3);
+        let reads = source.reads.load(Ordering::Relaxed);
+        assert_eq!(lazy.select(links[0]).unwrap(), TestRow { a: 50, b: 50 });
+        assert_eq!(source.reads.load(Ordering::Relaxed), reads);
+
+        let (count, dirty) = lazy.get_dirty_bytes();
+        assert_eq!(count, 5);
+        assert_eq!(dirty.len(), 1);
+        let persisted = Arc::new(TestSource::new(&lazy));
+        lazy.set_source(persisted.clone());
+        assert!(lazy.resident_pages() <= 2);
+        for link in &links[2..] {
+            lazy.select(*link).unwrap();
+        }
+        assert_eq!(lazy.select(links[0]).unwrap(), TestRow { a: 50, b: 50 });
+        assert!(persisted.reads.load(Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    fn lazy_page_read_error() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let link = pages.insert::<24>(TestRow { a: 1, b: 1 }).unwrap();
+        let lazy = DataPages::<TestRow, 48>::lazy(2, Arc::new(TestSource::new(&pages)), 1);
+        let mut missing = link;
+        missing.page_id = 1.into();
+
+        assert_eq!(lazy.select(link).unwrap(), TestRow { a: 1, b: 1 });
+        assert!(matches!(
+            lazy.select(missing),
+            Err(ExecutionError::PageLoadError(_))
+        ));
+        assert!(lazy.get_bytes().is_err());
+    }
+
     //#[test]
     fn bench() {
         let pages = Arc::new(DataPages::<TestRow>::new());
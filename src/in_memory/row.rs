@@ -18,7 +18,7 @@ pub trait RowWrapper<Inner> {
 }
 
 pub trait ArchivedRow {
-    fn is_locked(&self) -> Option<u16>;
+    fn is_locked(&self) -> Option<u64>;
 }
 
 /// General `Row` wrapper that is used to append general data for every `Inner`
@@ -51,7 +51,7 @@ impl<Inner> ArchivedRow for ArchivedGeneralRow<Inner>
 where
     Inner: Archive,
 {
-    fn is_locked(&self) -> Option<u16> {
+    fn is_locked(&self) -> Option<u64> {
         None
     }
 }
@@ -6,6 +6,28 @@ pub trait TableSecondaryIndex<Row> {
     fn save_row(&self, row: Row, link: Link) -> Result<(), WorkTableError>;
 
     fn delete_row(&self, row: Row, link: Link) -> Result<(), WorkTableError>;
+
+    /// Removes all entries from the indexes.
+    fn clear(&self);
+
+    /// Saves many rows at once. If some row can't be saved, already saved
+    /// rows are removed from the index, so it is left unchanged. Generated
+    /// indexes sort the rows' keys and save them in order.
+    fn save_rows(&self, rows: &[(Row, Link)]) -> Result<(), WorkTableError>
+    where
+        Row: Clone,
+    {
+        for (i, (row, link)) in rows.iter().enumerate() {
+            if let Err(e) = self.save_row(row.clone(), *link) {
+                for (row, link) in rows[..i].iter() {
+                    self.delete_row(row.clone(), *link)?;
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl<Row> TableSecondaryIndex<Row> for () {
@@ -16,4 +38,10 @@ impl<Row> TableSecondaryIndex<Row> for () {
     fn delete_row(&self, _: Row, _: Link) -> Result<(), WorkTableError> {
         Ok(())
     }
+
+    fn clear(&self) {}
+
+    fn save_rows(&self, _: &[(Row, Link)]) -> Result<(), WorkTableError> {
+        Ok(())
+    }
 }
@@ -5,6 +5,8 @@ mod primary_key;
 mod row;
 mod table;
 pub use data_bucket as persistence;
+#[cfg(feature = "arrow")]
+pub use {arrow_array, arrow_schema, parquet};
 mod database;
 
 // mod ty;
@@ -19,13 +21,29 @@ pub use table::*;
 pub use worktable_codegen::worktable;
 
 pub mod prelude {
-    pub use crate::database::DatabaseManager;
-    pub use crate::in_memory::{ArchivedRow, Data, DataPages, RowWrapper, StorableRow};
-    pub use crate::lock::LockMap;
+    pub use crate::database::{
+        BackupEntry, BackupManifest, Backupable, CapturedSpace, CheckpointConfig,
+        CheckpointScheduler, Checkpointable, CorruptedPage, DatabaseManager, FileGeneration,
+        FilePageSource, IndexEntries, InspectSpace, PageChecksums, PageJournal, PageSummary,
+        PageVerifier, Restorable, RestoredBackup, SpaceFile, SpaceLayout, SpaceReport, SpaceSchema,
+        SpaceSections, Wal, WalOperation, WalRecord, WalSyncPolicy, WalWrite, BACKUP_MANIFEST,
+    };
+    pub use crate::in_memory::{ArchivedRow, Data, DataPages, PageSource, RowWrapper, StorableRow};
+    pub use crate::lock::{block_on, LockGuard, LockInfo, LockMap, LockMetricsSnapshot};
     pub use crate::primary_key::{PrimaryKeyGenerator, PrimaryKeyGeneratorState, TablePrimaryKey};
+    #[cfg(feature = "arrow")]
+    pub use crate::table::arrow::{
+        arrow_schema, to_record_batch, ArrowExport, ArrowExportError, ARROW_BATCH_SIZE,
+    };
+    pub use crate::table::arrow::{ArrowRow, ArrowScalar, ArrowTable, ArrowType, ArrowValue};
+    pub use crate::table::csv::{read_csv, write_csv, CsvError, CsvRow, CsvValue};
+    pub use crate::table::import::IMPORT_BATCH_SIZE;
+    pub use crate::table::increment::{IncrementOp, Incrementable};
+    pub use crate::table::json::{read_json_lines, write_json_lines, JsonLinesError};
     pub use crate::table::select::{
         Order, SelectQueryBuilder, SelectQueryExecutor, SelectResult, SelectResultExecutor,
     };
+    pub use crate::table::snapshot::{Snapshot, WritesPause};
     pub use crate::{
         lock::Lock, IndexSet, KeyValue, TableIndex, TableRow, TableSecondaryIndex, WorkTable,
         WorkTableError,
@@ -41,5 +59,5 @@ pub mod prelude {
     pub use derive_more::{From, Into};
     pub use lockfree::set::Set as LockFreeSet;
     pub use scc::{ebr::Guard, tree_index::TreeIndex};
-    pub use worktable_codegen::{PersistIndex, PersistTable};
+    pub use worktable_codegen::{ArrowValue, CsvValue, PersistIndex, PersistTable};
 }
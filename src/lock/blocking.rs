@@ -0,0 +1,27 @@
+use std::cell::Cell;
+use std::future::Future;
+
+thread_local! {
+    static BLOCKING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `fut` to completion on the current thread. Lock waits inside of it
+/// park the thread instead of relying on the async runtime, so it can be used
+/// without runtime, concurrently with async operations on the same table.
+pub fn block_on<F: Future>(fut: F) -> F::Output {
+    struct Reset(bool);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            BLOCKING.with(|b| b.set(self.0))
+        }
+    }
+
+    let _reset = Reset(BLOCKING.with(|b| b.replace(true)));
+    futures::executor::block_on(fut)
+}
+
+/// Returns `true` if called inside of [`block_on`].
+pub fn is_blocking() -> bool {
+    BLOCKING.with(|b| b.get())
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use crate::lock::{Lock, LockMap};
+use crate::prelude::Link;
+use crate::WorkTableError;
+
+/// Guard of the operation's [`Lock`]. On drop it clears row's lock fields
+/// using `release` callbacks, unlocks [`Lock`] and removes it from
+/// [`LockMap`]. So rows are not left locked if operation's future was dropped
+/// or operation panicked. If lock was removed by [`LockMap::clear`], row's
+/// lock fields are not cleared.
+pub struct LockGuard<'a> {
+    lock_map: &'a LockMap,
+    id: u64,
+    lock: Arc<Lock>,
+    generation: u64,
+    release: Vec<(Link, Box<dyn FnOnce() + Send + 'a>)>,
+}
+
+impl<'a> LockGuard<'a> {
+    /// Creates new [`Lock`] with new id and registers it in [`LockMap`].
+    pub fn new(lock_map: &'a LockMap) -> Self {
+        let (id, lock, generation) = lock_map.create_lock();
+
+        Self {
+            lock_map,
+            id,
+            lock,
+            generation,
+            release: Vec::new(),
+        }
+    }
+
+    /// Returns id of the guarded [`Lock`].
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Runs `acquire` that checks and sets lock fields of the row at `link`
+    /// atomically using [`LockMap::acquire_row`]. Fails with
+    /// [`WorkTableError::NotFound`] if lock was removed by [`LockMap::clear`],
+    /// as rows were removed with it.
+    pub fn acquire_row<R>(
+        &self,
+        link: Link,
+        acquire: impl FnOnce() -> R,
+    ) -> Result<R, WorkTableError> {
+        self.lock_map
+            .acquire_row(link, self.generation, acquire)
+            .ok_or(WorkTableError::NotFound)
+    }
+
+    /// Returns `true` if lock was removed by [`LockMap::clear`]. Rows it
+    /// locked were removed with it.
+    pub fn is_cleared(&self) -> bool {
+        self.lock_map.is_cleared(self.generation)
+    }
+
+    /// Adds callback that clears lock fields of the row at `link`. It must be
+    /// added only after row's lock fields were set by this operation.
+    pub fn on_release<F: FnOnce() + Send + 'a>(&mut self, link: Link, release: F) {
+        self.release.push((link, Box::new(release)));
+    }
+
+    /// Clears row's lock fields right away instead of on drop.
+    pub fn release(&mut self) {
+        for (link, release) in self.release.drain(..) {
+            self.lock_map.acquire_row(link, self.generation, release);
+        }
+    }
+}
+
+impl Drop for LockGuard<'_> {
+    fn drop(&mut self) {
+        self.release();
+        self.lock.unlock();
+        self.lock_map.remove(&self.id.into());
+    }
+}
@@ -0,0 +1,83 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Counters of the waits for row locks held by other operations.
+#[derive(Debug, Default)]
+pub struct LockMetrics {
+    waits: AtomicU64,
+    timeouts: AtomicU64,
+    waiting: AtomicU64,
+    total_wait_us: AtomicU64,
+    max_wait_us: AtomicU64,
+}
+
+/// Values of [`LockMetrics`] at some moment.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LockMetricsSnapshot {
+    /// Count of finished waits, including timed out ones.
+    pub waits: u64,
+    /// Count of waits that failed with timeout.
+    pub timeouts: u64,
+    /// Count of operations that are waiting for a lock now.
+    pub waiting: u64,
+    pub total_wait_time: Duration,
+    pub max_wait_time: Duration,
+}
+
+impl LockMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts wait measurement. Wait is recorded when returned [`WaitTimer`]
+    /// is dropped, so cancelled waits are recorded too.
+    pub fn start_wait(&self) -> WaitTimer<'_> {
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        WaitTimer {
+            metrics: self,
+            started: Instant::now(),
+            timed_out: false,
+        }
+    }
+
+    fn finish_wait(&self, wait_time: Duration, timed_out: bool) {
+        let us = u64::try_from(wait_time.as_micros()).unwrap_or(u64::MAX);
+        self.waiting.fetch_sub(1, Ordering::Relaxed);
+        self.waits.fetch_add(1, Ordering::Relaxed);
+        if timed_out {
+            self.timeouts.fetch_add(1, Ordering::Relaxed);
+        }
+        self.total_wait_us.fetch_add(us, Ordering::Relaxed);
+        self.max_wait_us.fetch_max(us, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> LockMetricsSnapshot {
+        LockMetricsSnapshot {
+            waits: self.waits.load(Ordering::Relaxed),
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            waiting: self.waiting.load(Ordering::Relaxed),
+            total_wait_time: Duration::from_micros(self.total_wait_us.load(Ordering::Relaxed)),
+            max_wait_time: Duration::from_micros(self.max_wait_us.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct WaitTimer<'a> {
+    metrics: &'a LockMetrics,
+    started: Instant,
+    timed_out: bool,
+}
+
+impl WaitTimer<'_> {
+    pub fn timed_out(mut self) {
+        self.timed_out = true;
+    }
+}
+
+impl Drop for WaitTimer<'_> {
+    fn drop(&mut self) {
+        self.metrics
+            .finish_wait(self.started.elapsed(), self.timed_out)
+    }
+}
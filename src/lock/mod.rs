@@ -1,50 +1,229 @@
+mod blocking;
+mod guard;
+mod metrics;
 mod set;
 
-use std::future::Future;
+use std::collections::VecDeque;
+use std::future::{Future, IntoFuture};
 use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::task::{Context, Poll};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::{Duration, Instant};
 
 use derive_more::From;
-use futures::task::AtomicWaker;
 use rkyv::{Archive, Deserialize, Serialize};
 
-pub use set::LockMap;
+pub use blocking::{block_on, is_blocking};
+pub use guard::LockGuard;
+pub use metrics::{LockMetrics, LockMetricsSnapshot, WaitTimer};
+pub use set::{LockInfo, LockMap, WaitFor};
 
 #[derive(
     Archive, Clone, Copy, Deserialize, Debug, Eq, From, Hash, Ord, Serialize, PartialEq, PartialOrd,
 )]
-pub struct LockId(u16);
+pub struct LockId(u64);
 
+/// Lock of the operation. Any number of tasks can wait for it, they are all
+/// woken in the order they started waiting when lock is unlocked. Waking
+/// doesn't give row to the waiter, woken operations take row's lock fields
+/// using [`LockMap::acquire_row`] and wait again if other operation was
+/// first.
 #[derive(Debug)]
 pub struct Lock {
     locked: AtomicBool,
-    waker: AtomicWaker,
+    waiters: Mutex<WaitQueue>,
+    created: Instant,
 }
 
-impl Future for &Lock {
-    type Output = ();
-
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.as_ref().waker.register(cx.waker());
-        if self.locked.load(Ordering::Relaxed) {
-            Poll::Pending
-        } else {
-            Poll::Ready(())
-        }
-    }
+#[derive(Debug, Default)]
+struct WaitQueue {
+    next_ticket: u64,
+    queue: VecDeque<(u64, Waker)>,
 }
 
 impl Lock {
     pub fn new() -> Self {
         Self {
             locked: AtomicBool::from(true),
-            waker: AtomicWaker::new(),
+            waiters: Mutex::new(WaitQueue::default()),
+            created: Instant::now(),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Acquire)
+    }
+
+    /// Returns time passed since lock was created.
+    pub fn held_for(&self) -> Duration {
+        self.created.elapsed()
+    }
+
+    /// Returns count of the tasks that are waiting for this lock now.
+    pub fn waiters_count(&self) -> usize {
+        self.waiters.lock().unwrap().queue.len()
+    }
+
+    /// Returns future that is ready when lock is unlocked.
+    pub fn wait(&self) -> LockWait<'_> {
+        LockWait {
+            lock: self,
+            ticket: None,
+        }
+    }
+
+    /// Blocks current thread until lock is unlocked, parking it while
+    /// waiting. Returns `false` if lock was not unlocked during `timeout`.
+    pub fn wait_blocking(&self, timeout: Option<Duration>) -> bool {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut wait = std::pin::pin!(self.wait());
+        loop {
+            if wait.as_mut().poll(&mut cx).is_ready() {
+                return true;
+            }
+            if let Some(deadline) = deadline {
+                let now = Instant::now();
+                if now >= deadline {
+                    return false;
+                }
+                std::thread::park_timeout(deadline - now);
+            } else {
+                std::thread::park();
+            }
         }
     }
 
     pub fn unlock(&self) {
-        self.locked.store(false, Ordering::Relaxed);
-        self.waker.wake()
+        self.locked.store(false, Ordering::Release);
+        let waiters = std::mem::take(&mut self.waiters.lock().unwrap().queue);
+        for (_, waker) in waiters {
+            waker.wake()
+        }
+    }
+}
+
+impl<'a> IntoFuture for &'a Lock {
+    type Output = ();
+    type IntoFuture = LockWait<'a>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.wait()
+    }
+}
+
+struct ThreadWaker(std::thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark()
+    }
+}
+
+/// Future returned by [`Lock::wait`]. Its place in the wait queue is kept
+/// between polls and is freed if it is dropped before lock is unlocked.
+#[derive(Debug)]
+pub struct LockWait<'a> {
+    lock: &'a Lock,
+    ticket: Option<u64>,
+}
+
+impl Future for LockWait<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut waiters = self.lock.waiters.lock().unwrap();
+        if !self.lock.locked.load(Ordering::Acquire) {
+            drop(waiters);
+            self.ticket = None;
+            return Poll::Ready(());
+        }
+
+        if let Some(ticket) = self.ticket {
+            if let Some((_, waker)) = waiters.queue.iter_mut().find(|(t, _)| *t == ticket) {
+                waker.clone_from(cx.waker());
+                return Poll::Pending;
+            }
+        }
+        let ticket = waiters.next_ticket;
+        waiters.next_ticket += 1;
+        waiters.queue.push_back((ticket, cx.waker().clone()));
+        drop(waiters);
+        self.ticket = Some(ticket);
+
+        Poll::Pending
+    }
+}
+
+impl Drop for LockWait<'_> {
+    fn drop(&mut self) {
+        if let Some(ticket) = self.ticket {
+            let mut waiters = self.lock.waiters.lock().unwrap();
+            waiters.queue.retain(|(t, _)| *t != ticket);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::lock::Lock;
+
+    #[tokio::test]
+    async fn all_waiters_are_woken_in_order() {
+        let lock = Arc::new(Lock::new());
+        let order = Arc::new(Mutex::new(vec![]));
+
+        let mut handles = vec![];
+        for i in 0..4 {
+            let waiter = lock.clone();
+            let order = order.clone();
+            handles.push(tokio::spawn(async move {
+                waiter.wait().await;
+                order.lock().unwrap().push(i);
+            }));
+            while lock.waiters_count() != i + 1 {
+                tokio::task::yield_now().await;
+            }
+        }
+        lock.unlock();
+        for h in handles {
+            h.await.unwrap();
+        }
+
+        assert_eq!(lock.waiters_count(), 0);
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn wait_blocking() {
+        let lock = Arc::new(Lock::new());
+        assert!(!lock.wait_blocking(Some(Duration::from_millis(10))));
+        assert_eq!(lock.waiters_count(), 0);
+
+        let handle = {
+            let lock = lock.clone();
+            std::thread::spawn(move || lock.wait_blocking(None))
+        };
+        while lock.waiters_count() == 0 {
+            std::thread::yield_now();
+        }
+        lock.unlock();
+        assert!(handle.join().unwrap());
+    }
+
+    #[tokio::test]
+    async fn dropped_waiter_leaves_queue() {
+        let lock = Lock::new();
+        let res = tokio::time::timeout(Duration::from_millis(10), lock.wait()).await;
+        assert!(res.is_err());
+        assert_eq!(lock.waiters_count(), 0);
+
+        lock.unlock();
+        lock.wait().await;
     }
 }
@@ -1,22 +1,74 @@
-use std::sync::atomic::{AtomicU16, Ordering};
-use std::sync::Arc;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 
 use lockfree::map::Map;
 
-use crate::lock::{Lock, LockId};
+use crate::lock::{Lock, LockId, LockMetrics};
+use crate::prelude::Link;
+
+/// Count of the mutexes that guard checks and sets of the row's lock fields.
+const ROW_STRIPES: usize = 64;
 
 #[derive(Debug)]
 pub struct LockMap {
     set: Map<LockId, Arc<Lock>>,
 
-    next_id: AtomicU16,
+    /// Id of the next created lock. Ids are never reused, so id read from
+    /// row's lock fields can't point to the lock of other operation.
+    next_id: AtomicU64,
+
+    /// Wait-for graph. Maps operation's lock id to the id of the lock it
+    /// waits for now.
+    waits_for: Mutex<HashMap<u64, u64>>,
+
+    /// Generation of the locks, it's changed by [`LockMap::clear`].
+    generation: AtomicU64,
+
+    /// Mutexes held while row's lock fields are checked and set, so only one
+    /// of the operations that found row unlocked takes it. Row uses the
+    /// mutex chosen by its [`Link`].
+    rows: Box<[Mutex<()>]>,
+
+    metrics: LockMetrics,
+}
+
+/// Info about lock that is held now.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LockInfo {
+    pub id: u64,
+    /// Count of operations waiting for this lock.
+    pub waiters: usize,
+    /// Id of the lock that operation with this lock waits for now.
+    pub waits_for: Option<u64>,
+    pub held_for: Duration,
+}
+
+/// Edge of the [`LockMap`] wait-for graph. It is removed on drop.
+#[derive(Debug)]
+pub struct WaitFor<'a> {
+    lock_map: &'a LockMap,
+    waiter: u64,
+}
+
+impl Drop for WaitFor<'_> {
+    fn drop(&mut self) {
+        self.lock_map.waits_for.lock().unwrap().remove(&self.waiter);
+    }
 }
 
 impl LockMap {
     pub fn new() -> Self {
         Self {
             set: Map::new(),
-            next_id: AtomicU16::default(),
+            next_id: AtomicU64::new(1),
+            waits_for: Mutex::new(HashMap::new()),
+            generation: AtomicU64::new(0),
+            rows: (0..ROW_STRIPES).map(|_| Mutex::new(())).collect(),
+            metrics: LockMetrics::new(),
         }
     }
 
@@ -28,11 +80,198 @@ impl LockMap {
         self.set.get(id).map(|v| v.val().clone())
     }
 
+    /// Removes lock with provided id. Does nothing if it was already removed
+    /// by [`LockMap::clear`].
     pub fn remove(&self, id: &LockId) {
         self.set.remove(id);
     }
 
-    pub fn next_id(&self) -> u16 {
-        self.next_id.fetch_add(1, Ordering::Relaxed)
+    /// Removes all locks, starting new generation of the locks. Operations
+    /// holding removed locks can't take rows anymore and don't clear row's
+    /// lock fields, as rows they locked are removed with them.
+    pub fn clear(&self) {
+        let _rows = self
+            .rows
+            .iter()
+            .map(|m| m.lock().unwrap())
+            .collect::<Vec<_>>();
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        let removed = self.set.iter().map(|v| *v.key()).collect::<Vec<_>>();
+        for id in removed {
+            self.set.remove(&id);
+        }
+    }
+
+    /// Creates new [`Lock`] and registers it with new id. `0` is never used as
+    /// id because rows use it as "unlocked" value. Returns lock's id, lock and
+    /// its generation.
+    pub fn create_lock(&self) -> (u64, Arc<Lock>, u64) {
+        // Generation is read before lock is registered, so lock registered
+        // after `clear` with the old generation is seen as cleared.
+        let generation = self.generation.load(Ordering::Acquire);
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let lock = Arc::new(Lock::new());
+        self.set.insert(id.into(), lock.clone());
+
+        (id, lock, generation)
+    }
+
+    /// Registers that operation with `waiter` lock waits for `holder` lock.
+    /// Returns `None` if `holder` already waits for `waiter` through other
+    /// operations, so this wait would never end.
+    pub fn wait_for(&self, waiter: u64, holder: u64) -> Option<WaitFor<'_>> {
+        let mut waits_for = self.waits_for.lock().unwrap();
+        let mut next = holder;
+        for _ in 0..=waits_for.len() {
+            if next == waiter {
+                return None;
+            }
+            match waits_for.get(&next) {
+                Some(id) => next = *id,
+                None => break,
+            }
+        }
+        waits_for.insert(waiter, holder);
+
+        Some(WaitFor {
+            lock_map: self,
+            waiter,
+        })
+    }
+
+    /// Runs `acquire` while no other operation checks or changes lock fields
+    /// of the row at `link`. So check of the fields and their set inside of
+    /// `acquire` are done atomically, like compare-and-swap. Returns `None`
+    /// without running `acquire` if locks of the provided `generation` were
+    /// removed by [`LockMap::clear`].
+    pub fn acquire_row<R>(
+        &self,
+        link: Link,
+        generation: u64,
+        acquire: impl FnOnce() -> R,
+    ) -> Option<R> {
+        let _row = self.row_mutex(link);
+        (!self.is_cleared(generation)).then(acquire)
+    }
+
+    fn row_mutex(&self, link: Link) -> MutexGuard<'_, ()> {
+        let mut hasher = DefaultHasher::new();
+        usize::from(link.page_id).hash(&mut hasher);
+        link.offset.hash(&mut hasher);
+        let stripe = hasher.finish() as usize % self.rows.len();
+        self.rows[stripe].lock().unwrap()
+    }
+
+    /// Returns `true` if locks of the provided `generation` were removed by
+    /// [`LockMap::clear`].
+    pub fn is_cleared(&self, generation: u64) -> bool {
+        self.generation.load(Ordering::Acquire) != generation
+    }
+
+    /// Returns info about all locks that are held now, ordered by id.
+    pub fn held_locks(&self) -> Vec<LockInfo> {
+        let waits_for = self.waits_for.lock().unwrap().clone();
+        let mut locks = self
+            .set
+            .iter()
+            .map(|v| LockInfo {
+                id: v.key().0,
+                waiters: v.val().waiters_count(),
+                waits_for: waits_for.get(&v.key().0).copied(),
+                held_for: v.val().held_for(),
+            })
+            .collect::<Vec<_>>();
+        locks.sort_by_key(|l| l.id);
+        locks
+    }
+
+    pub fn metrics(&self) -> &LockMetrics {
+        &self.metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use crate::lock::LockMap;
+    use crate::prelude::Link;
+
+    fn link(offset: u32) -> Link {
+        Link {
+            page_id: 1u32.into(),
+            offset,
+            length: 8,
+        }
+    }
+
+    #[test]
+    fn ids_are_not_reused() {
+        let map = LockMap::new();
+        let (first, _, _) = map.create_lock();
+        assert_eq!(first, 1);
+        map.remove(&first.into());
+
+        let (id, _, _) = map.create_lock();
+        assert_eq!(id, 2);
+        assert!(map.get(&first.into()).is_none());
+        assert_eq!(map.held_locks().len(), 1);
+        assert_eq!(map.held_locks()[0].id, id);
+    }
+
+    #[test]
+    fn clear_starts_new_generation() {
+        let map = LockMap::new();
+        let (old, _, generation) = map.create_lock();
+        map.clear();
+        assert!(map.held_locks().is_empty());
+        assert!(map.is_cleared(generation));
+        assert_eq!(map.acquire_row(link(0), generation, || ()), None);
+
+        let (id, _, new_generation) = map.create_lock();
+        assert_ne!(new_generation, generation);
+        assert_ne!(id, old);
+        // Removal by the guard of the cleared lock doesn't remove other locks.
+        map.remove(&old.into());
+        assert_eq!(map.held_locks().len(), 1);
+        assert_eq!(map.acquire_row(link(0), new_generation, || 1), Some(1));
+        map.remove(&id.into());
+        assert!(map.held_locks().is_empty());
+    }
+
+    #[test]
+    fn row_is_taken_once() {
+        let map = LockMap::new();
+        // Stands for the row's lock field.
+        let row_lock = AtomicU64::new(0);
+        let taken = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    let (id, _, generation) = map.create_lock();
+                    map.acquire_row(link(0), generation, || {
+                        if row_lock.load(Ordering::Relaxed) == 0 {
+                            std::thread::sleep(Duration::from_millis(1));
+                            row_lock.store(id, Ordering::Relaxed);
+                            taken.fetch_add(1, Ordering::Relaxed);
+                        }
+                    });
+                });
+            }
+        });
+        assert_eq!(taken.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn wait_for_cycle() {
+        let map = LockMap::new();
+        let a = map.wait_for(1, 2).unwrap();
+        let _b = map.wait_for(2, 3).unwrap();
+        assert!(map.wait_for(3, 1).is_none());
+        assert!(map.wait_for(3, 3).is_none());
+
+        drop(a);
+        assert!(map.wait_for(3, 1).is_some());
     }
 }
@@ -6,6 +6,10 @@ pub trait TablePrimaryKey {
 
 pub trait PrimaryKeyGenerator<T> {
     fn next(&self) -> T;
+
+    /// Resets generator to its initial state. Used when the table is
+    /// truncated. Does nothing by default.
+    fn reset(&self) {}
 }
 
 pub trait PrimaryKeyGeneratorState {
@@ -14,6 +18,10 @@ pub trait PrimaryKeyGeneratorState {
     fn get_state(&self) -> Self::State;
 
     fn from_state(state: Self::State) -> Self;
+
+    /// Moves generator to the `state` if it's behind it. Used when rows with
+    /// already generated keys are imported. Does nothing by default.
+    fn advance_to(&self, _state: Self::State) {}
 }
 
 impl<T> PrimaryKeyGenerator<T> for AtomicU32
@@ -23,6 +31,10 @@ where
     fn next(&self) -> T {
         self.fetch_add(1, Ordering::Relaxed).into()
     }
+
+    fn reset(&self) {
+        self.store(0, Ordering::Relaxed)
+    }
 }
 
 impl PrimaryKeyGeneratorState for AtomicU32 {
@@ -35,6 +47,10 @@ impl PrimaryKeyGeneratorState for AtomicU32 {
     fn from_state(state: Self::State) -> Self {
         AtomicU32::from(state)
     }
+
+    fn advance_to(&self, state: Self::State) {
+        self.fetch_max(state, Ordering::Relaxed);
+    }
 }
 
 impl<T> PrimaryKeyGenerator<T> for AtomicU64
@@ -44,6 +60,10 @@ where
     fn next(&self) -> T {
         self.fetch_add(1, Ordering::Relaxed).into()
     }
+
+    fn reset(&self) {
+        self.store(0, Ordering::Relaxed)
+    }
 }
 
 impl PrimaryKeyGeneratorState for AtomicU64 {
@@ -56,6 +76,10 @@ impl PrimaryKeyGeneratorState for AtomicU64 {
     fn from_state(state: Self::State) -> Self {
         AtomicU64::from(state)
     }
+
+    fn advance_to(&self, state: Self::State) {
+        self.fetch_max(state, Ordering::Relaxed);
+    }
 }
 
 impl<T> PrimaryKeyGenerator<T> for AtomicI64
@@ -65,6 +89,10 @@ where
     fn next(&self) -> T {
         self.fetch_add(1, Ordering::Relaxed).into()
     }
+
+    fn reset(&self) {
+        self.store(0, Ordering::Relaxed)
+    }
 }
 
 impl PrimaryKeyGeneratorState for AtomicI64 {
@@ -77,6 +105,10 @@ impl PrimaryKeyGeneratorState for AtomicI64 {
     fn from_state(state: Self::State) -> Self {
         AtomicI64::from(state)
     }
+
+    fn advance_to(&self, state: Self::State) {
+        self.fetch_max(state, Ordering::Relaxed);
+    }
 }
 
 impl PrimaryKeyGeneratorState for () {
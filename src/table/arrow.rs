@@ -0,0 +1,327 @@
+use crate::WorkTableError;
+
+/// Arrow type of the column. 128-bit integers and UUIDs don't have native
+/// Arrow types, so they are exported as strings. Enums are exported as
+/// dictionaries of their variant names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArrowType {
+    Boolean,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    Float32,
+    Float64,
+    Utf8,
+    Dictionary,
+}
+
+/// Value of the column converted for Arrow export.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArrowScalar {
+    Null,
+    Boolean(bool),
+    Int8(i8),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    UInt8(u8),
+    UInt16(u16),
+    UInt32(u32),
+    UInt64(u64),
+    Float32(f32),
+    Float64(f64),
+    Utf8(String),
+    Dictionary(&'static str),
+}
+
+/// Common trait for the column types that can be exported to Arrow.
+/// Unit-only enums can derive it.
+pub trait ArrowValue {
+    const TYPE: ArrowType;
+    const NULLABLE: bool = false;
+
+    fn to_arrow(&self) -> ArrowScalar;
+}
+
+macro_rules! impl_arrow_value {
+    ($($t:ty => $variant:ident),*) => {
+        $(
+            impl ArrowValue for $t {
+                const TYPE: ArrowType = ArrowType::$variant;
+
+                fn to_arrow(&self) -> ArrowScalar {
+                    ArrowScalar::$variant(*self)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_arrow_value_as_string {
+    ($($t:ty),*) => {
+        $(
+            impl ArrowValue for $t {
+                const TYPE: ArrowType = ArrowType::Utf8;
+
+                fn to_arrow(&self) -> ArrowScalar {
+                    ArrowScalar::Utf8(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_arrow_value!(
+    bool => Boolean,
+    i8 => Int8,
+    i16 => Int16,
+    i32 => Int32,
+    i64 => Int64,
+    u8 => UInt8,
+    u16 => UInt16,
+    u32 => UInt32,
+    u64 => UInt64,
+    f32 => Float32,
+    f64 => Float64
+);
+impl_arrow_value_as_string!(i128, u128, char, uuid::Uuid);
+
+impl ArrowValue for String {
+    const TYPE: ArrowType = ArrowType::Utf8;
+
+    fn to_arrow(&self) -> ArrowScalar {
+        ArrowScalar::Utf8(self.clone())
+    }
+}
+
+impl<T: ArrowValue> ArrowValue for Option<T> {
+    const TYPE: ArrowType = T::TYPE;
+    const NULLABLE: bool = true;
+
+    fn to_arrow(&self) -> ArrowScalar {
+        self.as_ref().map(T::to_arrow).unwrap_or(ArrowScalar::Null)
+    }
+}
+
+/// Row that can be exported to Arrow. Implemented for rows of the tables
+/// whose columns all implement [`ArrowValue`].
+pub trait ArrowRow {
+    /// Names, types and nullability of the columns in the declaration order.
+    const FIELDS: &'static [(&'static str, ArrowType, bool)];
+
+    /// Returns values of the row's columns in the order of [`FIELDS`].
+    ///
+    /// [`FIELDS`]: ArrowRow::FIELDS
+    fn to_arrow_values(&self) -> Vec<ArrowScalar>;
+}
+
+/// Table whose rows can be exported to Arrow. Export methods are provided
+/// by `ArrowExport` if `arrow` feature is enabled.
+pub trait ArrowTable {
+    type Row: ArrowRow;
+
+    /// Returns table's rows in primary key order.
+    fn arrow_rows(&self) -> Result<Vec<Self::Row>, WorkTableError>;
+}
+
+#[cfg(feature = "arrow")]
+pub use export::*;
+
+#[cfg(feature = "arrow")]
+mod export {
+    use std::fmt;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use arrow_array::types::{
+        Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type,
+        UInt32Type, UInt64Type, UInt8Type,
+    };
+    use arrow_array::{
+        ArrayRef, BooleanArray, DictionaryArray, PrimitiveArray, RecordBatch, StringArray,
+    };
+    use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+    use parquet::arrow::ArrowWriter;
+    use parquet::errors::ParquetError;
+
+    use crate::table::arrow::{ArrowRow, ArrowScalar, ArrowTable, ArrowType};
+    use crate::table::select::{SelectResult, SelectResultExecutor};
+    use crate::WorkTableError;
+
+    /// Count of rows in one record batch of the table's export.
+    pub const ARROW_BATCH_SIZE: usize = 8192;
+
+    #[derive(Debug)]
+    pub enum ArrowExportError {
+        Arrow(ArrowError),
+        Parquet(ParquetError),
+        /// Rows of the table can't be selected.
+        Select(WorkTableError),
+    }
+
+    impl fmt::Display for ArrowExportError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                ArrowExportError::Arrow(e) => write!(f, "{}", e),
+                ArrowExportError::Parquet(e) => write!(f, "{}", e),
+                ArrowExportError::Select(e) => write!(f, "rows can't be selected: {}", e),
+            }
+        }
+    }
+
+    impl std::error::Error for ArrowExportError {}
+
+    impl From<ArrowError> for ArrowExportError {
+        fn from(e: ArrowError) -> Self {
+            ArrowExportError::Arrow(e)
+        }
+    }
+
+    impl From<ParquetError> for ArrowExportError {
+        fn from(e: ParquetError) -> Self {
+            ArrowExportError::Parquet(e)
+        }
+    }
+
+    impl From<ArrowType> for DataType {
+        fn from(type_: ArrowType) -> Self {
+            match type_ {
+                ArrowType::Boolean => DataType::Boolean,
+                ArrowType::Int8 => DataType::Int8,
+                ArrowType::Int16 => DataType::Int16,
+                ArrowType::Int32 => DataType::Int32,
+                ArrowType::Int64 => DataType::Int64,
+                ArrowType::UInt8 => DataType::UInt8,
+                ArrowType::UInt16 => DataType::UInt16,
+                ArrowType::UInt32 => DataType::UInt32,
+                ArrowType::UInt64 => DataType::UInt64,
+                ArrowType::Float32 => DataType::Float32,
+                ArrowType::Float64 => DataType::Float64,
+                ArrowType::Utf8 => DataType::Utf8,
+                ArrowType::Dictionary => {
+                    DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+                }
+            }
+        }
+    }
+
+    /// Returns Arrow schema of the row's columns.
+    pub fn arrow_schema<Row: ArrowRow>() -> SchemaRef {
+        let fields = Row::FIELDS
+            .iter()
+            .map(|(name, type_, nullable)| Field::new(*name, (*type_).into(), *nullable))
+            .collect::<Vec<_>>();
+        Arc::new(Schema::new(fields))
+    }
+
+    /// Converts rows to the record batch with [`arrow_schema`].
+    pub fn to_record_batch<Row: ArrowRow>(rows: &[Row]) -> Result<RecordBatch, ArrowError> {
+        let mut columns = Row::FIELDS
+            .iter()
+            .map(|_| Vec::with_capacity(rows.len()))
+            .collect::<Vec<_>>();
+        for row in rows {
+            for (column, value) in columns.iter_mut().zip(row.to_arrow_values()) {
+                column.push(value);
+            }
+        }
+        let arrays = Row::FIELDS
+            .iter()
+            .zip(columns)
+            .map(|((name, type_, _), values)| to_array(name, *type_, values))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        RecordBatch::try_new(arrow_schema::<Row>(), arrays)
+    }
+
+    macro_rules! collect_array {
+        ($name:expr, $values:expr, $array:ty, $variant:ident) => {
+            Arc::new(
+                $values
+                    .into_iter()
+                    .map(|value| match value {
+                        ArrowScalar::$variant(v) => Ok(Some(v)),
+                        ArrowScalar::Null => Ok(None),
+                        value => Err(ArrowError::InvalidArgumentError(format!(
+                            "column {} has value {:?} instead of {}",
+                            $name,
+                            value,
+                            stringify!($variant)
+                        ))),
+                    })
+                    .collect::<Result<$array, _>>()?,
+            )
+        };
+    }
+
+    fn to_array(
+        name: &str,
+        type_: ArrowType,
+        values: Vec<ArrowScalar>,
+    ) -> Result<ArrayRef, ArrowError> {
+        let array: ArrayRef = match type_ {
+            ArrowType::Boolean => collect_array!(name, values, BooleanArray, Boolean),
+            ArrowType::Int8 => collect_array!(name, values, PrimitiveArray<Int8Type>, Int8),
+            ArrowType::Int16 => collect_array!(name, values, PrimitiveArray<Int16Type>, Int16),
+            ArrowType::Int32 => collect_array!(name, values, PrimitiveArray<Int32Type>, Int32),
+            ArrowType::Int64 => collect_array!(name, values, PrimitiveArray<Int64Type>, Int64),
+            ArrowType::UInt8 => collect_array!(name, values, PrimitiveArray<UInt8Type>, UInt8),
+            ArrowType::UInt16 => collect_array!(name, values, PrimitiveArray<UInt16Type>, UInt16),
+            ArrowType::UInt32 => collect_array!(name, values, PrimitiveArray<UInt32Type>, UInt32),
+            ArrowType::UInt64 => collect_array!(name, values, PrimitiveArray<UInt64Type>, UInt64),
+            ArrowType::Float32 => {
+                collect_array!(name, values, PrimitiveArray<Float32Type>, Float32)
+            }
+            ArrowType::Float64 => {
+                collect_array!(name, values, PrimitiveArray<Float64Type>, Float64)
+            }
+            ArrowType::Utf8 => collect_array!(name, values, StringArray, Utf8),
+            ArrowType::Dictionary => {
+                collect_array!(name, values, DictionaryArray<Int32Type>, Dictionary)
+            }
+        };
+        Ok(array)
+    }
+
+    /// Export of the table's rows to Arrow record batches and Parquet files.
+    pub trait ArrowExport: ArrowTable {
+        /// Returns table's rows as record batches of [`ARROW_BATCH_SIZE`]
+        /// rows.
+        fn to_record_batches(&self) -> Result<Vec<RecordBatch>, ArrowExportError> {
+            let rows = self.arrow_rows().map_err(ArrowExportError::Select)?;
+            let batches = rows
+                .chunks(ARROW_BATCH_SIZE)
+                .map(to_record_batch)
+                .collect::<Result<_, _>>()?;
+            Ok(batches)
+        }
+
+        /// Writes table's rows to the `writer` as Parquet file.
+        fn write_parquet<W: Write + Send>(&self, writer: W) -> Result<(), ArrowExportError> {
+            let mut writer = ArrowWriter::try_new(writer, arrow_schema::<Self::Row>(), None)?;
+            for batch in self.to_record_batches()? {
+                writer.write(&batch)?;
+            }
+            writer.close()?;
+            Ok(())
+        }
+    }
+
+    impl<T: ArrowTable> ArrowExport for T {}
+
+    impl<Row, W> SelectResult<Row, W>
+    where
+        Row: ArrowRow,
+        W: SelectResultExecutor<Row>,
+    {
+        /// Executes the query and returns its rows as record batch.
+        pub fn to_record_batch(self) -> Result<RecordBatch, ArrowError> {
+            to_record_batch(&self.execute())
+        }
+    }
+}
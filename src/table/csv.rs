@@ -0,0 +1,219 @@
+use std::fmt;
+use std::io::{Read, Write};
+
+use crate::table::import::insert_in_batches;
+use crate::WorkTableError;
+
+/// Common trait for the column types that can be exported to and imported
+/// from CSV. Unit-only enums can derive it, so their values are written as
+/// variant names.
+pub trait CsvValue: Sized {
+    fn to_csv(&self) -> String;
+
+    /// Parses value from the CSV field. Returns description of the problem
+    /// if value is invalid.
+    fn from_csv(value: &str) -> Result<Self, String>;
+}
+
+macro_rules! impl_csv_value {
+    ($($t:ty),*) => {
+        $(
+            impl CsvValue for $t {
+                fn to_csv(&self) -> String {
+                    self.to_string()
+                }
+
+                fn from_csv(value: &str) -> Result<Self, String> {
+                    value.parse::<$t>().map_err(|e| e.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_csv_value!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    f32,
+    f64,
+    bool,
+    char,
+    String,
+    uuid::Uuid
+);
+
+/// `None` is written as an empty field, so empty `Option<String>` can't be
+/// distinguished from `None` and is imported as `None`.
+impl<T: CsvValue> CsvValue for Option<T> {
+    fn to_csv(&self) -> String {
+        self.as_ref().map(T::to_csv).unwrap_or_default()
+    }
+
+    fn from_csv(value: &str) -> Result<Self, String> {
+        if value.is_empty() {
+            Ok(None)
+        } else {
+            T::from_csv(value).map(Some)
+        }
+    }
+}
+
+/// Row that can be exported to and imported from CSV. Implemented for rows
+/// of the tables whose columns all implement [`CsvValue`].
+pub trait CsvRow: Sized {
+    /// Names of the columns in the declaration order, used as CSV header.
+    const COLUMNS: &'static [&'static str];
+
+    /// Returns values of the row's columns in the order of [`COLUMNS`].
+    ///
+    /// [`COLUMNS`]: CsvRow::COLUMNS
+    fn to_csv_values(&self) -> Vec<String>;
+
+    /// Parses row from the values in the order of [`COLUMNS`]. Returns name
+    /// of the column with invalid value and description of the problem.
+    ///
+    /// [`COLUMNS`]: CsvRow::COLUMNS
+    fn from_csv_values(values: &[&str]) -> Result<Self, (&'static str, String)>;
+}
+
+#[derive(Debug)]
+pub enum CsvError {
+    /// CSV can't be read or written.
+    Csv(csv::Error),
+    /// CSV header doesn't have the same columns as the table.
+    Header {
+        expected: Vec<String>,
+        found: Vec<String>,
+    },
+    /// Value of the column can't be parsed.
+    Value {
+        line: u64,
+        column: &'static str,
+        message: String,
+    },
+    /// Row can't be inserted into the table.
+    Insert { line: u64, error: WorkTableError },
+    /// Rows of the table can't be selected.
+    Select(WorkTableError),
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Csv(e) => write!(f, "{}", e),
+            CsvError::Header { expected, found } => write!(
+                f,
+                "CSV header has columns ({}), but the table has columns ({})",
+                found.join(", "),
+                expected.join(", ")
+            ),
+            CsvError::Value {
+                line,
+                column,
+                message,
+            } => write!(f, "line {}: invalid value of {}: {}", line, column, message),
+            CsvError::Insert { line, error } => {
+                write!(f, "line {}: row can't be inserted: {}", line, error)
+            }
+            CsvError::Select(e) => write!(f, "rows can't be selected: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl From<csv::Error> for CsvError {
+    fn from(e: csv::Error) -> Self {
+        CsvError::Csv(e)
+    }
+}
+
+/// Writes header and rows to the `writer`.
+pub fn write_csv<Row: CsvRow, W: Write>(rows: Vec<Row>, writer: W) -> Result<(), CsvError> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(Row::COLUMNS)?;
+    for row in rows {
+        writer.write_record(row.to_csv_values())?;
+    }
+    writer.flush().map_err(csv::Error::from)?;
+
+    Ok(())
+}
+
+/// Reads rows from the `reader` and inserts them in batches of
+/// [`IMPORT_BATCH_SIZE`] rows with `insert_many`. Columns are matched to the
+/// header by names, so they can be in any order.
+///
+/// If batch can't be inserted, `insert_many` gives its rows back and they are
+/// inserted one by one with `insert` to find the failed row. Rows before it
+/// stay in the table. Returns count of the inserted rows.
+///
+/// [`IMPORT_BATCH_SIZE`]: crate::table::import::IMPORT_BATCH_SIZE
+pub fn read_csv<Row, R, M, I>(reader: R, insert_many: M, insert: I) -> Result<usize, CsvError>
+where
+    Row: CsvRow,
+    R: Read,
+    M: FnMut(Vec<Row>) -> Result<(), Vec<Row>>,
+    I: FnMut(Row) -> Result<(), WorkTableError>,
+{
+    let mut reader = csv::Reader::from_reader(reader);
+    let header = reader.headers()?.clone();
+    let positions = Row::COLUMNS
+        .iter()
+        .map(|column| header.iter().position(|h| h == *column))
+        .collect::<Option<Vec<_>>>();
+    let positions = match positions {
+        Some(positions) if header.len() == Row::COLUMNS.len() => positions,
+        _ => {
+            return Err(CsvError::Header {
+                expected: Row::COLUMNS.iter().map(|c| c.to_string()).collect(),
+                found: header.iter().map(|h| h.to_string()).collect(),
+            })
+        }
+    };
+
+    let rows = reader.into_records().map(|record| {
+        let record = record?;
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        let values = positions.iter().map(|&i| &record[i]).collect::<Vec<_>>();
+        let row = Row::from_csv_values(&values).map_err(|(column, message)| CsvError::Value {
+            line,
+            column,
+            message,
+        })?;
+        Ok((line, row))
+    });
+
+    insert_in_batches(rows, insert_many, insert, |line, error| CsvError::Insert {
+        line,
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::table::csv::CsvValue;
+
+    #[test]
+    fn optional_value() {
+        assert_eq!(Some(5u64).to_csv(), "5");
+        assert_eq!(None::<u64>.to_csv(), "");
+        assert_eq!(Option::<u64>::from_csv(""), Ok(None));
+        assert_eq!(Option::<u64>::from_csv("5"), Ok(Some(5)));
+        assert!(Option::<u64>::from_csv("a").is_err());
+    }
+
+    #[test]
+    fn string_value() {
+        assert_eq!(String::from_csv("a, b"), Ok("a, b".to_string()));
+        assert_eq!("a".to_string().to_csv(), "a");
+    }
+}
@@ -0,0 +1,46 @@
+use crate::WorkTableError;
+
+/// Count of rows inserted by one `insert_many` call during import.
+pub const IMPORT_BATCH_SIZE: usize = 1024;
+
+/// Inserts rows read with their line numbers in batches of
+/// [`IMPORT_BATCH_SIZE`] rows with `insert_many`.
+///
+/// If batch can't be inserted, rows given back by `insert_many` are inserted
+/// one by one with `insert` to find the failed row, which is reported by
+/// `insert_error`. Rows before it stay in the table. Returns count of the
+/// inserted rows.
+pub(crate) fn insert_in_batches<Row, E, M, I>(
+    rows: impl Iterator<Item = Result<(u64, Row), E>>,
+    mut insert_many: M,
+    mut insert: I,
+    insert_error: impl Fn(u64, WorkTableError) -> E,
+) -> Result<usize, E>
+where
+    M: FnMut(Vec<Row>) -> Result<(), Vec<Row>>,
+    I: FnMut(Row) -> Result<(), WorkTableError>,
+{
+    let mut inserted = 0;
+    let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+    let mut insert_batch = |batch: Vec<(u64, Row)>| -> Result<usize, E> {
+        let len = batch.len();
+        let (lines, rows): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+        if let Err(rows) = insert_many(rows) {
+            for (line, row) in lines.into_iter().zip(rows) {
+                insert(row).map_err(|e| insert_error(line, e))?;
+            }
+        }
+        Ok(len)
+    };
+    for row in rows {
+        batch.push(row?);
+        if batch.len() == IMPORT_BATCH_SIZE {
+            inserted += insert_batch(std::mem::take(&mut batch))?;
+        }
+    }
+    if !batch.is_empty() {
+        inserted += insert_batch(batch)?;
+    }
+
+    Ok(inserted)
+}
@@ -0,0 +1,84 @@
+/// Operation that is applied to the column by `increment` queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncrementOp {
+    /// Adds value to the column (`+=`).
+    Add,
+    /// Subtracts value from the column (`-=`).
+    Sub,
+    /// Sets column to the minimum of its current value and provided one.
+    Min,
+    /// Sets column to the maximum of its current value and provided one.
+    Max,
+}
+
+/// Common trait for the column types that can be used in `increment` queries.
+pub trait Incrementable: Sized + PartialOrd {
+    /// Applies `op` to `self` with `rhs` operand. Returns `None` if result
+    /// overflows.
+    fn apply(self, op: IncrementOp, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_incrementable_int {
+    ($($t:ty),*) => {
+        $(
+            impl Incrementable for $t {
+                fn apply(self, op: IncrementOp, rhs: Self) -> Option<Self> {
+                    match op {
+                        IncrementOp::Add => self.checked_add(rhs),
+                        IncrementOp::Sub => self.checked_sub(rhs),
+                        IncrementOp::Min => Some(self.min(rhs)),
+                        IncrementOp::Max => Some(self.max(rhs)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_incrementable_float {
+    ($($t:ty),*) => {
+        $(
+            impl Incrementable for $t {
+                fn apply(self, op: IncrementOp, rhs: Self) -> Option<Self> {
+                    match op {
+                        IncrementOp::Add => Some(self + rhs),
+                        IncrementOp::Sub => Some(self - rhs),
+                        IncrementOp::Min => Some(self.min(rhs)),
+                        IncrementOp::Max => Some(self.max(rhs)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_incrementable_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+impl_incrementable_float!(f32, f64);
+
+#[cfg(test)]
+mod tests {
+    use crate::table::increment::{IncrementOp, Incrementable};
+
+    #[test]
+    fn int_apply() {
+        assert_eq!(10u64.apply(IncrementOp::Add, 5), Some(15));
+        assert_eq!(10u64.apply(IncrementOp::Sub, 5), Some(5));
+        assert_eq!(10u64.apply(IncrementOp::Min, 5), Some(5));
+        assert_eq!(10u64.apply(IncrementOp::Max, 5), Some(10));
+    }
+
+    #[test]
+    fn int_overflow() {
+        assert_eq!(u64::MAX.apply(IncrementOp::Add, 1), None);
+        assert_eq!(0u64.apply(IncrementOp::Sub, 1), None);
+        assert_eq!(i8::MIN.apply(IncrementOp::Sub, 1), None);
+    }
+
+    #[test]
+    fn float_apply() {
+        assert_eq!(1.5f64.apply(IncrementOp::Add, 1.0), Some(2.5));
+        assert_eq!(1.5f64.apply(IncrementOp::Sub, 1.0), Some(0.5));
+        assert_eq!(1.5f64.apply(IncrementOp::Min, 1.0), Some(1.0));
+        assert_eq!(1.5f64.apply(IncrementOp::Max, 1.0), Some(1.5));
+    }
+}
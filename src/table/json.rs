@@ -0,0 +1,91 @@
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::table::import::insert_in_batches;
+use crate::WorkTableError;
+
+#[derive(Debug)]
+pub enum JsonLinesError {
+    /// Lines can't be read or written.
+    Io(std::io::Error),
+    /// Line is not a valid JSON of the row.
+    Json { line: u64, error: serde_json::Error },
+    /// Row can't be inserted into the table.
+    Insert { line: u64, error: WorkTableError },
+    /// Rows of the table can't be selected.
+    Select(WorkTableError),
+}
+
+impl fmt::Display for JsonLinesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonLinesError::Io(e) => write!(f, "{}", e),
+            JsonLinesError::Json { line, error } => {
+                write!(f, "line {}: invalid row: {}", line, error)
+            }
+            JsonLinesError::Insert { line, error } => {
+                write!(f, "line {}: row can't be inserted: {}", line, error)
+            }
+            JsonLinesError::Select(e) => write!(f, "rows can't be selected: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for JsonLinesError {}
+
+impl From<std::io::Error> for JsonLinesError {
+    fn from(e: std::io::Error) -> Self {
+        JsonLinesError::Io(e)
+    }
+}
+
+/// Writes rows to the `writer` as JSON objects, one per line.
+pub fn write_json_lines<Row: Serialize, W: Write>(
+    rows: Vec<Row>,
+    mut writer: W,
+) -> Result<(), JsonLinesError> {
+    for (i, row) in rows.iter().enumerate() {
+        serde_json::to_writer(&mut writer, row).map_err(|error| JsonLinesError::Json {
+            line: i as u64 + 1,
+            error,
+        })?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Reads rows written by [`write_json_lines`] from the `reader` and inserts
+/// them like [`read_csv`] does. Empty lines are skipped.
+///
+/// [`read_csv`]: crate::table::csv::read_csv
+pub fn read_json_lines<Row, R, M, I>(
+    reader: R,
+    insert_many: M,
+    insert: I,
+) -> Result<usize, JsonLinesError>
+where
+    Row: DeserializeOwned,
+    R: Read,
+    M: FnMut(Vec<Row>) -> Result<(), Vec<Row>>,
+    I: FnMut(Row) -> Result<(), WorkTableError>,
+{
+    let rows = BufReader::new(reader)
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i as u64 + 1, line))
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(line, value)| {
+            let row = serde_json::from_str(&value?)
+                .map_err(|error| JsonLinesError::Json { line, error })?;
+            Ok((line, row))
+        });
+
+    insert_in_batches(rows, insert_many, insert, |line, error| {
+        JsonLinesError::Insert { line, error }
+    })
+}
@@ -1,9 +1,18 @@
+pub mod arrow;
+pub mod csv;
+pub mod import;
+pub mod increment;
+pub mod json;
 pub mod select;
+pub mod snapshot;
 
+use crate::database::{SpaceLayout, Wal, WalOperation};
 use crate::in_memory::{DataPages, RowWrapper, StorableRow};
-use crate::lock::LockMap;
+use crate::lock::{is_blocking, LockMap};
 use crate::primary_key::{PrimaryKeyGenerator, TablePrimaryKey};
+use crate::snapshot::{Snapshot, VersionStore, WritesPause};
 use crate::{in_memory, TableIndex, TableRow, TableSecondaryIndex};
+use data_bucket::page::PageId;
 use data_bucket::{Link, INNER_PAGE_SIZE};
 use derive_more::{Display, Error, From};
 #[cfg(feature = "perf_measurements")]
@@ -16,6 +25,8 @@ use rkyv::ser::Serializer;
 use rkyv::util::AlignedVec;
 use rkyv::{Archive, Deserialize, Serialize};
 use std::marker::PhantomData;
+use std::sync::Mutex;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct WorkTable<
@@ -42,6 +53,21 @@ pub struct WorkTable<
 
     pub table_name: &'static str,
 
+    /// Max time to wait for a row lock held by another operation. Operations
+    /// wait indefinitely if it is not set.
+    pub lock_timeout: Option<Duration>,
+
+    /// Old row versions needed by live snapshots.
+    pub versions: VersionStore<PrimaryKey, Row>,
+
+    /// Write-ahead log of the table's changes. It's set only for persisted
+    /// tables with WAL enabled that were loaded using `load_from_file`.
+    pub wal: Option<Wal>,
+
+    /// Placement of the pages in the table's file. It's set after the table
+    /// is persisted or loaded, so next persist writes only changed pages.
+    pub space_layout: Mutex<Option<SpaceLayout>>,
+
     pub pk_phantom: PhantomData<PrimaryKey>,
 }
 
@@ -64,6 +90,10 @@ where
             pk_gen: Default::default(),
             lock_map: LockMap::new(),
             table_name: "",
+            lock_timeout: None,
+            versions: VersionStore::default(),
+            wal: None,
+            space_layout: Mutex::new(None),
             pk_phantom: PhantomData,
         }
     }
@@ -85,6 +115,212 @@ where
         self.pk_gen.next()
     }
 
+    /// Waits until [`Lock`] with provided id is unlocked. `op_id` is id of the
+    /// waiting operation's lock. Returns [`WorkTableError::Deadlock`] if
+    /// lock's holder waits for this operation, directly or through other
+    /// operations, and [`WorkTableError::LockTimeout`] if lock was not
+    /// unlocked during `lock_timeout`. Inside of [`block_on`] current thread
+    /// is parked while waiting.
+    ///
+    /// [`Lock`]: crate::lock::Lock
+    /// [`block_on`]: crate::lock::block_on
+    pub async fn wait_lock(&self, op_id: u64, id: u64) -> Result<(), WorkTableError> {
+        let Some(lock) = self.lock_map.get(&id.into()) else {
+            return Ok(());
+        };
+        let Some(_wait_for) = self.lock_map.wait_for(op_id, id) else {
+            return Err(WorkTableError::Deadlock);
+        };
+        let timer = self.lock_map.metrics().start_wait();
+        if is_blocking() {
+            if !lock.wait_blocking(self.lock_timeout) {
+                timer.timed_out();
+                return Err(WorkTableError::LockTimeout);
+            }
+        } else if let Some(timeout) = self.lock_timeout {
+            if tokio::time::timeout(timeout, lock.wait()).await.is_err() {
+                timer.timed_out();
+                return Err(WorkTableError::LockTimeout);
+            }
+        } else {
+            lock.wait().await;
+        }
+
+        Ok(())
+    }
+
+    /// Creates read view of the table's current state. Rows changed after it
+    /// was created are seen by it with their old values.
+    pub fn snapshot(&self) -> Snapshot<'_, PrimaryKey, Row>
+    where
+        Row: Clone,
+    {
+        self.versions.snapshot()
+    }
+
+    /// Removes all rows from the table. Writes that are in progress are
+    /// waited for and new writes don't start until rows are removed. Versions
+    /// of the removed rows are kept only if some snapshot needs them, and
+    /// the version store is cleared otherwise. Row locks are removed with the
+    /// rows, so operations that locked rows before, but didn't start their
+    /// writes, fail with [`WorkTableError::NotFound`] instead of changing the
+    /// removed rows.
+    pub fn truncate(&self)
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived:
+            Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        let pause = self.versions.pause_writes();
+        self.truncate_paused(&pause)
+    }
+
+    /// Removes all rows from the table like [`WorkTable::truncate`] while
+    /// its writes are paused by the caller's `_pause`, so truncation can be
+    /// logged in the same pause.
+    pub fn truncate_paused(&self, _pause: &WritesPause<'_>)
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived:
+            Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        self.versions.record_truncate(|| {
+            self.pk_map
+                .iter()
+                .filter_map(|(pk, link)| self.data.select(*link).ok().map(|row| (pk.clone(), row)))
+                .collect()
+        });
+        let pks = self
+            .pk_map
+            .iter()
+            .map(|(pk, _)| pk.clone())
+            .collect::<Vec<_>>();
+        for pk in pks {
+            self.pk_map.remove(&pk);
+        }
+        self.indexes.clear();
+        self.data.clear();
+        self.lock_map.clear();
+    }
+
+    /// Changes primary key of the row at `link` from `old` to `new`. Row must
+    /// be locked by the caller and `new_row` must be the row with the new
+    /// primary key. Row is rewritten in place if its size is unchanged,
+    /// otherwise it's moved to the new [`Link`] and secondary indexes are
+    /// updated. On error the table is left unchanged. Returns `true` if row
+    /// was moved, so its old `link` must be deleted after it's unlocked.
+    pub fn rekey_row<const ROW_SIZE_HINT: usize>(
+        &self,
+        old: &PrimaryKey,
+        new: PrimaryKey,
+        row: Row,
+        new_row: Row,
+        link: Link,
+    ) -> Result<bool, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        self.pk_map
+            .insert(new.clone(), link)
+            .map_err(|_| WorkTableError::AlreadyExists)?;
+        if unsafe { self.data.update::<ROW_SIZE_HINT>(new_row.clone(), link) }.is_ok() {
+            self.pk_map.remove(old);
+            return Ok(false);
+        }
+
+        let new_link = match self.data.insert::<ROW_SIZE_HINT>(new_row.clone()) {
+            Ok(new_link) => new_link,
+            Err(e) => {
+                self.pk_map.remove(&new);
+                return Err(WorkTableError::PagesError(e));
+            }
+        };
+        if let Err(e) = self.indexes.delete_row(row.clone(), link) {
+            self.pk_map.remove(&new);
+            self.data
+                .delete(new_link)
+                .map_err(WorkTableError::PagesError)?;
+            return Err(e);
+        }
+        if let Err(e) = self.indexes.save_row(new_row.clone(), new_link) {
+            self.pk_map.remove(&new);
+            self.data
+                .delete(new_link)
+                .map_err(WorkTableError::PagesError)?;
+            self.indexes.save_row(row, link)?;
+            return Err(e);
+        }
+        self.pk_map.remove(&new);
+        // Key is free only while it's moved, so other insert can take it.
+        if self.pk_map.insert(new, new_link).is_err() {
+            self.data
+                .delete(new_link)
+                .map_err(WorkTableError::PagesError)?;
+            self.indexes.delete_row(new_row, new_link)?;
+            self.indexes.save_row(row, link)?;
+            return Err(WorkTableError::AlreadyExists);
+        }
+        self.pk_map.remove(old);
+
+        Ok(true)
+    }
+
+    /// Removes rows stored on the `lost` data pages from the primary index
+    /// and rebuilds secondary indexes from the remaining rows. Used when the
+    /// table is loaded with corrupted pages skipped, so indexes point only to
+    /// the loaded rows.
+    pub fn rebuild_indexes(&mut self, lost: &[PageId]) -> Result<(), WorkTableError>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived:
+            Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+        SecondaryIndexes: Default + TableSecondaryIndex<Row>,
+    {
+        let lost_keys = self
+            .pk_map
+            .iter()
+            .filter(|(_, link)| lost.contains(&link.page_id))
+            .map(|(pk, _)| pk.clone())
+            .collect::<Vec<_>>();
+        for pk in lost_keys {
+            self.pk_map.remove(&pk);
+        }
+
+        let indexes = SecondaryIndexes::default();
+        for (_, link) in self.pk_map.iter() {
+            let row = self
+                .data
+                .select(*link)
+                .map_err(WorkTableError::PagesError)?;
+            indexes.save_row(row, *link)?;
+        }
+        self.indexes = indexes;
+
+        Ok(())
+    }
+
     /// Selects `Row` from table identified with provided primary key. Returns `None` if no value presented.
     #[cfg_attr(
         feature = "perf_measurements",
@@ -122,6 +358,12 @@ where
         SecondaryIndexes: TableSecondaryIndex<Row>,
     {
         let pk = row.get_primary_key().clone();
+        let versions = self.versions.begin_write();
+        let mut wal = self.wal.as_ref().map(Wal::begin);
+        if let Some(wal) = wal.as_mut() {
+            wal.push(WalOperation::Insert, &row)?;
+        }
+        versions.record_with(|| self.pk_map.peek(&pk).is_none().then(|| (pk.clone(), None)));
         let link = self
             .data
             .insert::<ROW_SIZE_HINT>(row.clone())
@@ -130,9 +372,241 @@ where
             .insert(pk.clone(), link)
             .map_err(|_| WorkTableError::AlreadyExists)?;
         self.indexes.save_row(row, link)?;
+        if let Some(wal) = wal {
+            wal.commit()?;
+        }
 
         Ok(pk)
     }
+
+    /// Inserts many rows at once. Either all rows are inserted or none of
+    /// them, so on error the table is left unchanged. Rows are written to the
+    /// pages sequentially and primary keys are inserted into the primary index
+    /// in sorted order, which is much faster than inserting rows one by one.
+    #[cfg_attr(
+        feature = "perf_measurements",
+        performance_measurement(prefix_name = "WorkTable")
+    )]
+    pub fn insert_many<const ROW_SIZE_HINT: usize>(
+        &self,
+        rows: Vec<Row>,
+    ) -> Result<Vec<PrimaryKey>, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        PrimaryKey: Clone,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        self.try_insert_many::<ROW_SIZE_HINT>(rows)
+            .map_err(|(e, _)| e)
+    }
+
+    /// Inserts many rows at once like [`WorkTable::insert_many`]. On error
+    /// rows are given back in the order they were passed, so caller can
+    /// insert them other way without cloning them before.
+    pub fn try_insert_many<const ROW_SIZE_HINT: usize>(
+        &self,
+        rows: Vec<Row>,
+    ) -> Result<Vec<PrimaryKey>, (WorkTableError, Vec<Row>)>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        PrimaryKey: Clone,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        let pks = rows
+            .iter()
+            .map(|row| row.get_primary_key())
+            .collect::<Vec<_>>();
+        let mut order = (0..pks.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| pks[*a].cmp(&pks[*b]));
+        if order.windows(2).any(|w| pks[w[0]] == pks[w[1]])
+            || pks.iter().any(|pk| self.pk_map.peek(pk).is_some())
+        {
+            return Err((WorkTableError::AlreadyExists, rows));
+        }
+
+        let versions = self.versions.begin_write();
+        let mut wal = self.wal.as_ref().map(Wal::begin);
+        if let Some(wal) = wal.as_mut() {
+            for row in rows.iter() {
+                if let Err(e) = wal.push(WalOperation::Insert, row) {
+                    return Err((e, rows));
+                }
+            }
+        }
+        for pk in pks.iter() {
+            versions.record_with(|| Some((pk.clone(), None)));
+        }
+        let mut error = None;
+        let inserted = self.data.insert_many::<ROW_SIZE_HINT>(rows);
+        let mut rows = Vec::with_capacity(inserted.len());
+        let mut links = Vec::with_capacity(inserted.len());
+        for (row, link) in inserted {
+            match link {
+                Ok(link) => links.push(link),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+            rows.push(row);
+        }
+        if let Some(e) = error {
+            let e = self.remove_inserted(links, WorkTableError::PagesError(e));
+            return Err((e, rows));
+        }
+        let rows = rows.into_iter().zip(links).collect::<Vec<_>>();
+
+        for (inserted, i) in order.iter().enumerate() {
+            if self.pk_map.insert(pks[*i].clone(), rows[*i].1).is_err() {
+                for i in order.iter().take(inserted) {
+                    self.pk_map.remove(&pks[*i]);
+                }
+                let (rows, links) = rows.into_iter().unzip();
+                let e = self.remove_inserted(links, WorkTableError::AlreadyExists);
+                return Err((e, rows));
+            }
+        }
+
+        if let Err(e) = self.indexes.save_rows(&rows) {
+            for pk in pks.iter() {
+                self.pk_map.remove(pk);
+            }
+            let (rows, links) = rows.into_iter().unzip();
+            return Err((self.remove_inserted(links, e), rows));
+        }
+        if let Some(wal) = wal {
+            if let Err(e) = wal.commit() {
+                return Err((e, rows.into_iter().map(|(row, _)| row).collect()));
+            }
+        }
+
+        Ok(pks)
+    }
+
+    /// Removes rows at `links` that were written by failed
+    /// [`WorkTable::try_insert_many`]. Returns `error` that failed it, or the
+    /// error of the failed removal.
+    fn remove_inserted(&self, links: Vec<Link>, error: WorkTableError) -> WorkTableError {
+        let mut error = error;
+        for link in links {
+            if let Err(e) = self.data.delete(link) {
+                error = WorkTableError::PagesError(e);
+            }
+        }
+        error
+    }
+
+    /// Inserts many rows at once like [`WorkTable::insert_many`], but each
+    /// row is inserted independently, so rows that can't be inserted don't
+    /// affect other rows. Returns result for each row in the same order as
+    /// rows were passed.
+    #[cfg_attr(
+        feature = "perf_measurements",
+        performance_measurement(prefix_name = "WorkTable")
+    )]
+    pub fn bulk_load<const ROW_SIZE_HINT: usize>(
+        &self,
+        rows: Vec<Row>,
+    ) -> Vec<Result<PrimaryKey, WorkTableError>>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        PrimaryKey: Clone,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        let versions = self.versions.begin_write();
+        let mut wal = self.wal.as_ref().map(Wal::begin);
+        let mut entries = self
+            .data
+            .insert_many::<ROW_SIZE_HINT>(rows)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (row, link))| (i, row.get_primary_key(), row, link))
+            .collect::<Vec<_>>();
+        entries.sort_by(|(_, a, _, _), (_, b, _, _)| a.cmp(b));
+
+        let mut res = Vec::with_capacity(entries.len());
+        let mut saved_pks = Vec::with_capacity(entries.len());
+        let mut saved_rows = Vec::with_capacity(entries.len());
+        for (i, pk, row, link) in entries {
+            let link = match link {
+                Ok(link) => link,
+                Err(e) => {
+                    res.push((i, Err(WorkTableError::PagesError(e))));
+                    continue;
+                }
+            };
+            versions.record_with(|| self.pk_map.peek(&pk).is_none().then(|| (pk.clone(), None)));
+            if self.pk_map.insert(pk.clone(), link).is_err() {
+                let _ = self.data.delete(link);
+                res.push((i, Err(WorkTableError::AlreadyExists)));
+                continue;
+            }
+            saved_pks.push((i, pk));
+            saved_rows.push((row, link));
+        }
+
+        // Secondary indexes are built in bulk. If some row can't be saved,
+        // rows are saved one by one to find which of them fail.
+        let indexes_saved = self.indexes.save_rows(&saved_rows).is_ok();
+        for ((i, pk), (row, link)) in saved_pks.into_iter().zip(saved_rows) {
+            let record = wal
+                .as_ref()
+                .map(|_| rkyv::to_bytes::<rkyv::rancor::Error>(&row));
+            if !indexes_saved {
+                if let Err(e) = self.indexes.save_row(row, link) {
+                    self.pk_map.remove(&pk);
+                    let _ = self.data.delete(link);
+                    res.push((i, Err(e)));
+                    continue;
+                }
+            }
+            if let (Some(wal), Some(Ok(bytes))) = (wal.as_mut(), record) {
+                wal.push_bytes(WalOperation::Insert, bytes.as_slice());
+            }
+            res.push((i, Ok(pk)));
+        }
+        res.sort_by_key(|(i, _)| *i);
+
+        let res = res.into_iter().map(|(_, res)| res);
+        // Rows are inserted already, so if they can't be logged, it's
+        // reported for each of them.
+        match wal.map(|wal| wal.commit()) {
+            Some(Err(WorkTableError::WalError(e))) => res
+                .map(|res| {
+                    res.and_then(|_| {
+                        Err(WorkTableError::WalError(std::io::Error::new(
+                            e.kind(),
+                            e.to_string(),
+                        )))
+                    })
+                })
+                .collect(),
+            _ => res.collect(),
+        }
+    }
 }
 
 #[derive(Debug, Display, Error, From)]
@@ -140,7 +614,11 @@ pub enum WorkTableError {
     NotFound,
     AlreadyExists,
     SerializeError,
+    Overflow,
+    LockTimeout,
+    Deadlock,
     PagesError(in_memory::PagesExecutionError),
+    WalError(std::io::Error),
 }
 
 #[cfg(test)]
@@ -0,0 +1,389 @@
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+
+/// Saved versions of the row. Each `(ts, row)` entry is the row's value before
+/// the change at `ts`. `None` means row didn't exist.
+type RowVersions<Row> = Vec<(u64, Option<Row>)>;
+
+/// Old row versions that are needed by live [`Snapshot`]s.
+///
+/// Writers change rows inside of [`VersionStore::begin_write`] and save row's
+/// version before changing it. Version is saved only if some snapshot is live
+/// and it doesn't see the row's previous change yet. When the last snapshot
+/// that needs version is dropped, version is removed.
+pub struct VersionStore<PrimaryKey, Row> {
+    /// Logical time. It's increased on each saved version.
+    clock: AtomicU64,
+
+    /// Timestamps of the live snapshots with count of snapshots per timestamp.
+    snapshots: Mutex<BTreeMap<u64, usize>>,
+
+    /// Count of the live snapshots.
+    active: AtomicUsize,
+
+    /// Writes in progress and pause of the writes.
+    gate: Mutex<WritesGate>,
+
+    /// Notified when the last write in progress finishes or when writes are
+    /// resumed.
+    gate_changed: Condvar,
+
+    /// Count of the writes that were started.
+    writes: AtomicU64,
+
+    /// Saved versions of the rows.
+    history: Mutex<BTreeMap<PrimaryKey, RowVersions<Row>>>,
+}
+
+#[derive(Debug, Default)]
+struct WritesGate {
+    /// Count of the writes that are in progress.
+    writers: usize,
+
+    /// Set while new snapshot is created or writes are paused. New writes
+    /// don't start until it's unset, so only writes that are in progress are
+    /// waited for.
+    paused: bool,
+}
+
+impl<PrimaryKey, Row> Default for VersionStore<PrimaryKey, Row> {
+    fn default() -> Self {
+        Self {
+            clock: AtomicU64::new(0),
+            snapshots: Mutex::new(BTreeMap::new()),
+            active: AtomicUsize::new(0),
+            gate: Mutex::new(WritesGate::default()),
+            gate_changed: Condvar::new(),
+            writes: AtomicU64::new(0),
+            history: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl<PrimaryKey, Row> Debug for VersionStore<PrimaryKey, Row> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VersionStore")
+            .field("clock", &self.clock)
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+impl<PrimaryKey, Row> VersionStore<PrimaryKey, Row>
+where
+    PrimaryKey: Clone + Ord,
+    Row: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts write. Snapshots are not created until returned [`VersionWrite`]
+    /// is dropped, so snapshot never sees half-done write. If writes are
+    /// paused, thread is blocked until they are resumed.
+    pub fn begin_write(&self) -> VersionWrite<'_, PrimaryKey, Row> {
+        let mut gate = self
+            .gate_changed
+            .wait_while(self.gate.lock().unwrap(), |gate| gate.paused)
+            .unwrap();
+        gate.writers += 1;
+        drop(gate);
+        self.writes.fetch_add(1, Ordering::Relaxed);
+
+        VersionWrite { versions: self }
+    }
+
+    /// Waits for the writes that are in progress and doesn't let new writes
+    /// start until returned [`WritesPause`] is dropped. Thread is blocked
+    /// while waiting.
+    pub fn pause_writes(&self) -> WritesPause<'_> {
+        let mut gate = self
+            .gate_changed
+            .wait_while(self.gate.lock().unwrap(), |gate| gate.paused)
+            .unwrap();
+        gate.paused = true;
+        let _gate = self
+            .gate_changed
+            .wait_while(gate, |gate| gate.writers != 0)
+            .unwrap();
+
+        WritesPause {
+            gate: &self.gate,
+            gate_changed: &self.gate_changed,
+        }
+    }
+
+    /// Creates read view of the current state.
+    pub fn snapshot(&self) -> Snapshot<'_, PrimaryKey, Row> {
+        let pause = self.pause_writes();
+        let ts = self.clock.load(Ordering::SeqCst);
+        *self.snapshots.lock().unwrap().entry(ts).or_default() += 1;
+        self.active.fetch_add(1, Ordering::SeqCst);
+        drop(pause);
+
+        Snapshot { versions: self, ts }
+    }
+
+    /// Returns count of the writes that were started.
+    pub fn writes_count(&self) -> u64 {
+        self.writes.load(Ordering::Relaxed)
+    }
+
+    /// Returns count of the saved row versions.
+    pub fn versions_count(&self) -> usize {
+        self.history.lock().unwrap().values().map(Vec::len).sum()
+    }
+
+    /// Saves versions of all table's `rows` before the table is truncated.
+    /// Must be called while writes are paused. If no snapshot is live, no
+    /// versions are needed, so all saved versions are removed.
+    pub fn record_truncate<F>(&self, rows: F)
+    where
+        F: FnOnce() -> Vec<(PrimaryKey, Row)>,
+    {
+        let snapshots = self.snapshots.lock().unwrap();
+        let mut history = self.history.lock().unwrap();
+        let Some(last_snapshot) = snapshots.keys().last().copied() else {
+            history.clear();
+            return;
+        };
+        let ts = self.clock.fetch_add(1, Ordering::SeqCst) + 1;
+        for (pk, row) in rows() {
+            let versions = history.entry(pk).or_default();
+            if versions.last().is_some_and(|(t, _)| *t > last_snapshot) {
+                continue;
+            }
+            versions.push((ts, Some(row)));
+        }
+    }
+
+    fn release(&self, ts: u64) {
+        let mut snapshots = self.snapshots.lock().unwrap();
+        if let Some(count) = snapshots.get_mut(&ts) {
+            *count -= 1;
+            if *count == 0 {
+                snapshots.remove(&ts);
+            }
+        }
+        self.active.fetch_sub(1, Ordering::SeqCst);
+
+        let mut history = self.history.lock().unwrap();
+        if let Some(min_ts) = snapshots.keys().next() {
+            // Versions that were saved before the oldest snapshot was created
+            // are not needed by any snapshot.
+            for versions in history.values_mut() {
+                versions.retain(|(t, _)| t > min_ts);
+            }
+            history.retain(|_, versions| !versions.is_empty());
+        } else {
+            history.clear();
+        }
+    }
+}
+
+/// Write started by [`VersionStore::begin_write`].
+pub struct VersionWrite<'a, PrimaryKey, Row>
+where
+    PrimaryKey: Clone + Ord,
+    Row: Clone,
+{
+    versions: &'a VersionStore<PrimaryKey, Row>,
+}
+
+impl<PrimaryKey, Row> VersionWrite<'_, PrimaryKey, Row>
+where
+    PrimaryKey: Clone + Ord,
+    Row: Clone,
+{
+    /// Saves row's version before it's changed. `version` returns row's
+    /// primary key and current value and is called only if some snapshot can
+    /// need this version.
+    pub fn record_with<F>(&self, version: F)
+    where
+        F: FnOnce() -> Option<(PrimaryKey, Option<Row>)>,
+    {
+        if self.versions.active.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        let Some((pk, row)) = version() else {
+            return;
+        };
+        let last_snapshot = self
+            .versions
+            .snapshots
+            .lock()
+            .unwrap()
+            .keys()
+            .last()
+            .copied();
+        let Some(last_snapshot) = last_snapshot else {
+            return;
+        };
+
+        let mut history = self.versions.history.lock().unwrap();
+        let versions = history.entry(pk).or_default();
+        // All snapshots already see version saved after the last of them was
+        // created.
+        if versions.last().is_some_and(|(t, _)| *t > last_snapshot) {
+            return;
+        }
+        let ts = self.versions.clock.fetch_add(1, Ordering::SeqCst) + 1;
+        versions.push((ts, row));
+    }
+}
+
+impl<PrimaryKey, Row> Drop for VersionWrite<'_, PrimaryKey, Row>
+where
+    PrimaryKey: Clone + Ord,
+    Row: Clone,
+{
+    fn drop(&mut self) {
+        let mut gate = self.versions.gate.lock().unwrap();
+        gate.writers -= 1;
+        if gate.writers == 0 {
+            self.versions.gate_changed.notify_all();
+        }
+    }
+}
+
+/// Pause of the writes started by [`VersionStore::pause_writes`].
+pub struct WritesPause<'a> {
+    gate: &'a Mutex<WritesGate>,
+    gate_changed: &'a Condvar,
+}
+
+impl Drop for WritesPause<'_> {
+    fn drop(&mut self) {
+        self.gate.lock().unwrap().paused = false;
+        self.gate_changed.notify_all();
+    }
+}
+
+/// Read view of the table at some logical time. Rows changed after snapshot
+/// was created are seen with their values at this time.
+pub struct Snapshot<'a, PrimaryKey, Row>
+where
+    PrimaryKey: Clone + Ord,
+    Row: Clone,
+{
+    versions: &'a VersionStore<PrimaryKey, Row>,
+    ts: u64,
+}
+
+impl<PrimaryKey, Row> Snapshot<'_, PrimaryKey, Row>
+where
+    PrimaryKey: Clone + Ord,
+    Row: Clone,
+{
+    /// Returns logical time of the snapshot.
+    pub fn ts(&self) -> u64 {
+        self.ts
+    }
+
+    /// Returns row's value seen by snapshot. `live` must be read from the
+    /// table before this call.
+    pub fn resolve(&self, pk: &PrimaryKey, live: Option<Row>) -> Option<Row> {
+        let history = self.versions.history.lock().unwrap();
+        match history
+            .get(pk)
+            .and_then(|versions| versions.iter().find(|(t, _)| *t > self.ts))
+        {
+            Some((_, row)) => row.clone(),
+            None => live,
+        }
+    }
+
+    /// Returns primary keys of the rows that were changed after snapshot was
+    /// created.
+    pub fn changed_keys(&self) -> Vec<PrimaryKey> {
+        let history = self.versions.history.lock().unwrap();
+        history
+            .iter()
+            .filter(|(_, versions)| versions.iter().any(|(t, _)| *t > self.ts))
+            .map(|(pk, _)| pk.clone())
+            .collect()
+    }
+}
+
+impl<PrimaryKey, Row> Drop for Snapshot<'_, PrimaryKey, Row>
+where
+    PrimaryKey: Clone + Ord,
+    Row: Clone,
+{
+    fn drop(&mut self) {
+        self.versions.release(self.ts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    use crate::table::snapshot::VersionStore;
+
+    #[test]
+    fn snapshot_sees_old_versions() {
+        let versions = VersionStore::<u64, &str>::new();
+        let write = versions.begin_write();
+        write.record_with(|| Some((1, None)));
+        drop(write);
+        assert_eq!(versions.versions_count(), 0);
+
+        let snapshot = versions.snapshot();
+        let write = versions.begin_write();
+        write.record_with(|| Some((1, Some("a"))));
+        write.record_with(|| Some((1, Some("b"))));
+        write.record_with(|| Some((2, None)));
+        drop(write);
+        assert_eq!(versions.versions_count(), 2);
+
+        let later = versions.snapshot();
+        let write = versions.begin_write();
+        write.record_with(|| Some((1, Some("c"))));
+        drop(write);
+
+        assert_eq!(snapshot.resolve(&1, Some("d")), Some("a"));
+        assert_eq!(snapshot.resolve(&2, Some("e")), None);
+        assert_eq!(snapshot.resolve(&3, Some("f")), Some("f"));
+        assert_eq!(snapshot.changed_keys(), vec![1, 2]);
+        assert_eq!(later.resolve(&1, Some("d")), Some("c"));
+        assert_eq!(later.resolve(&2, Some("e")), Some("e"));
+
+        drop(snapshot);
+        assert_eq!(versions.versions_count(), 1);
+        drop(later);
+        assert_eq!(versions.versions_count(), 0);
+        assert_eq!(versions.writes_count(), 3);
+    }
+
+    #[test]
+    fn pause_waits_for_writes() {
+        let versions = VersionStore::<u64, &str>::new();
+        let paused = AtomicBool::new(false);
+        let resumed = AtomicBool::new(false);
+        std::thread::scope(|s| {
+            let write = versions.begin_write();
+            s.spawn(|| {
+                let pause = versions.pause_writes();
+                paused.store(true, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(20));
+                resumed.store(true, Ordering::SeqCst);
+                drop(pause);
+            });
+            std::thread::sleep(Duration::from_millis(20));
+            assert!(!paused.load(Ordering::SeqCst));
+            drop(write);
+
+            // New write waits until writes are resumed.
+            while !paused.load(Ordering::SeqCst) {
+                std::thread::yield_now();
+            }
+            drop(versions.begin_write());
+            assert!(resumed.load(Ordering::SeqCst));
+        });
+        assert_eq!(versions.writes_count(), 2);
+    }
+}
@@ -0,0 +1,225 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: TestBackup,
+    persist: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        value: u64,
+    },
+    config: {
+        wal_sync: always,
+    }
+);
+
+worktable! (
+    name: TestBackupOther,
+    persist: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        name: String,
+    },
+    indexes: {
+        name_idx: name unique,
+    },
+);
+
+fn get_dir(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("worktable_{}_{}", name, uuid::Uuid::new_v4()))
+        .to_string_lossy()
+        .to_string()
+}
+
+fn get_manager() -> Arc<DatabaseManager> {
+    let dir = get_dir("backup_db");
+    Arc::new(DatabaseManager {
+        config_path: dir.clone(),
+        database_files_dir: dir,
+    })
+}
+
+fn insert_rows(table: &TestBackupWorkTable, other: &TestBackupOtherWorkTable, count: u64) {
+    for value in 0..count {
+        let row = TestBackupRow {
+            id: table.get_next_pk().into(),
+            value,
+        };
+        table.insert(row).unwrap();
+        let row = TestBackupOtherRow {
+            id: other.get_next_pk().into(),
+            name: uuid::Uuid::new_v4().to_string(),
+        };
+        other.insert(row).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn backup_restore() {
+    let manager = get_manager();
+    let table = Arc::new(TestBackupWorkTable::load_from_file(manager.clone()).unwrap());
+    let other = Arc::new(TestBackupOtherWorkTable::load_from_file(manager.clone()).unwrap());
+    manager.register(&table);
+    manager.register(&other);
+    insert_rows(&table, &other, 20);
+    table.persist().unwrap();
+
+    let backup_dir = get_dir("backup");
+    let manifest = manager.backup(&backup_dir).unwrap();
+    assert_eq!(
+        manifest
+            .tables
+            .iter()
+            .map(|e| (e.table.as_str(), e.file.as_str(), e.writes))
+            .collect::<Vec<_>>(),
+        vec![
+            ("TestBackup", "test_backup.wt", 20),
+            ("TestBackupOther", "test_backup_other.wt", 20)
+        ]
+    );
+    assert_eq!(BackupManifest::read(&backup_dir).unwrap(), manifest);
+    assert!(manager.backup(&backup_dir).is_err());
+
+    let rows = table.select_all().execute().unwrap();
+    let other_rows = other.select_all().execute().unwrap();
+    // Changes made after the backup are logged, but the log is not replayed
+    // on top of the restored file.
+    insert_rows(&table, &other, 5);
+    assert!(manager.restore(&backup_dir).is_err());
+    drop(table);
+    drop(other);
+
+    let restored = manager.restore(&backup_dir).unwrap();
+    assert_eq!(restored.manifest, manifest);
+    let table = restored.load::<TestBackupWorkTable>().unwrap();
+    let other = restored.load::<TestBackupOtherWorkTable>().unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), rows);
+    assert_eq!(other.select_all().execute().unwrap(), other_rows);
+    assert_eq!(table.get_next_pk(), 20.into());
+    // Loaded tables are registered, so they can't be restored again while
+    // they are alive.
+    assert!(manager.restore(&backup_dir).is_err());
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+    std::fs::remove_dir_all(backup_dir).unwrap();
+}
+
+#[test]
+fn backup_while_writes_continue() {
+    let manager = get_manager();
+    let table = Arc::new(TestBackupWorkTable::new(manager.clone()));
+    let other = Arc::new(TestBackupOtherWorkTable::new(manager.clone()));
+    manager.register(&table);
+    manager.register(&other);
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let writer = {
+        let table = table.clone();
+        let other = other.clone();
+        let stop = stop.clone();
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                insert_rows(&table, &other, 1);
+            }
+        })
+    };
+    while table.writes_count() < 100 {
+        std::thread::yield_now()
+    }
+    let backup_dir = get_dir("backup");
+    let manifest = manager.backup(&backup_dir).unwrap();
+    stop.store(true, Ordering::Relaxed);
+    writer.join().unwrap();
+    drop(table);
+    drop(other);
+
+    let restore_manager = get_manager();
+    let restored = restore_manager.restore(&backup_dir).unwrap();
+    let table = restored.load::<TestBackupWorkTable>().unwrap();
+    let other = restored.load::<TestBackupOtherWorkTable>().unwrap();
+    let count = table.select_all().execute().unwrap().len() as u64;
+    let other_count = other.select_all().execute().unwrap().len() as u64;
+    // Each table has all rows inserted before the backup and only them.
+    assert_eq!(count, manifest.tables[0].writes);
+    assert_eq!(other_count, manifest.tables[1].writes);
+    // Tables are captured at one moment, so the other table has at most the
+    // row of the pair that was being inserted.
+    assert!(count == other_count || count == other_count + 1);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).ok();
+    std::fs::remove_dir_all(restore_manager.database_files_dir.as_str()).unwrap();
+    std::fs::remove_dir_all(backup_dir).unwrap();
+}
+
+#[test]
+fn restore_corrupted_backup() {
+    let manager = get_manager();
+    let table = Arc::new(TestBackupWorkTable::new(manager.clone()));
+    let other = Arc::new(TestBackupOtherWorkTable::new(manager.clone()));
+    manager.register(&table);
+    manager.register(&other);
+    insert_rows(&table, &other, 10);
+    let backup_dir = get_dir("backup");
+    manager.backup(&backup_dir).unwrap();
+
+    let restore_manager = get_manager();
+    let path = format!("{}/test_backup_other.wt", backup_dir);
+    let mut bytes = std::fs::read(&path).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xFF;
+    std::fs::write(&path, bytes).unwrap();
+    let error = restore_manager.restore(&backup_dir).unwrap_err();
+    assert!(error.to_string().contains("test_backup_other.wt"));
+    // Nothing is restored if any file is invalid.
+    assert!(!std::path::Path::new(
+        format!("{}/test_backup.wt", restore_manager.database_files_dir).as_str()
+    )
+    .exists());
+
+    std::fs::remove_file(format!("{}/{}", backup_dir, BACKUP_MANIFEST)).unwrap();
+    assert!(restore_manager.restore(&backup_dir).is_err());
+
+    std::fs::remove_dir_all(backup_dir).unwrap();
+}
+
+#[tokio::test]
+async fn backup_keeps_empty_links() {
+    let manager = get_manager();
+    let table = Arc::new(TestBackupWorkTable::new(manager.clone()));
+    manager.register(&table);
+    let other = Arc::new(TestBackupOtherWorkTable::new(manager.clone()));
+    insert_rows(&table, &other, 3);
+    let pk = TestBackupPrimaryKey::from(1);
+    let link = TableIndex::peek(&table.0.pk_map, &pk).unwrap();
+    table.delete(pk).await.unwrap();
+    let backup_dir = get_dir("backup");
+    manager.backup(&backup_dir).unwrap();
+
+    // Backup doesn't change the table, so deleted row's link is reused.
+    let row = TestBackupRow {
+        id: table.get_next_pk().into(),
+        value: 3,
+    };
+    let pk = table.insert(row).unwrap();
+    assert_eq!(TableIndex::peek(&table.0.pk_map, &pk).unwrap(), link);
+    drop(table);
+
+    let restore_manager = get_manager();
+    let restored = restore_manager.restore(&backup_dir).unwrap();
+    assert!(restored.load::<TestBackupOtherWorkTable>().is_err());
+    let table = restored.load::<TestBackupWorkTable>().unwrap();
+    let row = TestBackupRow {
+        id: table.get_next_pk().into(),
+        value: 3,
+    };
+    let pk = table.insert(row).unwrap();
+    assert_eq!(TableIndex::peek(&table.0.pk_map, &pk).unwrap(), link);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).ok();
+    std::fs::remove_dir_all(restore_manager.database_files_dir.as_str()).unwrap();
+    std::fs::remove_dir_all(backup_dir).unwrap();
+}
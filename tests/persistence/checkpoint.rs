@@ -0,0 +1,163 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: TestCheckpoint,
+    persist: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        value: u64,
+    },
+);
+
+fn get_manager() -> Arc<DatabaseManager> {
+    let dir = std::env::temp_dir()
+        .join(format!("worktable_checkpoint_{}", uuid::Uuid::new_v4()))
+        .to_string_lossy()
+        .to_string();
+
+    Arc::new(DatabaseManager::new(dir.clone(), dir))
+}
+
+fn file_exists(manager: &DatabaseManager) -> bool {
+    std::path::Path::new(format!("{}/test_checkpoint.wt", manager.database_files_dir).as_str())
+        .exists()
+}
+
+fn insert_rows(table: &TestCheckpointWorkTable, count: u64) {
+    for value in 0..count {
+        let row = TestCheckpointRow {
+            id: table.get_next_pk().into(),
+            value,
+        };
+        table.insert(row).unwrap();
+    }
+}
+
+async fn wait_for_file(manager: &DatabaseManager) {
+    for _ in 0..500 {
+        if file_exists(manager) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("table was not persisted")
+}
+
+#[tokio::test]
+async fn table_is_persisted_after_writes() {
+    let manager = get_manager();
+    let table = Arc::new(TestCheckpointWorkTable::load_from_file(manager.clone()).unwrap());
+    manager.register(&table);
+    let scheduler = manager.start_checkpoints(CheckpointConfig {
+        interval: None,
+        writes: Some(10),
+        check_interval: Duration::from_millis(10),
+    });
+
+    insert_rows(&table, 5);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!file_exists(&manager));
+
+    insert_rows(&table, 5);
+    wait_for_file(&manager).await;
+    scheduler.stop().await;
+
+    let loaded = TestCheckpointWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(
+        loaded.select_all().execute().unwrap(),
+        table.select_all().execute().unwrap()
+    );
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[tokio::test]
+async fn table_is_persisted_on_interval() {
+    let manager = get_manager();
+    let table = Arc::new(TestCheckpointWorkTable::load_from_file(manager.clone()).unwrap());
+    manager.register(&table);
+    let scheduler = manager.start_checkpoints(CheckpointConfig {
+        interval: Some(Duration::from_millis(50)),
+        writes: None,
+        check_interval: Duration::from_millis(10),
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    // Table without changes is not persisted.
+    assert!(!file_exists(&manager));
+
+    insert_rows(&table, 3);
+    wait_for_file(&manager).await;
+    scheduler.stop().await;
+
+    let loaded = TestCheckpointWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(loaded.select_all().execute().unwrap().len(), 3);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[tokio::test]
+async fn table_registered_after_start_is_persisted() {
+    let manager = get_manager();
+    let scheduler = manager.start_checkpoints(CheckpointConfig {
+        interval: Some(Duration::from_millis(20)),
+        writes: None,
+        check_interval: Duration::from_millis(10),
+    });
+    let table = Arc::new(TestCheckpointWorkTable::load_from_file(manager.clone()).unwrap());
+    insert_rows(&table, 3);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    // Table is persisted only after it's registered in the manager.
+    assert!(!file_exists(&manager));
+
+    manager.register(&table);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    insert_rows(&table, 3);
+    wait_for_file(&manager).await;
+    scheduler.stop().await;
+
+    let loaded = TestCheckpointWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(loaded.select_all().execute().unwrap().len(), 6);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[tokio::test]
+async fn deleted_row_link_is_reused_after_checkpoint() {
+    let manager = get_manager();
+    let table = Arc::new(TestCheckpointWorkTable::load_from_file(manager.clone()).unwrap());
+    manager.register(&table);
+    let scheduler = manager.start_checkpoints(CheckpointConfig {
+        interval: None,
+        writes: Some(4),
+        check_interval: Duration::from_millis(10),
+    });
+
+    insert_rows(&table, 3);
+    let pk = TestCheckpointPrimaryKey::from(1);
+    let link = TableIndex::peek(&table.0.pk_map, &pk).unwrap();
+    table.delete(pk).await.unwrap();
+    wait_for_file(&manager).await;
+    scheduler.stop().await;
+
+    let row = TestCheckpointRow {
+        id: table.get_next_pk().into(),
+        value: 3,
+    };
+    let pk = table.insert(row).unwrap();
+    assert_eq!(TableIndex::peek(&table.0.pk_map, &pk).unwrap(), link);
+
+    let loaded = TestCheckpointWorkTable::load_from_file(manager.clone()).unwrap();
+    let row = TestCheckpointRow {
+        id: loaded.get_next_pk().into(),
+        value: 3,
+    };
+    let pk = loaded.insert(row).unwrap();
+    assert_eq!(TableIndex::peek(&loaded.0.pk_map, &pk).unwrap(), link);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
@@ -0,0 +1,164 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::sync::Arc;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: TestChecksum,
+    persist: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        another: u64,
+        value: u64,
+    },
+    indexes: {
+        another_idx: another unique,
+    },
+);
+
+fn get_manager() -> Arc<DatabaseManager> {
+    let dir = std::env::temp_dir()
+        .join(format!("worktable_checksums_{}", uuid::Uuid::new_v4()))
+        .to_string_lossy()
+        .to_string();
+
+    Arc::new(DatabaseManager::new(dir.clone(), dir))
+}
+
+fn file_path(manager: &DatabaseManager) -> String {
+    format!("{}/test_checksum.wt", manager.database_files_dir)
+}
+
+fn insert_rows(table: &TestChecksumWorkTable, range: std::ops::Range<u64>) -> Vec<TestChecksumRow> {
+    range
+        .map(|i| {
+            let row = TestChecksumRow {
+                id: table.get_next_pk().into(),
+                another: i,
+                value: i * 2,
+            };
+            table.insert(row.clone()).unwrap();
+            row
+        })
+        .collect()
+}
+
+fn parse_space(manager: &DatabaseManager) -> TestChecksumSpace {
+    let mut file = File::open(file_path(manager)).unwrap();
+    TestChecksumSpace::parse_file(&mut file).unwrap()
+}
+
+/// Flips byte in the middle of the file's page.
+fn corrupt_page(manager: &DatabaseManager, page_id: usize) {
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(file_path(manager))
+        .unwrap();
+    let offset = (page_id * PAGE_SIZE + 1000) as u64;
+    let mut byte = [0];
+    file.seek(SeekFrom::Start(offset)).unwrap();
+    file.read_exact(&mut byte).unwrap();
+    file.seek(SeekFrom::Start(offset)).unwrap();
+    file.write_all(&[!byte[0]]).unwrap();
+}
+
+#[test]
+fn corrupted_data_page_is_reported() {
+    let manager = get_manager();
+    let table = TestChecksumWorkTable::load_from_file(manager.clone()).unwrap();
+    let rows = insert_rows(&table, 0..2000);
+    table.persist().unwrap();
+    drop(table);
+
+    let page_id = parse_space(&manager).info.inner.data_intervals[0].0;
+    corrupt_page(&manager, page_id);
+    let error = TestChecksumWorkTable::load_from_file(manager.clone()).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains(format!("page {} of type Data", page_id).as_str()));
+
+    let (table, corrupted) =
+        TestChecksumWorkTable::load_from_file_skipping_corrupted(manager.clone()).unwrap();
+    assert_eq!(
+        corrupted,
+        vec![CorruptedPage {
+            page_id: page_id as u32,
+            page_type: PageType::Data,
+        }]
+    );
+    let loaded = table.select_all().execute().unwrap();
+    assert!(!loaded.is_empty());
+    assert!(loaded.len() < rows.len());
+    for row in &rows {
+        let found = table.select_by_another(row.another);
+        if loaded.contains(row) {
+            assert_eq!(found.as_ref(), Some(row));
+        } else {
+            assert_eq!(found, None);
+            assert_eq!(table.select(row.id.into()), None);
+        }
+    }
+
+    // Table is written as a whole, so corrupted page is replaced.
+    let mut rows = loaded;
+    rows.extend(insert_rows(&table, 2000..2100));
+    table.persist().unwrap();
+    drop(table);
+    let table = TestChecksumWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), rows);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[test]
+fn corrupted_index_page_is_rebuilt() {
+    let manager = get_manager();
+    let table = TestChecksumWorkTable::load_from_file(manager.clone()).unwrap();
+    let rows = insert_rows(&table, 0..2000);
+    table.persist().unwrap();
+    drop(table);
+
+    let page_id = parse_space(&manager)
+        .info
+        .inner
+        .secondary_index_intervals
+        .get("another_idx")
+        .unwrap()[0]
+        .0;
+    corrupt_page(&manager, page_id);
+    assert!(TestChecksumWorkTable::load_from_file(manager.clone()).is_err());
+
+    let (table, corrupted) =
+        TestChecksumWorkTable::load_from_file_skipping_corrupted(manager.clone()).unwrap();
+    assert_eq!(corrupted.len(), 1);
+    assert_eq!(corrupted[0].page_type, PageType::Index);
+    assert_eq!(table.select_all().execute().unwrap(), rows);
+    for row in &rows {
+        assert_eq!(table.select_by_another(row.another).as_ref(), Some(row));
+    }
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[test]
+fn previous_file_is_loaded_if_page_is_corrupted() {
+    let manager = get_manager();
+    let table = TestChecksumWorkTable::load_from_file(manager.clone()).unwrap();
+    let rows = insert_rows(&table, 0..2000);
+    table.persist().unwrap();
+    insert_rows(&table, 2000..2100);
+    table.persist().unwrap();
+    drop(table);
+
+    let page_id = parse_space(&manager).info.inner.data_intervals[0].0;
+    corrupt_page(&manager, page_id);
+    let (table, corrupted) =
+        TestChecksumWorkTable::load_from_file_skipping_corrupted(manager.clone()).unwrap();
+    assert!(corrupted.is_empty());
+    assert_eq!(table.select_all().execute().unwrap(), rows);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
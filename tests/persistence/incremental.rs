@@ -0,0 +1,276 @@
+use std::sync::Arc;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: TestIncremental,
+    persist: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        another: u64,
+        exchange: String,
+        value: u64,
+    },
+    indexes: {
+        another_idx: another unique,
+        exchange_idx: exchange,
+    },
+);
+
+fn get_manager() -> Arc<DatabaseManager> {
+    let dir = std::env::temp_dir()
+        .join(format!("worktable_incremental_{}", uuid::Uuid::new_v4()))
+        .to_string_lossy()
+        .to_string();
+
+    Arc::new(DatabaseManager::new(dir.clone(), dir))
+}
+
+fn file_path(manager: &DatabaseManager) -> String {
+    format!("{}/test_incremental.wt", manager.database_files_dir)
+}
+
+/// Returns ids of the pages that differ in the files' contents. Checksums
+/// footer is not compared.
+fn changed_pages(before: &[u8], after: &[u8]) -> Vec<usize> {
+    let (before, after) = (without_footer(before), without_footer(after));
+    let pages = before.len().max(after.len()).div_ceil(PAGE_SIZE);
+    (0..pages)
+        .filter(|i| {
+            let page = |bytes: &[u8]| {
+                let start = (i * PAGE_SIZE).min(bytes.len());
+                let end = ((i + 1) * PAGE_SIZE).min(bytes.len());
+                bytes[start..end].to_vec()
+            };
+            page(before) != page(after)
+        })
+        .collect()
+}
+
+/// Returns file's content without checksums footer.
+fn without_footer(bytes: &[u8]) -> &[u8] {
+    let count = u32::from_le_bytes(
+        bytes[bytes.len() - 16..bytes.len() - 12]
+            .try_into()
+            .unwrap(),
+    );
+    &bytes[..bytes.len() - 16 - count as usize * 4]
+}
+
+fn insert_rows(table: &TestIncrementalWorkTable, range: std::ops::Range<u64>) {
+    for i in range {
+        let row = TestIncrementalRow {
+            id: table.get_next_pk().into(),
+            another: i,
+            exchange: format!("exchange_{}", i % 10),
+            value: 0,
+        };
+        table.insert(row).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn only_changed_pages_are_written() {
+    let manager = get_manager();
+    let table = TestIncrementalWorkTable::load_from_file(manager.clone()).unwrap();
+    insert_rows(&table, 0..2000);
+    table.persist().unwrap();
+    let before = std::fs::read(file_path(&manager)).unwrap();
+    let metadata = std::fs::metadata(file_path(&manager)).unwrap();
+
+    let mut row = table.select_by_another(1000).unwrap();
+    row.value = 1;
+    table.update(row).await.unwrap();
+    table.persist().unwrap();
+    let after = std::fs::read(file_path(&manager)).unwrap();
+    // Pages are written to the existing file instead of its copy.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let ino = std::fs::metadata(file_path(&manager)).unwrap().ino();
+        assert_eq!(metadata.ino(), ino);
+    }
+    #[cfg(not(unix))]
+    let _ = metadata;
+
+    assert_eq!(before.len(), after.len());
+    let changed = changed_pages(&before, &after);
+    // Space info page and the data page with the row.
+    assert!(changed.len() <= 2);
+    assert!(changed.iter().any(|p| *p != 0));
+
+    let expected = table.select_all().execute().unwrap();
+    drop(table);
+    let table = TestIncrementalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), expected);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[tokio::test]
+async fn table_is_loaded_after_incremental_persists() {
+    let manager = get_manager();
+    let table = TestIncrementalWorkTable::load_from_file(manager.clone()).unwrap();
+    insert_rows(&table, 0..500);
+    table.persist().unwrap();
+    drop(table);
+
+    let table = TestIncrementalWorkTable::load_from_file(manager.clone()).unwrap();
+    let before = std::fs::read(file_path(&manager)).unwrap();
+    table.persist().unwrap();
+    let after = std::fs::read(file_path(&manager)).unwrap();
+    assert!(changed_pages(&before, &after).iter().all(|p| *p == 0));
+
+    insert_rows(&table, 500..3000);
+    for i in (0..500).step_by(3) {
+        let row = table.select_by_another(i).unwrap();
+        table.delete(row.id.into()).await.unwrap();
+    }
+    table.persist().unwrap();
+    let expected = table.select_all().execute().unwrap();
+    drop(table);
+
+    let table = TestIncrementalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), expected);
+    assert_eq!(table.select_by_another(3), None);
+    assert_eq!(table.select_by_another(2999).unwrap().another, 2999);
+    assert_eq!(
+        table
+            .select_by_exchange("exchange_1".to_string())
+            .unwrap()
+            .execute()
+            .len(),
+        expected
+            .iter()
+            .filter(|r| r.exchange == "exchange_1")
+            .count()
+    );
+    insert_rows(&table, 3000..3001);
+    assert!(table.select_by_another(3000).unwrap().id > expected.last().unwrap().id);
+    table.truncate(false).unwrap();
+    table.persist().unwrap();
+    drop(table);
+
+    let table = TestIncrementalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert!(table.select_all().execute().unwrap().is_empty());
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+fn truncate_file(manager: &DatabaseManager) {
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(file_path(manager))
+        .unwrap()
+        .set_len(0)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn previous_file_is_loaded_if_current_is_truncated() {
+    let manager = get_manager();
+    let table = TestIncrementalWorkTable::new(manager.clone());
+    insert_rows(&table, 0..500);
+    table.persist().unwrap();
+    let expected = table.select_all().execute().unwrap();
+    drop(table);
+
+    // Table that isn't loaded from the file writes it as a whole, keeping the
+    // replaced one.
+    let table = TestIncrementalWorkTable::new(manager.clone());
+    insert_rows(&table, 500..600);
+    table.persist().unwrap();
+    drop(table);
+
+    truncate_file(&manager);
+    let table = TestIncrementalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), expected);
+
+    // Loaded generation is written as a whole.
+    insert_rows(&table, 600..700);
+    table.persist().unwrap();
+    let expected = table.select_all().execute().unwrap();
+    drop(table);
+
+    let table = TestIncrementalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), expected);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[tokio::test]
+async fn incremental_persist_removes_previous_file() {
+    let manager = get_manager();
+    let space_file = SpaceFile::new(file_path(&manager));
+    for range in [0..500, 500..600] {
+        let table = TestIncrementalWorkTable::new(manager.clone());
+        insert_rows(&table, range);
+        table.persist().unwrap();
+    }
+    assert!(space_file.previous_path().exists());
+
+    let table = TestIncrementalWorkTable::load_from_file(manager.clone()).unwrap();
+    insert_rows(&table, 600..700);
+    table.persist().unwrap();
+    // Previous file doesn't have the rows persisted before this persist, so
+    // it's not loaded instead of the current one.
+    assert!(!space_file.previous_path().exists());
+    assert!(!space_file.journal_path().exists());
+    let expected = table.select_all().execute().unwrap();
+    drop(table);
+
+    let table = TestIncrementalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), expected);
+    drop(table);
+
+    truncate_file(&manager);
+    assert!(TestIncrementalWorkTable::load_from_file(manager.clone()).is_err());
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[tokio::test]
+async fn corrupted_file_after_incremental_persist_is_not_replaced() {
+    let manager = get_manager();
+    let space_file = SpaceFile::new(file_path(&manager));
+    for range in [0..500, 500..600] {
+        let table = TestIncrementalWorkTable::new(manager.clone());
+        insert_rows(&table, range);
+        table.persist().unwrap();
+    }
+    let table = TestIncrementalWorkTable::load_from_file(manager.clone()).unwrap();
+    insert_rows(&table, 600..700);
+    table.persist().unwrap();
+    let expected = table.select_all().execute().unwrap();
+    drop(table);
+
+    // Flips byte in the middle of the first data page.
+    let mut file = std::fs::File::open(file_path(&manager)).unwrap();
+    let page_id = TestIncrementalSpace::parse_file(&mut file)
+        .unwrap()
+        .info
+        .inner
+        .data_intervals[0]
+        .0;
+    let mut bytes = std::fs::read(file_path(&manager)).unwrap();
+    bytes[page_id * PAGE_SIZE + 1000] ^= 0xFF;
+    std::fs::write(file_path(&manager), bytes).unwrap();
+
+    // There is no previous file to load instead, so only rows of the valid
+    // pages are loaded.
+    let error = TestIncrementalWorkTable::load_from_file(manager.clone()).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains(format!("page {} of type Data", page_id).as_str()));
+    assert!(!space_file.previous_path().exists());
+    let (table, corrupted) =
+        TestIncrementalWorkTable::load_from_file_skipping_corrupted(manager.clone()).unwrap();
+    assert_eq!(corrupted.len(), 1);
+    let loaded = table.select_all().execute().unwrap();
+    assert!(!loaded.is_empty());
+    assert!(loaded.len() < expected.len());
+    assert!(loaded.iter().all(|row| expected.contains(row)));
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
@@ -0,0 +1,57 @@
+use std::fs::File;
+
+use worktable::prelude::*;
+
+use crate::persistence::{
+    get_test_wt, TestPersistWorkTable, TESTPERSIST_INNER_SIZE, TESTPERSIST_PAGE_SIZE,
+};
+
+#[test]
+fn test_inspect_file() {
+    let mut file = File::open("tests/data/expected/test_persist.wt").unwrap();
+    let report = TestPersistWorkTable::inspect(&mut file).unwrap();
+
+    assert!(report.problems.is_empty(), "{:?}", report.problems);
+    assert_eq!(report.page_size, TESTPERSIST_PAGE_SIZE);
+    assert_eq!(report.schema.as_ref().unwrap().version, 0);
+    assert_eq!(report.pages.len(), 4);
+    assert_eq!(report.pages[0].page_type, PageType::SpaceInfo);
+    assert_eq!(report.pages[3].page_type, PageType::Data);
+    assert!(report.pages.iter().all(|p| p.checksum_valid == Some(true)));
+
+    let sections = report.sections.as_ref().unwrap();
+    assert_eq!(sections.primary_key, vec![(1, 1)]);
+    assert_eq!(sections.data, vec![(3, 3)]);
+    assert!(report.free_pages().is_empty());
+
+    let primary_key = &report.indexes[0];
+    assert_eq!(primary_key.name, "primary key");
+    assert_eq!(primary_key.entries.len(), 99);
+    let another_idx = report
+        .indexes
+        .iter()
+        .find(|i| i.name == "another_idx")
+        .unwrap();
+    assert_eq!(another_idx.entries.len(), 99);
+}
+
+#[test]
+fn test_inspect_rows() {
+    let mut file = File::open("tests/data/expected/test_persist.wt").unwrap();
+    let rows = TestPersistWorkTable::read_rows(&mut file).unwrap();
+
+    assert_eq!(rows, get_test_wt().select_all().execute().unwrap());
+}
+
+#[test]
+fn test_inspect_without_table_type() {
+    let mut file = File::open("tests/data/expected/test_persist.wt").unwrap();
+    let report =
+        SpaceReport::read::<{ TESTPERSIST_PAGE_SIZE }, { TESTPERSIST_INNER_SIZE }>(&mut file)
+            .unwrap();
+
+    assert!(report.problems.is_empty(), "{:?}", report.problems);
+    assert_eq!(report.pages.len(), 4);
+    assert!(report.info.is_none());
+    assert!(report.indexes.is_empty());
+}
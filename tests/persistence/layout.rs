@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use worktable::prelude::*;
+
+mod declared {
+    use worktable::prelude::*;
+    use worktable::worktable;
+
+    worktable! (
+        name: TestLayout,
+        persist: true,
+        columns: {
+            id: u64 primary_key autoincrement,
+            first: u32,
+            second: u32,
+        },
+    );
+}
+
+mod reordered {
+    use worktable::prelude::*;
+    use worktable::worktable;
+
+    worktable! (
+        name: TestLayout,
+        persist: true,
+        columns: {
+            id: u64 primary_key autoincrement,
+            second: u32,
+            first: u32,
+        },
+    );
+}
+
+fn get_manager() -> Arc<DatabaseManager> {
+    let dir = std::env::temp_dir()
+        .join(format!("worktable_layout_{}", uuid::Uuid::new_v4()))
+        .to_string_lossy()
+        .to_string();
+
+    Arc::new(DatabaseManager::new(dir.clone(), dir))
+}
+
+#[test]
+fn changed_column_order_is_detected() {
+    let manager = get_manager();
+    let table = declared::TestLayoutWorkTable::load_from_file(manager.clone()).unwrap();
+    for i in 0..10 {
+        let row = declared::TestLayoutRow {
+            id: table.get_next_pk().into(),
+            first: i,
+            second: i * 2,
+        };
+        table.insert(row).unwrap();
+    }
+    table.persist().unwrap();
+    drop(table);
+
+    let error = reordered::TestLayoutWorkTable::load_from_file(manager.clone()).unwrap_err();
+    assert!(error.to_string().contains("has columns"));
+    let path = format!("{}/test_layout.wt", manager.database_files_dir);
+    assert!(std::path::Path::new(path.as_str()).exists());
+
+    let table = declared::TestLayoutWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap().len(), 10);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
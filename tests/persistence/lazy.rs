@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: TestLazy,
+    persist: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        another: u64,
+        exchange: String,
+    },
+    indexes: {
+        another_idx: another unique,
+        exchange_idx: exchange,
+    },
+);
+
+const CACHE_PAGES: usize = 2;
+
+fn get_manager() -> Arc<DatabaseManager> {
+    let dir = std::env::temp_dir()
+        .join(format!("worktable_lazy_{}", uuid::Uuid::new_v4()))
+        .to_string_lossy()
+        .to_string();
+
+    Arc::new(DatabaseManager::new(dir.clone(), dir))
+}
+
+fn insert_rows(table: &TestLazyWorkTable, range: std::ops::Range<u64>) {
+    for i in range {
+        let row = TestLazyRow {
+            id: table.get_next_pk().into(),
+            another: i,
+            exchange: format!("exchange_{}", i % 10),
+        };
+        table.insert(row).unwrap();
+    }
+}
+
+#[test]
+fn lazy_table_has_same_rows() {
+    let manager = get_manager();
+    let table = TestLazyWorkTable::load_from_file(manager.clone()).unwrap();
+    insert_rows(&table, 0..3000);
+    table.persist().unwrap();
+    let expected = table.select_all().execute().unwrap();
+    drop(table);
+
+    let table = TestLazyWorkTable::load_from_file_lazy(manager.clone(), CACHE_PAGES).unwrap();
+    assert!(table.0.data.is_lazy());
+    assert_eq!(table.0.data.resident_pages(), 0);
+    assert_eq!(table.select_all().execute().unwrap(), expected);
+    assert!(table.0.data.resident_pages() <= CACHE_PAGES);
+    assert_eq!(table.select_by_another(1500).unwrap().another, 1500);
+    assert_eq!(
+        table
+            .select_by_exchange("exchange_1".to_string())
+            .unwrap()
+            .execute()
+            .len(),
+        300
+    );
+    assert!(table.0.data.resident_pages() <= CACHE_PAGES);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[tokio::test]
+async fn lazy_table_changes_are_persisted() {
+    let manager = get_manager();
+    let table = TestLazyWorkTable::load_from_file(manager.clone()).unwrap();
+    insert_rows(&table, 0..3000);
+    table.persist().unwrap();
+    drop(table);
+
+    let table = TestLazyWorkTable::load_from_file_lazy(manager.clone(), CACHE_PAGES).unwrap();
+    for i in (0..3000).step_by(100) {
+        let mut row = table.select_by_another(i).unwrap();
+        row.exchange = "changed".to_string();
+        table.update(row).await.unwrap();
+    }
+    for i in (1..3000).step_by(100) {
+        let row = table.select_by_another(i).unwrap();
+        table.delete(row.id.into()).await.unwrap();
+    }
+    insert_rows(&table, 3000..3500);
+    table.persist().unwrap();
+    // Changed pages can be evicted after they are persisted.
+    table.select_all().execute().unwrap();
+    assert!(table.0.data.resident_pages() <= CACHE_PAGES);
+    let expected = table.select_all().execute().unwrap();
+
+    let loaded = TestLazyWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(loaded.select_all().execute().unwrap(), expected);
+    drop(loaded);
+
+    insert_rows(&table, 3500..3600);
+    table.persist().unwrap();
+    let expected = table.select_all().execute().unwrap();
+    drop(table);
+
+    let table = TestLazyWorkTable::load_from_file_lazy(manager.clone(), CACHE_PAGES).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), expected);
+    assert_eq!(
+        table
+            .select_by_exchange("changed".to_string())
+            .unwrap()
+            .execute()
+            .len(),
+        30
+    );
+    assert_eq!(table.select_by_another(1), None);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[test]
+fn lazy_table_without_file_is_empty() {
+    let manager = get_manager();
+    let table = TestLazyWorkTable::load_from_file_lazy(manager.clone(), CACHE_PAGES).unwrap();
+    assert!(!table.0.data.is_lazy());
+    assert!(table.select_all().execute().unwrap().is_empty());
+    insert_rows(&table, 0..10);
+    table.persist().unwrap();
+    drop(table);
+
+    let table = TestLazyWorkTable::load_from_file_lazy(manager.clone(), CACHE_PAGES).unwrap();
+    assert_eq!(table.select_all().execute().unwrap().len(), 10);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+mod v0 {
+    use worktable::prelude::*;
+    use worktable::worktable;
+
+    worktable! (
+        name: TestMigration,
+        persist: true,
+        columns: {
+            id: u64 primary_key autoincrement,
+            another: u32,
+            note: u32 optional,
+            legacy: String,
+        },
+        indexes: {
+            another_idx: another unique,
+        },
+    );
+}
+
+worktable! (
+    name: TestMigration,
+    persist: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        other: u64,
+        value: u64,
+        note: u64 optional,
+    },
+    indexes: {
+        other_idx: other unique,
+    },
+    migrations: {
+        1: {
+            add value: u64 = row.id * 10,
+            rename another -> other,
+        },
+        2: {
+            widen other: u32 -> u64,
+            widen note: u32 optional -> u64 optional,
+            drop legacy: String,
+        },
+    },
+);
+
+fn get_manager() -> Arc<DatabaseManager> {
+    let dir = std::env::temp_dir()
+        .join(format!("worktable_migration_{}", uuid::Uuid::new_v4()))
+        .to_string_lossy()
+        .to_string();
+
+    Arc::new(DatabaseManager::new(dir.clone(), dir))
+}
+
+#[test]
+fn rows_are_migrated_on_load() {
+    let manager = get_manager();
+    let table = v0::TestMigrationWorkTable::load_from_file(manager.clone()).unwrap();
+    for i in 0..100 {
+        let row = v0::TestMigrationRow {
+            id: table.get_next_pk().into(),
+            another: i + 1000,
+            note: (i % 2 == 0).then_some(i),
+            legacy: format!("legacy_{}", i),
+        };
+        table.insert(row).unwrap();
+    }
+    table.persist().unwrap();
+    drop(table);
+
+    let table = TestMigrationWorkTable::load_from_file(manager.clone()).unwrap();
+    let rows = table.select_all().execute().unwrap();
+    assert_eq!(rows.len(), 100);
+    for (i, row) in rows.iter().enumerate() {
+        let expected = TestMigrationRow {
+            id: i as u64,
+            other: i as u64 + 1000,
+            value: i as u64 * 10,
+            note: (i % 2 == 0).then_some(i as u64),
+        };
+        assert_eq!(row, &expected);
+        assert_eq!(table.select_by_other(expected.other), Some(expected));
+    }
+    let pk: u64 = table.get_next_pk().into();
+    assert_eq!(pk, 100);
+
+    // Migrated table is written with the current schema.
+    table.persist().unwrap();
+    drop(table);
+    let table = TestMigrationWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), rows);
+    drop(table);
+
+    // File with the newer schema is not replaced by the previous one.
+    let error = v0::TestMigrationWorkTable::load_from_file(manager.clone()).unwrap_err();
+    assert!(error.to_string().contains("schema version 2"));
+    let path = format!("{}/test_migration.wt", manager.database_files_dir);
+    assert!(std::path::Path::new(path.as_str()).exists());
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
@@ -3,7 +3,16 @@ use std::sync::Arc;
 use worktable::prelude::*;
 use worktable::worktable;
 
+mod backup;
+mod checkpoint;
+mod checksums;
+mod incremental;
+mod inspect;
+mod layout;
+mod lazy;
+mod migration;
 mod read;
+mod wal;
 mod write;
 
 worktable! (
@@ -30,10 +39,10 @@ worktable! (
 pub const TEST_ROW_COUNT: usize = 100;
 
 pub fn get_empty_test_wt() -> TestPersistWorkTable {
-    let manager = Arc::new(DatabaseManager {
-        config_path: "tests/data".to_string(),
-        database_files_dir: "test/data".to_string(),
-    });
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "test/data".to_string(),
+    ));
 
     TestPersistWorkTable::new(manager)
 }
@@ -53,10 +62,10 @@ pub fn get_test_wt() -> TestPersistWorkTable {
 }
 
 pub fn get_test_wt_without_secondary_indexes() -> TestWithoutSecondaryIndexesWorkTable {
-    let manager = Arc::new(DatabaseManager {
-        config_path: "tests/data".to_string(),
-        database_files_dir: "test/data".to_string(),
-    });
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "test/data".to_string(),
+    ));
 
     let table = TestWithoutSecondaryIndexesWorkTable::new(manager);
 
@@ -89,10 +89,10 @@ fn test_data_parse() {
 
 #[test]
 fn test_space_parse() {
-    let manager = Arc::new(DatabaseManager {
-        config_path: "tests/data".to_string(),
-        database_files_dir: "tests/data/expected".to_string(),
-    });
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "tests/data/expected".to_string(),
+    ));
     let table = TestPersistWorkTable::load_from_file(manager).unwrap();
     let expected = get_test_wt();
 
@@ -104,10 +104,10 @@ fn test_space_parse() {
 
 #[test]
 fn test_space_parse_no_file() {
-    let manager = Arc::new(DatabaseManager {
-        config_path: "tests/data".to_string(),
-        database_files_dir: "tests/data/non-existent".to_string(),
-    });
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "tests/data/non-existent".to_string(),
+    ));
     let table = TestPersistWorkTable::load_from_file(manager).unwrap();
     let expected = get_empty_test_wt();
     assert_eq!(
@@ -118,10 +118,10 @@ fn test_space_parse_no_file() {
 
 #[test]
 fn test_space_insert_after_read() {
-    let manager = Arc::new(DatabaseManager {
-        config_path: "tests/data".to_string(),
-        database_files_dir: "tests/data/expected".to_string(),
-    });
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "tests/data/expected".to_string(),
+    ));
     let table = TestPersistWorkTable::load_from_file(manager).unwrap();
 
     let row = TestPersistRow {
@@ -140,10 +140,10 @@ fn test_space_insert_after_read() {
 
 #[tokio::test]
 async fn test_space_delete_after_read() {
-    let manager = Arc::new(DatabaseManager {
-        config_path: "tests/data".to_string(),
-        database_files_dir: "tests/data/expected".to_string(),
-    });
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "tests/data/expected".to_string(),
+    ));
     let table = TestPersistWorkTable::load_from_file(manager).unwrap();
 
     table
@@ -0,0 +1,243 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: TestWal,
+    persist: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        another: u64,
+        exchange: String,
+    },
+    indexes: {
+        another_idx: another unique,
+    },
+    queries: {
+        update: {
+            ExchangeByAnother(exchange) by another,
+        },
+    },
+    config: {
+        wal_sync: always,
+    }
+);
+
+fn get_manager(separate_config_path: bool) -> Arc<DatabaseManager> {
+    let dir = std::env::temp_dir()
+        .join(format!("worktable_wal_{}", uuid::Uuid::new_v4()))
+        .to_string_lossy()
+        .to_string();
+    let config_path = if separate_config_path {
+        format!("{}/config", dir)
+    } else {
+        dir.clone()
+    };
+
+    Arc::new(DatabaseManager::new(config_path, dir))
+}
+
+fn get_row(table: &TestWalWorkTable, another: u64) -> TestWalRow {
+    TestWalRow {
+        id: table.get_next_pk().into(),
+        another,
+        exchange: "test".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn changes_are_replayed_after_restart() {
+    let manager = get_manager(false);
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    let rows = (0..4)
+        .map(|i| {
+            let row = get_row(&table, i);
+            table.insert(row.clone()).unwrap();
+            row
+        })
+        .collect::<Vec<_>>();
+
+    let mut updated = rows[0].clone();
+    updated.exchange = "updated".to_string();
+    table.update(updated.clone()).await.unwrap();
+    table
+        .update_exchange_by_another(
+            ExchangeByAnotherQuery {
+                exchange: "by_another".to_string(),
+            },
+            1,
+        )
+        .await
+        .unwrap();
+    table.delete(rows[2].id.into()).await.unwrap();
+    table
+        .update_pk(rows[3].id.into(), 100u64.into())
+        .await
+        .unwrap();
+    let expected = table.select_all().execute().unwrap();
+    drop(table);
+
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), expected);
+    assert_eq!(table.select_by_another(1).unwrap().exchange, "by_another");
+    let row = get_row(&table, 10);
+    assert!(row.id > 100);
+    table.insert(row.clone()).unwrap();
+    drop(table);
+
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select(row.id.into()), Some(row));
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[test]
+fn persist_resets_log() {
+    let manager = get_manager(false);
+    let wal_path = format!("{}/test_wal.wal", manager.database_files_dir);
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    let first = get_row(&table, 1);
+    table.insert(first.clone()).unwrap();
+    assert_eq!(Wal::read(wal_path.as_str()).unwrap().len(), 1);
+
+    table.persist().unwrap();
+    assert!(Wal::read(wal_path.as_str()).unwrap().is_empty());
+    let second = get_row(&table, 2);
+    table.insert(second.clone()).unwrap();
+    drop(table);
+
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(
+        table.select_all().execute().unwrap(),
+        vec![first.clone(), second.clone()]
+    );
+
+    table.truncate(false).unwrap();
+    drop(table);
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert!(table.select_all().execute().unwrap().is_empty());
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[test]
+fn truncate_is_logged_in_order_with_concurrent_writes() {
+    let manager = get_manager(false);
+    let table = Arc::new(TestWalWorkTable::load_from_file(manager.clone()).unwrap());
+    let stop = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let writer = {
+        let table = table.clone();
+        let stop = stop.clone();
+        std::thread::spawn(move || {
+            let mut another = 0;
+            while !stop.load(std::sync::atomic::Ordering::Relaxed) {
+                table.insert(get_row(&table, another)).unwrap();
+                another += 1;
+            }
+        })
+    };
+    for _ in 0..10 {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        table.truncate(false).unwrap();
+    }
+    stop.store(true, std::sync::atomic::Ordering::Relaxed);
+    writer.join().unwrap();
+    let rows = table.select_all().execute().unwrap();
+    drop(table);
+
+    // Rows inserted before each truncation are logged before it.
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), rows);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[test]
+fn log_is_kept_if_persisted_to_other_dir() {
+    let manager = get_manager(true);
+    let wal_path = format!("{}/test_wal.wal", manager.database_files_dir);
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    let row = get_row(&table, 1);
+    table.insert(row.clone()).unwrap();
+
+    table.persist().unwrap();
+    assert_eq!(Wal::read(wal_path.as_str()).unwrap().len(), 1);
+    drop(table);
+
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), vec![row]);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[test]
+fn torn_write_is_ignored() {
+    let manager = get_manager(false);
+    let wal_path = format!("{}/test_wal.wal", manager.database_files_dir);
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    let rows = table
+        .insert_many(vec![get_row(&table, 1), get_row(&table, 2)])
+        .unwrap();
+    drop(table);
+
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(wal_path.as_str())
+        .unwrap();
+    file.write_all(&[64, 0, 0, 0, 1, 2, 3]).unwrap();
+    drop(file);
+
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    let loaded = table
+        .select_all()
+        .execute()
+        .unwrap()
+        .into_iter()
+        .map(|row| row.get_primary_key())
+        .collect::<Vec<_>>();
+    assert_eq!(loaded, rows);
+    let row = get_row(&table, 3);
+    table.insert(row.clone()).unwrap();
+    drop(table);
+
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap().len(), 3);
+    assert_eq!(table.select(row.id.into()), Some(row));
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
+
+#[test]
+fn previous_file_is_loaded_with_retired_log() {
+    let manager = get_manager(false);
+    let file_path = format!("{}/test_wal.wt", manager.database_files_dir);
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    let mut rows = vec![];
+    for i in 0..5 {
+        let row = get_row(&table, i);
+        table.insert(row.clone()).unwrap();
+        rows.push(row);
+        if i == 1 || i == 3 {
+            table.persist().unwrap();
+        }
+    }
+    drop(table);
+
+    // Crash between renames of the persisted file.
+    std::fs::remove_file(file_path.as_str()).unwrap();
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), rows);
+
+    let row = get_row(&table, 5);
+    table.insert(row.clone()).unwrap();
+    rows.push(row);
+    table.persist().unwrap();
+    drop(table);
+
+    let table = TestWalWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap(), rows);
+
+    std::fs::remove_dir_all(manager.database_files_dir.as_str()).unwrap();
+}
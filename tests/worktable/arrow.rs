@@ -0,0 +1,138 @@
+use rkyv::{Archive, Deserialize, Serialize};
+use worktable::arrow_array::types::Int32Type;
+use worktable::arrow_array::{Array, DictionaryArray, StringArray, UInt64Array};
+use worktable::arrow_schema::DataType;
+use worktable::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use worktable::prelude::*;
+use worktable::worktable;
+
+#[derive(
+    Archive, ArrowValue, Clone, Copy, Debug, Deserialize, Serialize, PartialEq, PartialOrd,
+)]
+#[rkyv(compare(PartialEq), derive(Debug))]
+pub enum Kind {
+    First,
+    Second,
+}
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        kind: Kind,
+        name: String optional,
+        group: u64,
+        value: f64,
+    },
+    indexes: {
+        group_idx: group,
+    },
+);
+
+fn get_test_wt(count: u64) -> TestWorkTable {
+    let table = TestWorkTable::default();
+    for i in 0..count {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            kind: if i % 2 == 0 {
+                Kind::First
+            } else {
+                Kind::Second
+            },
+            name: (i % 3 == 0).then(|| format!("name {}", i)),
+            group: i % 4,
+            value: i as f64 / 2.0,
+        };
+        table.insert(row).unwrap();
+    }
+    table
+}
+
+#[test]
+fn schema() {
+    let schema = arrow_schema::<TestRow>();
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|f| (f.name().as_str(), f.data_type().clone(), f.is_nullable()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fields,
+        vec![
+            ("id", DataType::UInt64, false),
+            (
+                "kind",
+                DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+                false
+            ),
+            ("name", DataType::Utf8, true),
+            ("group", DataType::UInt64, false),
+            ("value", DataType::Float64, false),
+        ]
+    );
+}
+
+#[test]
+fn record_batches() {
+    let table = get_test_wt(ARROW_BATCH_SIZE as u64 + 10);
+    let batches = table.to_record_batches().unwrap();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].num_rows(), ARROW_BATCH_SIZE);
+    assert_eq!(batches[1].num_rows(), 10);
+
+    let batch = &batches[0];
+    let ids = batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<UInt64Array>()
+        .unwrap();
+    assert_eq!(ids.value(0), 0);
+    assert_eq!(ids.value(5), 5);
+
+    let kinds = batch
+        .column(1)
+        .as_any()
+        .downcast_ref::<DictionaryArray<Int32Type>>()
+        .unwrap();
+    let values = kinds
+        .values()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(values.value(kinds.keys().value(1) as usize), "Second");
+
+    let names = batch
+        .column(2)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(names.value(0), "name 0");
+    assert!(names.is_null(1));
+}
+
+#[test]
+fn select_result_record_batch() {
+    let table = get_test_wt(20);
+    let batch = table.select_by_group(1).unwrap().to_record_batch().unwrap();
+    assert_eq!(batch.num_rows(), 5);
+}
+
+#[test]
+fn write_parquet() {
+    let table = get_test_wt(100);
+    let path =
+        std::env::temp_dir().join(format!("worktable_arrow_{}.parquet", uuid::Uuid::new_v4()));
+    table
+        .write_parquet(std::fs::File::create(&path).unwrap())
+        .unwrap();
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 100);
+    assert_eq!(batches[0].schema(), arrow_schema::<TestRow>());
+
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+        another: u64,
+        exchange: String
+    },
+    indexes: {
+        test_idx: test unique,
+        exchange_idx: exchange,
+    },
+    queries: {
+        update: {
+            AnotherByExchange(another) by exchange,
+            AnotherByTest(another) by test,
+        },
+        delete: {
+            ByTest() by test,
+        },
+        increment: {
+            Another(another) by id,
+        }
+    }
+);
+
+fn fill(table: &TestWorkTable) -> Vec<TestRow> {
+    (0..3)
+        .map(|i| {
+            let row = TestRow {
+                id: table.get_next_pk().into(),
+                test: i,
+                another: i as u64,
+                exchange: "test".to_string(),
+            };
+            table.insert(row.clone()).unwrap();
+            row
+        })
+        .collect()
+}
+
+#[test]
+fn update_and_delete_without_runtime() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let mut updated = rows[0].clone();
+    updated.another = 100;
+    table.update_blocking(updated.clone()).unwrap();
+    assert_eq!(table.select(rows[0].id.into()).unwrap(), updated);
+
+    table
+        .update_another_by_exchange_blocking(
+            AnotherByExchangeQuery { another: 200 },
+            "test".to_string(),
+        )
+        .unwrap();
+    for row in &rows {
+        assert_eq!(table.select(row.id.into()).unwrap().another, 200);
+    }
+    let res = table
+        .increment_another_blocking(AnotherQuery { another: 1 }, rows[1].id.into())
+        .unwrap();
+    assert_eq!(res.another, 201);
+
+    table.delete_by_test_blocking(0).unwrap();
+    assert!(table.select(rows[0].id.into()).is_none());
+    let deleted = table.delete_where_blocking(|row| row.test == 1).unwrap();
+    assert_eq!(deleted, 1);
+
+    let mut upserted = rows[2].clone();
+    upserted.exchange = "new".to_string();
+    table.upsert_blocking(upserted.clone()).unwrap();
+    assert_eq!(table.select(rows[2].id.into()).unwrap(), upserted);
+    table.delete_blocking(rows[2].id.into()).unwrap();
+    assert_eq!(table.select_all().execute().unwrap().len(), 0);
+}
+
+#[test]
+fn blocking_update_waits_for_lock() {
+    let table = Arc::new(TestWorkTable::default());
+    let rows = fill(&table);
+    let link = TableIndex::peek(&table.0.pk_map, &rows[0].get_primary_key()).unwrap();
+
+    let mut guard = LockGuard::new(&table.0.lock_map);
+    let id = guard.id();
+    unsafe {
+        table
+            .0
+            .data
+            .with_mut_ref(link, |archived| archived.lock = id.into())
+            .unwrap();
+    }
+    guard.on_release(link, || unsafe {
+        let _ = table
+            .0
+            .data
+            .with_mut_ref(link, |archived| archived.lock = 0u64.into());
+    });
+
+    let mut updated = rows[0].clone();
+    updated.another = 100;
+    let handle = {
+        let table = table.clone();
+        let updated = updated.clone();
+        std::thread::spawn(move || table.update_blocking(updated))
+    };
+    while table.lock_metrics().waiting == 0 {
+        std::thread::yield_now();
+    }
+    assert_eq!(table.select(rows[0].id.into()).unwrap(), rows[0]);
+
+    drop(guard);
+    handle.join().unwrap().unwrap();
+    assert_eq!(table.select(rows[0].id.into()).unwrap(), updated);
+}
+
+#[test]
+fn blocking_lock_timeout() {
+    let mut table = TestWorkTable::default();
+    table.set_lock_timeout(Some(Duration::from_millis(20)));
+    let rows = fill(&table);
+    let link = TableIndex::peek(&table.0.pk_map, &rows[0].get_primary_key()).unwrap();
+
+    let guard = LockGuard::new(&table.0.lock_map);
+    let id = guard.id();
+    unsafe {
+        table
+            .0
+            .data
+            .with_mut_ref(link, |archived| archived.lock = id.into())
+            .unwrap();
+    }
+
+    let res = table.delete_blocking(rows[0].id.into());
+    assert!(matches!(res, Err(WorkTableError::LockTimeout)));
+    assert_eq!(table.lock_metrics().timeouts, 1);
+}
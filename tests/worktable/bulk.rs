@@ -0,0 +1,131 @@
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+        another: u64,
+        exchange: String
+    },
+    indexes: {
+        test_idx: test unique,
+        exchnage_idx: exchange,
+    }
+);
+
+fn rows(table: &TestWorkTable, count: i64) -> Vec<TestRow> {
+    (0..count)
+        .map(|i| TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64,
+            exchange: format!("exchange_{}", i % 3),
+        })
+        .collect()
+}
+
+#[test]
+fn insert_many() {
+    let table = TestWorkTable::default();
+    let rows = rows(&table, 1000);
+
+    let pks = table.insert_many(rows.clone()).unwrap();
+    assert_eq!(pks.len(), rows.len());
+    for (pk, row) in pks.into_iter().zip(rows) {
+        assert_eq!(table.select(pk).unwrap(), row);
+        assert_eq!(table.select_by_test(row.test).unwrap(), row);
+    }
+    assert_eq!(
+        table
+            .select_by_exchange("exchange_1".to_string())
+            .unwrap()
+            .execute()
+            .len(),
+        333
+    );
+}
+
+#[test]
+fn insert_many_duplicate_pk() {
+    let table = TestWorkTable::default();
+    let mut rows = rows(&table, 10);
+    rows[5].id = rows[2].id;
+    rows[5].test = 100;
+
+    assert!(matches!(
+        table.insert_many(rows),
+        Err(WorkTableError::AlreadyExists)
+    ));
+    assert_eq!(table.select_all().execute().unwrap().len(), 0);
+}
+
+#[test]
+fn insert_many_duplicate_unique_index() {
+    let table = TestWorkTable::default();
+    let mut rows = rows(&table, 10);
+    let existing = rows.pop().unwrap();
+    table.insert(existing.clone()).unwrap();
+    rows[7].test = existing.test;
+
+    assert!(matches!(
+        table.insert_many(rows.clone()),
+        Err(WorkTableError::AlreadyExists)
+    ));
+    assert_eq!(
+        table.select_all().execute().unwrap(),
+        vec![existing.clone()]
+    );
+    assert_eq!(table.select_by_test(existing.test).unwrap(), existing);
+    assert!(table.select_by_test(rows[0].test).is_none());
+}
+
+#[test]
+fn bulk_load() {
+    let table = TestWorkTable::default();
+    let mut rows = rows(&table, 10);
+    rows[5].test = rows[2].test;
+
+    let res = table.bulk_load(rows.clone());
+    assert_eq!(res.len(), rows.len());
+    assert_eq!(res.iter().filter(|r| r.is_ok()).count(), 9);
+    assert_eq!(table.select_all().execute().unwrap().len(), 9);
+    for (res, row) in res.into_iter().zip(rows) {
+        if let Ok(pk) = res {
+            assert_eq!(table.select(pk).unwrap(), row);
+        }
+    }
+}
+
+#[test]
+fn insert_many_extends_existing_index_keys() {
+    let table = TestWorkTable::default();
+    let mut rows = rows(&table, 10);
+    let existing = rows.pop().unwrap();
+    table.insert(existing.clone()).unwrap();
+
+    let mut duplicated = rows.clone();
+    duplicated[8].test = duplicated[1].test;
+    assert!(matches!(
+        table.insert_many(duplicated),
+        Err(WorkTableError::AlreadyExists)
+    ));
+    assert_eq!(
+        table
+            .select_by_exchange(existing.exchange.clone())
+            .unwrap()
+            .execute(),
+        vec![existing.clone()]
+    );
+
+    table.insert_many(rows).unwrap();
+    assert_eq!(
+        table
+            .select_by_exchange(existing.exchange.clone())
+            .unwrap()
+            .execute()
+            .len(),
+        4
+    );
+}
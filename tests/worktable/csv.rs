@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use rkyv::{Archive, Deserialize, Serialize};
+use worktable::prelude::*;
+use worktable::worktable;
+
+#[derive(Archive, Clone, Copy, Debug, Deserialize, Serialize, PartialEq, PartialOrd, CsvValue)]
+#[rkyv(compare(PartialEq), derive(Debug))]
+pub enum Kind {
+    First,
+    Second,
+}
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        kind: Kind,
+        name: String optional,
+        another: u64,
+    },
+    indexes: {
+        another_idx: another unique,
+    },
+);
+
+fn rows() -> Vec<TestRow> {
+    vec![
+        TestRow {
+            id: 1,
+            kind: Kind::First,
+            name: None,
+            another: 10,
+        },
+        TestRow {
+            id: 2,
+            kind: Kind::Second,
+            name: Some("a, \"b\"".to_string()),
+            another: 20,
+        },
+    ]
+}
+
+#[test]
+fn export() {
+    let table = TestWorkTable::default();
+    for row in rows() {
+        table.insert(row).unwrap();
+    }
+
+    let mut csv = vec![];
+    table.export_csv(&mut csv).unwrap();
+
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "id,kind,name,another\n1,First,,10\n2,Second,\"a, \"\"b\"\"\",20\n"
+    );
+}
+
+#[test]
+fn export_import() {
+    let table = TestWorkTable::default();
+    for row in rows() {
+        table.insert(row).unwrap();
+    }
+    let mut csv = vec![];
+    table.export_csv(&mut csv).unwrap();
+
+    let imported = TestWorkTable::default();
+    assert_eq!(imported.import_csv(csv.as_slice()).unwrap(), 2);
+    assert_eq!(
+        imported.select_all().execute().unwrap(),
+        table.select_all().execute().unwrap()
+    );
+    // Generator continues after the imported keys.
+    assert_eq!(imported.get_next_pk(), 3.into());
+}
+
+#[test]
+fn import_into_shared_table() {
+    let table = Arc::new(TestWorkTable::default());
+    let csv = "id,kind,name,another\n1,First,,10\n2,Second,,20\n";
+
+    let handle = {
+        let table = table.clone();
+        std::thread::spawn(move || table.import_csv(csv.as_bytes()).unwrap())
+    };
+    assert_eq!(handle.join().unwrap(), 2);
+    let pk = table
+        .insert(TestRow {
+            id: table.get_next_pk().into(),
+            kind: Kind::First,
+            name: None,
+            another: 30,
+        })
+        .unwrap();
+    assert_eq!(pk, 3.into());
+}
+
+#[test]
+fn import_columns_in_any_order() {
+    let table = TestWorkTable::default();
+    let csv = "another,name,kind,id\n10,,First,1\n20,\"a, \"\"b\"\"\",Second,2\n";
+
+    assert_eq!(table.import_csv(csv.as_bytes()).unwrap(), 2);
+    assert_eq!(table.select_all().execute().unwrap(), rows());
+}
+
+#[test]
+fn import_many_batches() {
+    let table = TestWorkTable::default();
+    let mut csv = "id,kind,name,another\n".to_string();
+    let count = IMPORT_BATCH_SIZE * 2 + 1;
+    for i in 0..count {
+        csv.push_str(format!("{},First,name {},{}\n", i, i, i).as_str());
+    }
+
+    assert_eq!(table.import_csv(csv.as_bytes()).unwrap(), count);
+    assert_eq!(table.select_all().execute().unwrap().len(), count);
+}
+
+#[test]
+fn import_wrong_header() {
+    let table = TestWorkTable::default();
+    let csv = "id,kind,another\n1,First,10\n";
+
+    let res = table.import_csv(csv.as_bytes());
+    assert!(matches!(res, Err(CsvError::Header { .. })));
+}
+
+#[test]
+fn import_invalid_value() {
+    let table = TestWorkTable::default();
+    let csv = "id,kind,name,another\n1,First,,10\n2,Third,,20\n";
+
+    match table.import_csv(csv.as_bytes()) {
+        Err(CsvError::Value { line, column, .. }) => {
+            assert_eq!(line, 3);
+            assert_eq!(column, "kind");
+        }
+        res => panic!("unexpected result {:?}", res),
+    }
+    assert!(table.select_all().execute().unwrap().is_empty());
+}
+
+#[test]
+fn import_duplicate() {
+    let table = TestWorkTable::default();
+    let csv = "id,kind,name,another\n1,First,,10\n2,First,,20\n1,Second,,30\n";
+
+    match table.import_csv(csv.as_bytes()) {
+        Err(CsvError::Insert { line, error }) => {
+            assert_eq!(line, 4);
+            assert!(matches!(error, WorkTableError::AlreadyExists));
+        }
+        res => panic!("unexpected result {:?}", res),
+    }
+    // Rows before the failed one stay in the table.
+    assert_eq!(table.select_all().execute().unwrap().len(), 2);
+}
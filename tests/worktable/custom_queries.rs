@@ -0,0 +1,157 @@
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+        another: u64,
+        exchange: String
+    },
+    queries: {
+        update: {
+            AnotherByTest(another) by test,
+            AnotherByTestAndExchange(another) by (test, exchange),
+        },
+        delete: {
+            ByAnother() by another,
+            ByTestAndExchange() by (test, exchange),
+        }
+    }
+);
+
+worktable! (
+    name: TestCompositePk,
+    columns: {
+        id: u64 primary_key,
+        test: i64 primary_key,
+        another: u64,
+    },
+    queries: {
+        update: {
+            AnotherByPk(another) by (id, test),
+        },
+        delete: {
+            ByPk() by (id, test),
+        }
+    }
+);
+
+fn fill(table: &TestWorkTable) -> Vec<TestRow> {
+    (0..4)
+        .map(|i| {
+            let row = TestRow {
+                id: table.get_next_pk().into(),
+                test: i % 2,
+                another: i as u64,
+                exchange: if i < 2 { "a" } else { "b" }.to_string(),
+            };
+            table.insert(row.clone()).unwrap();
+            row
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn update_by_non_indexed() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    table
+        .update_another_by_test(AnotherByTestQuery { another: 10 }, 1)
+        .await
+        .unwrap();
+    for row in rows {
+        let selected = table.select(row.id.into()).unwrap();
+        if row.test == 1 {
+            assert_eq!(selected.another, 10);
+        } else {
+            assert_eq!(selected.another, row.another);
+        }
+    }
+
+    assert!(matches!(
+        table
+            .update_another_by_test(AnotherByTestQuery { another: 10 }, 5)
+            .await,
+        Err(WorkTableError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn update_by_multiple_columns() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let res = table
+        .update_another_by_test_and_exchange_returning(
+            AnotherByTestAndExchangeQuery { another: 10 },
+            (1, "b".to_string()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].0, rows[3]);
+    assert_eq!(res[0].1.another, 10);
+    assert_eq!(table.select(rows[1].id.into()).unwrap(), rows[1]);
+}
+
+#[tokio::test]
+async fn delete_by_non_indexed() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let deleted = table.delete_by_another_returning(2).await.unwrap();
+    assert_eq!(deleted, vec![rows[2].clone()]);
+    assert_eq!(table.select_all().execute().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn delete_by_multiple_columns() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let count = table
+        .delete_by_test_and_exchange_count((0, "a".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    assert!(table.select(rows[0].id.into()).is_none());
+    assert_eq!(table.select_all().execute().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn update_by_composite_pk() {
+    let table = TestCompositePkWorkTable::default();
+    let row = TestCompositePkRow {
+        id: 1,
+        test: 2,
+        another: 3,
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    table
+        .update_another_by_pk(AnotherByPkQuery { another: 10 }, pk.clone())
+        .await
+        .unwrap();
+    assert_eq!(table.select(pk).unwrap().another, 10);
+}
+
+#[tokio::test]
+async fn delete_by_composite_pk() {
+    let table = TestCompositePkWorkTable::default();
+    let row = TestCompositePkRow {
+        id: 1,
+        test: 2,
+        another: 3,
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    assert_eq!(
+        table.delete_by_pk_returning(pk.clone()).await.unwrap(),
+        vec![row]
+    );
+    assert!(table.select(pk.clone()).is_none());
+    assert_eq!(table.delete_by_pk_count(pk).await.unwrap(), 0);
+}
@@ -0,0 +1,120 @@
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+        bids_qty: u64,
+        price: f64,
+    },
+    indexes: {
+        test_idx: test unique,
+    },
+    queries: {
+        increment: {
+            Qty(bids_qty) by id,
+            QtyAndPrice(bids_qty, price) by test,
+        }
+    }
+);
+
+#[tokio::test]
+async fn increment() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        bids_qty: 10,
+        price: 1.0,
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    let res = table
+        .increment_qty(QtyQuery { bids_qty: 5 }, pk.clone())
+        .await
+        .unwrap();
+    assert_eq!(res.bids_qty, 15);
+
+    let res = table
+        .decrement_qty(QtyQuery { bids_qty: 3 }, pk.clone())
+        .await
+        .unwrap();
+    assert_eq!(res.bids_qty, 12);
+
+    let selected_row = table.select(pk).unwrap();
+    assert_eq!(selected_row.bids_qty, 12);
+    assert_eq!(selected_row.price, 1.0);
+}
+
+#[tokio::test]
+async fn min_max_by_unique() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        bids_qty: 10,
+        price: 1.0,
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    let res = table
+        .max_qty_and_price(
+            QtyAndPriceQuery {
+                bids_qty: 5,
+                price: 2.0,
+            },
+            1,
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.bids_qty, 10);
+    assert_eq!(res.price, 2.0);
+
+    let res = table
+        .min_qty_and_price(
+            QtyAndPriceQuery {
+                bids_qty: 5,
+                price: 3.0,
+            },
+            1,
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.bids_qty, 5);
+    assert_eq!(res.price, 2.0);
+
+    let selected_row = table.select(pk).unwrap();
+    assert_eq!(selected_row.bids_qty, 5);
+    assert_eq!(selected_row.price, 2.0);
+}
+
+#[tokio::test]
+async fn overflow() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        bids_qty: 1,
+        price: 1.0,
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    let res = table
+        .decrement_qty(QtyQuery { bids_qty: 2 }, pk.clone())
+        .await;
+    assert!(res.is_err());
+
+    let selected_row = table.select(pk).unwrap();
+    assert_eq!(selected_row, row);
+}
+
+#[tokio::test]
+async fn not_found() {
+    let table = TestWorkTable::default();
+    let res = table
+        .increment_qty(QtyQuery { bids_qty: 2 }, 1.into())
+        .await;
+    assert!(res.is_err());
+}
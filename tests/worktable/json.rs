@@ -0,0 +1,118 @@
+use rkyv::{Archive, Deserialize, Serialize};
+use worktable::prelude::*;
+use worktable::worktable;
+
+#[derive(
+    Archive,
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    PartialOrd,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[rkyv(compare(PartialEq), derive(Debug))]
+pub enum Kind {
+    First,
+    Second,
+}
+
+worktable! (
+    name: Test,
+    serde: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        kind: Kind,
+        name: String optional,
+        another: u64,
+    },
+    indexes: {
+        another_idx: another unique,
+    },
+    queries: {
+        update: {
+            NameById(name) by id,
+        }
+    }
+);
+
+fn get_test_wt() -> TestWorkTable {
+    let table = TestWorkTable::default();
+    for i in 0..10 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            kind: if i % 2 == 0 {
+                Kind::First
+            } else {
+                Kind::Second
+            },
+            name: (i % 3 == 0).then(|| format!("name {}", i)),
+            another: i,
+        };
+        table.insert(row).unwrap();
+    }
+    table
+}
+
+#[test]
+fn serde_types() {
+    let row = TestRow {
+        id: 1,
+        kind: Kind::Second,
+        name: None,
+        another: 2,
+    };
+    let json = serde_json::to_string(&row).unwrap();
+    assert_eq!(
+        json,
+        "{\"id\":1,\"kind\":\"Second\",\"name\":null,\"another\":2}"
+    );
+    assert_eq!(serde_json::from_str::<TestRow>(&json).unwrap(), row);
+
+    let pk: TestPrimaryKey = serde_json::from_str("1").unwrap();
+    assert_eq!(pk, 1.into());
+    let query: NameByIdQuery = serde_json::from_str("{\"name\":\"a\"}").unwrap();
+    assert_eq!(query.name, Some("a".to_string()));
+}
+
+#[test]
+fn dump_restore() {
+    let table = get_test_wt();
+    let mut json = vec![];
+    table.to_json_lines(&mut json).unwrap();
+    assert_eq!(json.iter().filter(|b| **b == b'\n').count(), 10);
+
+    let restored = TestWorkTable::from_json_lines(json.as_slice()).unwrap();
+    assert_eq!(
+        restored.select_all().execute().unwrap(),
+        table.select_all().execute().unwrap()
+    );
+    // Generator continues after the restored keys.
+    assert_eq!(restored.get_next_pk(), 10.into());
+}
+
+#[test]
+fn restore_invalid_line() {
+    let json = "{\"id\":0,\"kind\":\"First\",\"name\":null,\"another\":0}\n{\"id\":1}\n";
+
+    match TestWorkTable::from_json_lines(json.as_bytes()) {
+        Err(JsonLinesError::Json { line, .. }) => assert_eq!(line, 2),
+        res => panic!("unexpected result {:?}", res.map(|_| ())),
+    }
+}
+
+#[test]
+fn restore_duplicate() {
+    let json = "{\"id\":0,\"kind\":\"First\",\"name\":null,\"another\":0}\n\n{\"id\":0,\"kind\":\"First\",\"name\":null,\"another\":1}\n";
+
+    match TestWorkTable::from_json_lines(json.as_bytes()) {
+        Err(JsonLinesError::Insert { line, error }) => {
+            assert_eq!(line, 3);
+            assert!(matches!(error, WorkTableError::AlreadyExists));
+        }
+        res => panic!("unexpected result {:?}", res.map(|_| ())),
+    }
+}
@@ -0,0 +1,291 @@
+use std::time::Duration;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+        another: u64,
+        exchange: String
+    },
+    indexes: {
+        test_idx: test unique,
+        exchange_idx: exchange,
+    },
+    queries: {
+        update: {
+            AnotherByExchange(another) by exchange,
+            AnotherByTest(another) by test,
+        },
+    },
+    config: {
+        lock_timeout_ms: 50
+    }
+);
+
+fn fill(table: &TestWorkTable) -> Vec<TestRow> {
+    (0..2)
+        .map(|i| {
+            let row = TestRow {
+                id: table.get_next_pk().into(),
+                test: i,
+                another: i as u64,
+                exchange: "test".to_string(),
+            };
+            table.insert(row.clone()).unwrap();
+            row
+        })
+        .collect()
+}
+
+fn link(table: &TestWorkTable, row: &TestRow) -> Link {
+    TableIndex::peek(&table.0.pk_map, &row.get_primary_key()).unwrap()
+}
+
+/// Locks row like some other operation does, lock is held until returned
+/// guard is dropped.
+fn hold_row_lock<'a>(table: &'a TestWorkTable, row: &TestRow) -> LockGuard<'a> {
+    let mut guard = LockGuard::new(&table.0.lock_map);
+    let id = guard.id();
+    let link = link(table, row);
+    unsafe {
+        table
+            .0
+            .data
+            .with_mut_ref(link, |archived| archived.lock = id.into())
+            .unwrap();
+    }
+    guard.on_release(link, move || unsafe {
+        let _ = table
+            .0
+            .data
+            .with_mut_ref(link, |archived| archived.lock = 0u64.into());
+    });
+    guard
+}
+
+#[tokio::test]
+async fn update_lock_timeout() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+    let guard = hold_row_lock(&table, &rows[0]);
+
+    let mut updated = rows[0].clone();
+    updated.another = 100;
+    let res = table.update(updated.clone()).await;
+    assert!(matches!(res, Err(WorkTableError::LockTimeout)));
+    let res = table
+        .update_another_by_test(AnotherByTestQuery { another: 100 }, 0)
+        .await;
+    assert!(matches!(res, Err(WorkTableError::LockTimeout)));
+    assert_eq!(table.select(rows[0].id.into()).unwrap(), rows[0]);
+
+    drop(guard);
+    table.update(updated.clone()).await.unwrap();
+    assert_eq!(table.select(rows[0].id.into()).unwrap(), updated);
+}
+
+#[tokio::test]
+async fn delete_lock_timeout() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+    let guard = hold_row_lock(&table, &rows[1]);
+
+    let res = table.delete(rows[1].id.into()).await;
+    assert!(matches!(res, Err(WorkTableError::LockTimeout)));
+    assert!(table.select(rows[1].id.into()).is_some());
+
+    drop(guard);
+    table.delete(rows[1].id.into()).await.unwrap();
+    assert!(table.select(rows[1].id.into()).is_none());
+}
+
+#[tokio::test]
+async fn wait_without_timeout() {
+    let mut table = TestWorkTable::default();
+    table.set_lock_timeout(None);
+    let rows = fill(&table);
+    let guard = hold_row_lock(&table, &rows[0]);
+
+    let mut updated = rows[0].clone();
+    updated.another = 100;
+    let (res, _) = tokio::join!(table.update(updated.clone()), async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(guard)
+    });
+    res.unwrap();
+    assert_eq!(table.select(rows[0].id.into()).unwrap(), updated);
+}
+
+#[tokio::test]
+async fn cancelled_update_releases_locks() {
+    let mut table = TestWorkTable::default();
+    table.set_lock_timeout(None);
+    let rows = fill(&table);
+    let guard = hold_row_lock(&table, &rows[1]);
+
+    // First row is locked by the update, then it waits for the second one
+    // until it is cancelled.
+    let res = tokio::time::timeout(
+        Duration::from_millis(50),
+        table.update_another_by_exchange(
+            AnotherByExchangeQuery { another: 100 },
+            "test".to_string(),
+        ),
+    )
+    .await;
+    assert!(res.is_err());
+    let lock = table
+        .0
+        .data
+        .with_ref(link(&table, &rows[0]), |archived| {
+            archived.check_another_by_exchange_lock()
+        })
+        .unwrap();
+    assert_eq!(lock, None);
+
+    drop(guard);
+    table
+        .update_another_by_exchange(AnotherByExchangeQuery { another: 100 }, "test".to_string())
+        .await
+        .unwrap();
+    for row in rows {
+        assert_eq!(table.select(row.id.into()).unwrap().another, 100);
+    }
+}
+
+#[tokio::test]
+async fn lock_wait_metrics() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+    assert_eq!(table.lock_metrics(), LockMetricsSnapshot::default());
+
+    let guard = hold_row_lock(&table, &rows[0]);
+    let mut updated = rows[0].clone();
+    updated.another = 100;
+    let res = table.update(updated.clone()).await;
+    assert!(matches!(res, Err(WorkTableError::LockTimeout)));
+    drop(guard);
+    table.update(updated).await.unwrap();
+
+    let metrics = table.lock_metrics();
+    assert_eq!(metrics.waits, 1);
+    assert_eq!(metrics.timeouts, 1);
+    assert_eq!(metrics.waiting, 0);
+    assert!(metrics.max_wait_time >= Duration::from_millis(50));
+    assert_eq!(metrics.total_wait_time, metrics.max_wait_time);
+}
+
+#[tokio::test]
+async fn inspect_held_locks() {
+    let mut table = TestWorkTable::default();
+    table.set_lock_timeout(None);
+    let rows = fill(&table);
+    assert!(table.held_locks().is_empty());
+
+    let guard = hold_row_lock(&table, &rows[0]);
+    let id = guard.id();
+    assert_ne!(id, 0);
+    let locks = table.held_locks();
+    assert_eq!(locks.len(), 1);
+    assert_eq!(locks[0].id, id);
+    assert_eq!(locks[0].waiters, 0);
+
+    let mut updated = rows[0].clone();
+    updated.another = 100;
+    let (res, _) = tokio::join!(table.update(updated), async {
+        while table.held_locks().iter().all(|l| l.waiters == 0) {
+            tokio::task::yield_now().await;
+        }
+        let locks = table.held_locks();
+        assert_eq!(locks.len(), 2);
+        assert_eq!(locks.iter().find(|l| l.id == id).unwrap().waiters, 1);
+        drop(guard)
+    });
+    res.unwrap();
+    assert!(table.held_locks().is_empty());
+}
+
+#[tokio::test]
+async fn deadlock_is_detected() {
+    let mut table = TestWorkTable::default();
+    table.set_lock_timeout(None);
+    let rows = fill(&table);
+    let guard = hold_row_lock(&table, &rows[0]);
+    let id = guard.id();
+
+    let (res, _) = tokio::join!(
+        table.update_another_by_exchange(
+            AnotherByExchangeQuery { another: 100 },
+            "test".to_string()
+        ),
+        async {
+            let other = loop {
+                if let Some(l) = table
+                    .held_locks()
+                    .into_iter()
+                    .find(|l| l.waits_for == Some(id))
+                {
+                    break l.id;
+                }
+                tokio::task::yield_now().await;
+            };
+            // Operation that holds first row now waits for the update, that
+            // waits for it.
+            let res = table.0.wait_lock(id, other).await;
+            assert!(matches!(res, Err(WorkTableError::Deadlock)));
+            drop(guard)
+        }
+    );
+    res.unwrap();
+    assert_eq!(table.select(rows[0].id.into()).unwrap().another, 100);
+}
+
+#[test]
+fn woken_writers_take_row_one_by_one() {
+    let mut table = TestWorkTable::default();
+    table.set_lock_timeout(None);
+    let rows = fill(&table);
+    let guard = hold_row_lock(&table, &rows[0]);
+
+    let writers = 4;
+    let results = std::thread::scope(|s| {
+        let handles = (1..=writers)
+            .map(|i| {
+                let mut updated = rows[0].clone();
+                updated.another = 100 + i;
+                let table = &table;
+                s.spawn(move || table.update_returning_blocking(updated).unwrap())
+            })
+            .collect::<Vec<_>>();
+        while table.lock_metrics().waiting != writers {
+            std::thread::yield_now();
+        }
+        drop(guard);
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    // Each writer must see the row written by the previous one, so all old
+    // values are different and they chain from the initial value to the
+    // final one.
+    let mut old = results
+        .iter()
+        .map(|(old, _)| old.another)
+        .collect::<Vec<_>>();
+    let mut new = results
+        .iter()
+        .map(|(_, new)| new.another)
+        .collect::<Vec<_>>();
+    let last = table.select(rows[0].id.into()).unwrap().another;
+    old.push(last);
+    new.push(rows[0].another);
+    old.sort();
+    new.sort();
+    assert_eq!(old, new);
+}
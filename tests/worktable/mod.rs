@@ -1,9 +1,22 @@
 mod array;
+#[cfg(feature = "arrow")]
+mod arrow;
 mod base;
+mod blocking;
+mod bulk;
 mod config;
+mod csv;
 mod custom_pk;
+mod custom_queries;
+mod increment;
 mod index_type;
+mod json;
+mod lock_timeout;
 mod option;
+mod range_delete;
+mod returning;
+mod snapshot;
 mod tuple_primary_key;
+mod update_pk;
 mod uuid;
 mod with_enum;
@@ -0,0 +1,205 @@
+use std::time::Duration;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+        another: u64,
+        exchange: String
+    },
+    indexes: {
+        test_idx: test unique,
+        exchnage_idx: exchange,
+    },
+    queries: {
+        delete: {
+            ByExchange() by exchange,
+        },
+    }
+);
+
+fn fill(table: &TestWorkTable) -> Vec<TestRow> {
+    (0..10)
+        .map(|i| {
+            let row = TestRow {
+                id: table.get_next_pk().into(),
+                test: i,
+                another: i as u64,
+                exchange: format!("exchange_{}", i % 3),
+            };
+            table.insert(row.clone()).unwrap();
+            row
+        })
+        .collect()
+}
+
+/// Locks row like some other operation does, lock is held until returned
+/// guard is dropped.
+fn hold_row_lock<'a>(table: &'a TestWorkTable, row: &TestRow) -> LockGuard<'a> {
+    let mut guard = LockGuard::new(&table.0.lock_map);
+    let id = guard.id();
+    let link = TableIndex::peek(&table.0.pk_map, &row.get_primary_key()).unwrap();
+    unsafe {
+        table
+            .0
+            .data
+            .with_mut_ref(link, |archived| archived.lock = id.into())
+            .unwrap();
+    }
+    guard.on_release(link, move || unsafe {
+        let _ = table
+            .0
+            .data
+            .with_mut_ref(link, |archived| archived.lock = 0u64.into());
+    });
+    guard
+}
+
+#[tokio::test]
+async fn delete_range_by_pk() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let deleted = table.delete_range_by_id(2..5).await.unwrap();
+    assert_eq!(deleted, 3);
+    for row in rows {
+        let selected = table.select(row.id.into());
+        if (2..5).contains(&row.id) {
+            assert!(selected.is_none());
+        } else {
+            assert_eq!(selected.unwrap(), row);
+        }
+    }
+}
+
+#[tokio::test]
+async fn delete_range_by_unique_index() {
+    let table = TestWorkTable::default();
+    fill(&table);
+
+    let deleted = table.delete_range_by_test(..=3).await.unwrap();
+    assert_eq!(deleted, 4);
+    assert_eq!(table.select_all().execute().unwrap().len(), 6);
+    assert!(table.select_by_test(3).is_none());
+    assert!(table.select_by_test(4).is_some());
+}
+
+#[tokio::test]
+async fn delete_range_by_non_unique_index() {
+    let table = TestWorkTable::default();
+    fill(&table);
+
+    let deleted = table
+        .delete_range_by_exchange("exchange_1".to_string()..)
+        .await
+        .unwrap();
+    assert_eq!(deleted, 6);
+    let rest = table.select_all().execute().unwrap();
+    assert_eq!(rest.len(), 4);
+    assert!(rest.iter().all(|r| r.exchange == "exchange_0"));
+}
+
+#[tokio::test]
+async fn delete_where() {
+    let table = TestWorkTable::default();
+    fill(&table);
+
+    let deleted = table
+        .delete_where(|row| row.another % 2 == 0)
+        .await
+        .unwrap();
+    assert_eq!(deleted, 5);
+    let rest = table.select_all().execute().unwrap();
+    assert_eq!(rest.len(), 5);
+    assert!(rest.iter().all(|r| r.another % 2 == 1));
+}
+
+#[tokio::test]
+async fn truncate() {
+    let table = TestWorkTable::default();
+    fill(&table);
+
+    table.truncate(false);
+    assert_eq!(table.select_all().execute().unwrap().len(), 0);
+    assert!(table.select_by_test(1).is_none());
+    assert_eq!(table.get_next_pk(), 10u64.into());
+
+    let rows = fill(&table);
+    assert_eq!(table.select_by_test(1).unwrap(), rows[1]);
+
+    table.truncate(true);
+    assert_eq!(table.select_all().execute().unwrap().len(), 0);
+    assert_eq!(table.get_next_pk(), 0u64.into());
+}
+
+#[tokio::test]
+async fn truncate_with_snapshot() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+    let snapshot = table.snapshot();
+
+    table.truncate(false);
+    assert!(table.select_all().execute().unwrap().is_empty());
+    assert_eq!(snapshot.select_all().execute().unwrap(), rows);
+    assert_eq!(snapshot.select(rows[3].id.into()), Some(rows[3].clone()));
+
+    drop(snapshot);
+    assert_eq!(table.0.versions.versions_count(), 0);
+    let rows = fill(&table);
+    assert_eq!(table.select_all().execute().unwrap(), rows);
+}
+
+#[tokio::test]
+async fn truncate_removes_row_locks() {
+    let mut table = TestWorkTable::default();
+    table.set_lock_timeout(Some(Duration::from_millis(20)));
+    let rows = fill(&table);
+    let old_guard = hold_row_lock(&table, &rows[0]);
+
+    table.truncate(true);
+    assert!(table.held_locks().is_empty());
+
+    // New row gets the same primary key and `Link` as the removed one.
+    let rows = fill(&table);
+    let guard = hold_row_lock(&table, &rows[0]);
+    assert!(old_guard.is_cleared());
+    assert!(!guard.is_cleared());
+    // Guard of the removed lock doesn't unlock the new row.
+    drop(old_guard);
+    assert_eq!(table.held_locks().len(), 1);
+
+    let mut updated = rows[0].clone();
+    updated.another = 100;
+    let res = table.update(updated.clone()).await;
+    assert!(matches!(res, Err(WorkTableError::LockTimeout)));
+
+    drop(guard);
+    table.update(updated.clone()).await.unwrap();
+    assert_eq!(table.select(rows[0].id.into()).unwrap(), updated);
+}
+
+#[tokio::test]
+async fn truncate_during_custom_delete() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+    let guard = hold_row_lock(&table, &rows[3]);
+
+    // Delete waits for one of its rows while the table is truncated, so it's
+    // skipped with the rows that are not deleted yet.
+    let (deleted, _) = tokio::join!(
+        table.delete_by_exchange_count("exchange_0".to_string()),
+        async {
+            while table.lock_metrics().waiting != 1 {
+                tokio::task::yield_now().await;
+            }
+            table.truncate(false);
+            drop(guard)
+        }
+    );
+    assert!(deleted.unwrap() < 4);
+    assert!(table.select_all().execute().unwrap().is_empty());
+}
@@ -0,0 +1,168 @@
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+        another: u64,
+        exchange: String
+    },
+    indexes: {
+        test_idx: test unique,
+        exchnage_idx: exchange,
+    }
+    queries: {
+        update: {
+            AnotherByExchange(another) by exchange,
+            AnotherByTest(another) by test,
+            AnotherById(another) by id,
+        },
+        delete: {
+            ByAnother() by another,
+            ByExchange() by exchange,
+            ByTest() by test,
+        }
+    }
+);
+
+fn fill(table: &TestWorkTable) -> Vec<TestRow> {
+    (0..3)
+        .map(|i| {
+            let row = TestRow {
+                id: table.get_next_pk().into(),
+                test: i,
+                another: i as u64,
+                exchange: "test".to_string(),
+            };
+            table.insert(row.clone()).unwrap();
+            row
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn update_returning() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+    let mut updated = rows[0].clone();
+    updated.another = 100;
+    updated.exchange = "new".to_string();
+
+    let (old, new) = table.update_returning(updated.clone()).await.unwrap();
+    assert_eq!(old, rows[0]);
+    assert_eq!(new, updated);
+    assert_eq!(table.select(updated.id.into()).unwrap(), updated);
+}
+
+#[tokio::test]
+async fn update_by_pk_returning() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let (old, new) = table
+        .update_another_by_id_returning(AnotherByIdQuery { another: 7 }, rows[1].id.into())
+        .await
+        .unwrap();
+    assert_eq!(old, rows[1]);
+    assert_eq!(new.another, 7);
+    assert_eq!(new.exchange, rows[1].exchange);
+}
+
+#[tokio::test]
+async fn update_by_unique_returning() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let (old, new) = table
+        .update_another_by_test_returning(AnotherByTestQuery { another: 7 }, 2)
+        .await
+        .unwrap();
+    assert_eq!(old, rows[2]);
+    assert_eq!(new.another, 7);
+}
+
+#[tokio::test]
+async fn update_by_non_unique_returning() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let mut res = table
+        .update_another_by_exchange_returning(
+            AnotherByExchangeQuery { another: 7 },
+            "test".to_string(),
+        )
+        .await
+        .unwrap();
+    res.sort_by_key(|(old, _)| old.id);
+    assert_eq!(res.len(), 3);
+    for ((old, new), row) in res.into_iter().zip(rows) {
+        assert_eq!(old, row);
+        assert_eq!(new.id, row.id);
+        assert_eq!(new.another, 7);
+    }
+}
+
+#[tokio::test]
+async fn delete_returning() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let deleted = table.delete_returning(rows[0].id.into()).await.unwrap();
+    assert_eq!(deleted, rows[0]);
+    assert!(table.select(rows[0].id.into()).is_none());
+    assert!(matches!(
+        table.delete_returning(rows[0].id.into()).await,
+        Err(WorkTableError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn delete_by_unique_returning() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let deleted = table.delete_by_test_returning(1).await.unwrap();
+    assert_eq!(deleted, vec![rows[1].clone()]);
+    assert!(table.select_by_test(1).is_none());
+}
+
+#[tokio::test]
+async fn delete_by_non_unique_returning() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let mut deleted = table
+        .delete_by_exchange_returning("test".to_string())
+        .await
+        .unwrap();
+    deleted.sort_by_key(|row| row.id);
+    assert_eq!(deleted, rows);
+    assert_eq!(table.select_all().execute().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn delete_by_field_returning() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let deleted = table.delete_by_another_returning(2).await.unwrap();
+    assert_eq!(deleted, vec![rows[2].clone()]);
+}
+
+#[tokio::test]
+async fn delete_count() {
+    let table = TestWorkTable::default();
+    fill(&table);
+
+    assert_eq!(table.delete_by_test_count(5).await.unwrap(), 0);
+    assert_eq!(table.delete_by_another_count(0).await.unwrap(), 1);
+    assert_eq!(
+        table
+            .delete_by_exchange_count("test".to_string())
+            .await
+            .unwrap(),
+        2
+    );
+}
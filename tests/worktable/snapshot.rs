@@ -0,0 +1,151 @@
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+        another: u64,
+        exchange: String
+    },
+    indexes: {
+        test_idx: test unique,
+        exchange_idx: exchange,
+    },
+    queries: {
+        update: {
+            AnotherByExchange(another) by exchange,
+        },
+        increment: {
+            Another(another) by id,
+        }
+    }
+);
+
+fn fill(table: &TestWorkTable) -> Vec<TestRow> {
+    (0..3)
+        .map(|i| {
+            let row = TestRow {
+                id: table.get_next_pk().into(),
+                test: i,
+                another: i as u64,
+                exchange: "test".to_string(),
+            };
+            table.insert(row.clone()).unwrap();
+            row
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn snapshot_is_not_affected_by_writes() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+    let snapshot = table.snapshot();
+
+    let mut updated = rows[0].clone();
+    updated.exchange = "new".to_string();
+    table.update(updated.clone()).await.unwrap();
+    table
+        .update_another_by_exchange(AnotherByExchangeQuery { another: 100 }, "test".to_string())
+        .await
+        .unwrap();
+    table.delete(rows[1].id.into()).await.unwrap();
+    let new_row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 10,
+        another: 10,
+        exchange: "test".to_string(),
+    };
+    table.insert(new_row.clone()).unwrap();
+
+    assert_eq!(snapshot.select(rows[0].id.into()), Some(rows[0].clone()));
+    assert_eq!(snapshot.select(rows[1].id.into()), Some(rows[1].clone()));
+    assert_eq!(snapshot.select(new_row.id.into()), None);
+    assert_eq!(snapshot.select_all().execute().unwrap(), rows);
+    assert_eq!(snapshot.select_by_test(1), Some(rows[1].clone()));
+    assert_eq!(snapshot.select_by_test(10), None);
+    assert_eq!(
+        snapshot
+            .select_by_exchange("test".to_string())
+            .unwrap()
+            .execute(),
+        rows
+    );
+    // Index has the new value of the row, but snapshot sees the old one.
+    assert!(snapshot.select_by_exchange("new".to_string()).is_err());
+    let seen = std::cell::RefCell::new(vec![]);
+    snapshot
+        .iter_with(|row| {
+            seen.borrow_mut().push(row);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(seen.into_inner(), rows);
+
+    let all = table.select_all().execute().unwrap();
+    assert_eq!(all.len(), 3);
+    assert_eq!(all[0].exchange, "new");
+    assert_eq!(all[1].another, 100);
+    assert_eq!(all[2], new_row);
+}
+
+#[tokio::test]
+async fn snapshots_see_own_versions() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    let first = table.snapshot();
+    table
+        .increment_another(AnotherQuery { another: 1 }, rows[0].id.into())
+        .await
+        .unwrap();
+    let second = table.snapshot();
+    table
+        .increment_another(AnotherQuery { another: 1 }, rows[0].id.into())
+        .await
+        .unwrap();
+
+    assert_eq!(first.select(rows[0].id.into()).unwrap().another, 0);
+    assert_eq!(second.select(rows[0].id.into()).unwrap().another, 1);
+    assert_eq!(table.select(rows[0].id.into()).unwrap().another, 2);
+    assert!(first.ts() <= second.ts());
+}
+
+#[tokio::test]
+async fn versions_are_removed_with_snapshots() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+
+    table.delete(rows[0].id.into()).await.unwrap();
+    assert_eq!(table.0.versions.versions_count(), 0);
+
+    let snapshot = table.snapshot();
+    table.delete(rows[1].id.into()).await.unwrap();
+    let mut updated = rows[2].clone();
+    updated.another = 100;
+    table.update(updated.clone()).await.unwrap();
+    table.update(updated).await.unwrap();
+    assert_eq!(table.0.versions.versions_count(), 2);
+    assert_eq!(snapshot.select_all().execute().unwrap(), rows[1..].to_vec());
+
+    drop(snapshot);
+    assert_eq!(table.0.versions.versions_count(), 0);
+}
+
+#[tokio::test]
+async fn update_pk_in_snapshot() {
+    let table = TestWorkTable::default();
+    let rows = fill(&table);
+    let snapshot = table.snapshot();
+
+    table
+        .update_pk(rows[0].id.into(), 100u64.into())
+        .await
+        .unwrap();
+
+    assert_eq!(snapshot.select(rows[0].id.into()), Some(rows[0].clone()));
+    assert_eq!(snapshot.select(100u64.into()), None);
+    assert_eq!(snapshot.select_all().execute().unwrap(), rows);
+}
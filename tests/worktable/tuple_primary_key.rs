@@ -24,3 +24,26 @@ fn insert() {
     assert_eq!(selected_row, row);
     assert!(table.select((1, 0).into()).is_none())
 }
+
+worktable! (
+    name: TestMixed,
+    columns: {
+        id: u64 primary_key,
+        name: String primary_key,
+        another: i64,
+    }
+);
+
+#[test]
+fn key_follows_declaration_order() {
+    let table = TestMixedWorkTable::default();
+    let row = TestMixedRow {
+        id: 1,
+        name: "a".to_string(),
+        another: 1,
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    assert_eq!(pk, (1, "a".to_string()).into());
+    assert_eq!(table.select((1, "a".to_string()).into()), Some(row));
+}
@@ -0,0 +1,407 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+        exchange: String
+    },
+    indexes: {
+        test_idx: test unique,
+        exchnage_idx: exchange,
+    }
+);
+
+worktable! (
+    name: TestStringPk,
+    columns: {
+        name: String primary_key,
+        test: i64,
+    },
+    indexes: {
+        test_idx: test unique,
+    }
+);
+
+worktable! (
+    name: TestKind,
+    columns: {
+        name: String primary_key,
+        kind: u64,
+        value: u64,
+    },
+    indexes: {
+        kind_idx: kind,
+    },
+    queries: {
+        update: {
+            ValueByKind(value) by kind,
+        },
+        delete: {
+            ByKind() by kind,
+            ByValue() by value,
+        },
+    }
+);
+
+#[tokio::test]
+async fn update_pk() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: 1,
+        test: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    table.update_pk(pk.clone(), 10u64.into()).await.unwrap();
+    assert!(table.select(pk).is_none());
+    let expected = TestRow { id: 10, ..row };
+    assert_eq!(table.select(10u64.into()).unwrap(), expected);
+    assert_eq!(table.select_by_test(1).unwrap(), expected);
+    assert_eq!(
+        table
+            .select_by_exchange("test".to_string())
+            .unwrap()
+            .execute(),
+        vec![expected]
+    );
+}
+
+#[tokio::test]
+async fn update_pk_collision() {
+    let table = TestWorkTable::default();
+    for i in 0..2 {
+        table
+            .insert(TestRow {
+                id: i,
+                test: i as i64,
+                exchange: "test".to_string(),
+            })
+            .unwrap();
+    }
+
+    assert!(matches!(
+        table.update_pk(0u64.into(), 1u64.into()).await,
+        Err(WorkTableError::AlreadyExists)
+    ));
+    assert_eq!(table.select(0u64.into()).unwrap().test, 0);
+    assert_eq!(table.select(1u64.into()).unwrap().test, 1);
+
+    assert!(matches!(
+        table.update_pk(5u64.into(), 6u64.into()).await,
+        Err(WorkTableError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn update_pk_with_size_change() {
+    let table = TestStringPkWorkTable::default();
+    let row = TestStringPkRow {
+        name: "a".to_string(),
+        test: 1,
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    let new_pk: TestStringPkPrimaryKey = "a much longer primary key".to_string().into();
+    table.update_pk(pk.clone(), new_pk.clone()).await.unwrap();
+    assert!(table.select(pk).is_none());
+    let expected = TestStringPkRow {
+        name: "a much longer primary key".to_string(),
+        test: 1,
+    };
+    assert_eq!(table.select(new_pk).unwrap(), expected);
+    assert_eq!(table.select_by_test(1).unwrap(), expected);
+}
+
+/// Secondary index that fails the next save or delete if it's requested.
+#[derive(Debug, Default)]
+struct FailingIndex {
+    links: Mutex<Vec<Link>>,
+    fail_save: AtomicBool,
+    fail_delete: AtomicBool,
+}
+
+impl TableSecondaryIndex<TestStringPkRow> for FailingIndex {
+    fn save_row(&self, _: TestStringPkRow, link: Link) -> Result<(), WorkTableError> {
+        if self.fail_save.swap(false, Ordering::Relaxed) {
+            return Err(WorkTableError::AlreadyExists);
+        }
+        self.links.lock().unwrap().push(link);
+        Ok(())
+    }
+
+    fn delete_row(&self, _: TestStringPkRow, link: Link) -> Result<(), WorkTableError> {
+        if self.fail_delete.swap(false, Ordering::Relaxed) {
+            return Err(WorkTableError::NotFound);
+        }
+        self.links.lock().unwrap().retain(|l| *l != link);
+        Ok(())
+    }
+
+    fn clear(&self) {
+        self.links.lock().unwrap().clear()
+    }
+}
+
+type FailingTable = WorkTable<
+    TestStringPkRow,
+    TestStringPkPrimaryKey,
+    TreeIndex<TestStringPkPrimaryKey, Link>,
+    FailingIndex,
+>;
+
+#[test]
+fn moved_row_is_restored_on_index_error() {
+    let table = FailingTable::default();
+    let row = TestStringPkRow {
+        name: "a".to_string(),
+        test: 1,
+    };
+    let old = table
+        .insert::<{ TestStringPkRow::ROW_SIZE }>(row.clone())
+        .unwrap();
+    let link = TableIndex::peek(&table.pk_map, &old).unwrap();
+    let new: TestStringPkPrimaryKey = "a much longer primary key".to_string().into();
+    let new_row = TestStringPkRow {
+        name: "a much longer primary key".to_string(),
+        test: 1,
+    };
+
+    for fail in [&table.indexes.fail_delete, &table.indexes.fail_save] {
+        fail.store(true, Ordering::Relaxed);
+        assert!(table
+            .rekey_row::<{ TestStringPkRow::ROW_SIZE }>(
+                &old,
+                new.clone(),
+                row.clone(),
+                new_row.clone(),
+                link,
+            )
+            .is_err());
+        assert_eq!(TableIndex::peek(&table.pk_map, &old), Some(link));
+        assert_eq!(TableIndex::peek(&table.pk_map, &new), None);
+        assert_eq!(table.select(old.clone()), Some(row.clone()));
+        assert_eq!(*table.indexes.links.lock().unwrap(), vec![link]);
+    }
+
+    let moved = table
+        .rekey_row::<{ TestStringPkRow::ROW_SIZE }>(&old, new.clone(), row, new_row.clone(), link)
+        .unwrap();
+    assert!(moved);
+    assert_eq!(TableIndex::peek(&table.pk_map, &old), None);
+    let new_link = TableIndex::peek(&table.pk_map, &new).unwrap();
+    assert_eq!(table.select(new), Some(new_row));
+    assert_eq!(*table.indexes.links.lock().unwrap(), vec![new_link]);
+}
+
+/// Locks row like some other operation does, lock is held until returned
+/// guard is dropped.
+fn hold_row_lock<'a>(
+    table: &'a TestStringPkWorkTable,
+    pk: &TestStringPkPrimaryKey,
+) -> LockGuard<'a> {
+    let mut guard = LockGuard::new(&table.0.lock_map);
+    let id = guard.id();
+    let link = TableIndex::peek(&table.0.pk_map, pk).unwrap();
+    unsafe {
+        table
+            .0
+            .data
+            .with_mut_ref(link, |archived| archived.lock = id.into())
+            .unwrap();
+    }
+    guard.on_release(link, move || unsafe {
+        let _ = table
+            .0
+            .data
+            .with_mut_ref(link, |archived| archived.lock = 0u64.into());
+    });
+    guard
+}
+
+#[tokio::test]
+async fn delete_waiting_for_moved_row() {
+    let table = TestStringPkWorkTable::default();
+    let pk = table
+        .insert(TestStringPkRow {
+            name: "a".to_string(),
+            test: 1,
+        })
+        .unwrap();
+    let new_pk: TestStringPkPrimaryKey = "a much longer primary key".to_string().into();
+    let guard = hold_row_lock(&table, &pk);
+
+    // Both operations wait for the row, `update_pk` takes it first and moves
+    // it, freeing its old `Link`.
+    let (moved, deleted, _) = tokio::join!(
+        table.update_pk(pk.clone(), new_pk.clone()),
+        table.delete(pk.clone()),
+        async {
+            while table.lock_metrics().waiting != 2 {
+                tokio::task::yield_now().await;
+            }
+            drop(guard)
+        }
+    );
+    moved.unwrap();
+    assert!(matches!(deleted, Err(WorkTableError::NotFound)));
+
+    let expected = TestStringPkRow {
+        name: "a much longer primary key".to_string(),
+        test: 1,
+    };
+    assert_eq!(table.select(new_pk.clone()).unwrap(), expected);
+    assert_eq!(table.select_by_test(1).unwrap(), expected);
+
+    // Old `Link` was freed once, so it's reused only by one of the new rows.
+    let rows = (2..4)
+        .map(|i| TestStringPkRow {
+            name: i.to_string(),
+            test: i,
+        })
+        .collect::<Vec<_>>();
+    for row in &rows {
+        table.insert(row.clone()).unwrap();
+    }
+    for row in rows {
+        assert_eq!(table.select(row.name.clone().into()).unwrap(), row);
+    }
+    assert_eq!(table.select(new_pk).unwrap(), expected);
+}
+
+#[tokio::test]
+async fn update_pk_waiting_for_deleted_row() {
+    let table = TestStringPkWorkTable::default();
+    let pk = table
+        .insert(TestStringPkRow {
+            name: "a".to_string(),
+            test: 1,
+        })
+        .unwrap();
+    let new_pk: TestStringPkPrimaryKey = "b".to_string().into();
+    let guard = hold_row_lock(&table, &pk);
+
+    let (deleted, moved, _) = tokio::join!(
+        table.delete(pk.clone()),
+        table.update_pk(pk.clone(), new_pk.clone()),
+        async {
+            while table.lock_metrics().waiting != 2 {
+                tokio::task::yield_now().await;
+            }
+            drop(guard)
+        }
+    );
+    deleted.unwrap();
+    assert!(matches!(moved, Err(WorkTableError::NotFound)));
+    assert!(table.select(pk).is_none());
+    assert!(table.select(new_pk).is_none());
+    assert_eq!(table.select_all().execute().unwrap().len(), 0);
+}
+
+/// Locks row like some other operation does, lock is held until returned
+/// guard is dropped.
+fn hold_kind_row_lock<'a>(table: &'a TestKindWorkTable, pk: &TestKindPrimaryKey) -> LockGuard<'a> {
+    let mut guard = LockGuard::new(&table.0.lock_map);
+    let id = guard.id();
+    let link = TableIndex::peek(&table.0.pk_map, pk).unwrap();
+    unsafe {
+        table
+            .0
+            .data
+            .with_mut_ref(link, |archived| archived.lock = id.into())
+            .unwrap();
+    }
+    guard.on_release(link, move || unsafe {
+        let _ = table
+            .0
+            .data
+            .with_mut_ref(link, |archived| archived.lock = 0u64.into());
+    });
+    guard
+}
+
+fn insert_kind_row(table: &TestKindWorkTable) -> TestKindPrimaryKey {
+    table
+        .insert(TestKindRow {
+            name: "a".to_string(),
+            kind: 1,
+            value: 0,
+        })
+        .unwrap()
+}
+
+#[tokio::test]
+async fn non_unique_update_waiting_for_moved_row() {
+    let table = TestKindWorkTable::default();
+    let pk = insert_kind_row(&table);
+    let new_pk: TestKindPrimaryKey = "a much longer primary key".to_string().into();
+    let guard = hold_kind_row_lock(&table, &pk);
+
+    // `update_pk` takes the row first and moves it, so the update finds only
+    // the freed `Link` when it takes the lock.
+    let (moved, updated, _) = tokio::join!(
+        table.update_pk(pk.clone(), new_pk.clone()),
+        table.update_value_by_kind(ValueByKindQuery { value: 10 }, 1),
+        async {
+            while table.lock_metrics().waiting != 2 {
+                tokio::task::yield_now().await;
+            }
+            drop(guard)
+        }
+    );
+    moved.unwrap();
+    assert!(matches!(updated, Err(WorkTableError::NotFound)));
+    assert_eq!(table.select(new_pk).unwrap().value, 0);
+}
+
+#[tokio::test]
+async fn custom_delete_waiting_for_moved_row() {
+    let table = TestKindWorkTable::default();
+    let pk = insert_kind_row(&table);
+    let new_pk: TestKindPrimaryKey = "b".to_string().into();
+    let guard = hold_kind_row_lock(&table, &pk);
+
+    // Row's primary key is changed while delete waits for it, so it's
+    // skipped instead of failing the delete.
+    let (moved, deleted, _) = tokio::join!(
+        table.update_pk(pk.clone(), new_pk.clone()),
+        table.delete_by_kind_count(1),
+        async {
+            while table.lock_metrics().waiting != 2 {
+                tokio::task::yield_now().await;
+            }
+            drop(guard)
+        }
+    );
+    moved.unwrap();
+    assert_eq!(deleted.unwrap(), 0);
+    assert!(table.select(new_pk).is_some());
+}
+
+#[tokio::test]
+async fn custom_delete_waiting_for_changed_row() {
+    let table = TestKindWorkTable::default();
+    let pk = insert_kind_row(&table);
+    let guard = hold_kind_row_lock(&table, &pk);
+
+    // Row doesn't match the delete's condition once it's locked.
+    let (updated, deleted, _) = tokio::join!(
+        table.update_value_by_kind(ValueByKindQuery { value: 10 }, 1),
+        table.delete_by_value_returning(0),
+        async {
+            while table.lock_metrics().waiting != 2 {
+                tokio::task::yield_now().await;
+            }
+            drop(guard)
+        }
+    );
+    updated.unwrap();
+    assert_eq!(deleted.unwrap(), vec![]);
+    assert_eq!(table.select(pk).unwrap().value, 10);
+}
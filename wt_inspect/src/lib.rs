@@ -0,0 +1,162 @@
+//! Inspector of the `.wt` files written by persisted `WorkTable`s.
+//!
+//! Files are described using only their page headers, as space info page
+//! can be parsed only with the table's type. Tables registered in the
+//! [`Inspector`] are inspected with their types, so their space info and
+//! index contents are shown and their rows can be dumped as JSON:
+//!
+//! ```ignore
+//! fn main() -> eyre::Result<()> {
+//!     wt_inspect::Inspector::default()
+//!         .register::<TestWorkTable>(|row| serde_json::json!({ "id": row.id, "name": row.name }))
+//!         .register_serde::<AnotherWorkTable>()
+//!         .run(std::env::args().skip(1))
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+
+use worktable::prelude::*;
+
+type RowsDump = Box<dyn Fn(&mut File) -> eyre::Result<Vec<serde_json::Value>>>;
+
+const USAGE: &str = "usage: wt-inspect <file.wt> [--rows]";
+
+/// Table registered in the [`Inspector`].
+struct RegisteredTable {
+    inspect: fn(&mut File) -> eyre::Result<SpaceReport>,
+    rows: RowsDump,
+}
+
+/// Inspects `.wt` files, using types of the registered tables for their
+/// files.
+#[derive(Default)]
+pub struct Inspector {
+    /// Registered tables by names of their files.
+    tables: HashMap<String, RegisteredTable>,
+}
+
+impl Inspector {
+    /// Registers table's type, so its files are inspected with it. Rows are
+    /// converted to JSON by `to_json`.
+    pub fn register<T>(self, to_json: impl Fn(&T::Row) -> serde_json::Value + 'static) -> Self
+    where
+        T: InspectSpace + 'static,
+    {
+        self.insert::<T>(Box::new(move |file| {
+            Ok(T::read_rows(file)?.iter().map(&to_json).collect())
+        }))
+    }
+
+    /// Registers table's type like [`register`], but rows are converted to
+    /// JSON by their `Serialize` implementation, which is derived for tables
+    /// declared with `serde: true`.
+    ///
+    /// [`register`]: Inspector::register
+    pub fn register_serde<T>(self) -> Self
+    where
+        T: InspectSpace + 'static,
+        T::Row: serde::Serialize,
+    {
+        self.insert::<T>(Box::new(|file| {
+            T::read_rows(file)?
+                .iter()
+                .map(|row| Ok(serde_json::to_value(row)?))
+                .collect()
+        }))
+    }
+
+    fn insert<T: InspectSpace>(mut self, rows: RowsDump) -> Self {
+        self.tables.insert(
+            T::FILE_NAME.to_string(),
+            RegisteredTable {
+                inspect: T::inspect,
+                rows,
+            },
+        );
+        self
+    }
+
+    /// Returns report of the file. Files of the not registered tables are
+    /// read with the default page size.
+    pub fn inspect(&self, path: impl AsRef<Path>) -> eyre::Result<SpaceReport> {
+        let path = path.as_ref();
+        let mut file = File::open(path)?;
+        match self.table(path) {
+            Some(table) => (table.inspect)(&mut file),
+            None => SpaceReport::read::<PAGE_SIZE, INNER_PAGE_SIZE>(&mut file),
+        }
+    }
+
+    /// Returns rows of the file converted to JSON. Fails if the file's table
+    /// is not registered.
+    pub fn rows(&self, path: impl AsRef<Path>) -> eyre::Result<Vec<serde_json::Value>> {
+        let path = path.as_ref();
+        let Some(table) = self.table(path) else {
+            eyre::bail!(
+                "table of the file {} is not registered, so its rows can't be read",
+                path.display()
+            )
+        };
+        (table.rows)(&mut File::open(path)?)
+    }
+
+    /// Runs inspector with the command line arguments: path to the file and
+    /// optional `--rows` flag that prints file's rows as JSON array instead
+    /// of the report. Fails if problems are found in the file.
+    pub fn run(&self, args: impl IntoIterator<Item = String>) -> eyre::Result<()> {
+        let mut path = None;
+        let mut rows = false;
+        for arg in args {
+            match arg.as_str() {
+                "--rows" => rows = true,
+                _ if path.is_none() => path = Some(arg),
+                _ => eyre::bail!(USAGE),
+            }
+        }
+        let Some(path) = path else { eyre::bail!(USAGE) };
+
+        if rows {
+            let rows = self.rows(&path)?;
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+            return Ok(());
+        }
+        let report = self.inspect(&path)?;
+        print!("{}", report);
+        if !report.problems.is_empty() {
+            eyre::bail!("{} problems found in {}", report.problems.len(), path)
+        }
+
+        Ok(())
+    }
+
+    fn table(&self, path: &Path) -> Option<&RegisteredTable> {
+        self.tables.get(table_file_name(path)?)
+    }
+}
+
+/// Returns name of the table's file without extension. Previous and invalid
+/// generations of the file have the same name.
+fn table_file_name(path: &Path) -> Option<&str> {
+    let file_name = path.file_name()?.to_str()?;
+    file_name.split_once(".wt").map(|(name, _)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::table_file_name;
+
+    #[test]
+    fn table_is_found_by_file_name() {
+        assert_eq!(table_file_name(Path::new("data/test.wt")), Some("test"));
+        assert_eq!(
+            table_file_name(Path::new("test_table.wt.prev")),
+            Some("test_table")
+        );
+        assert_eq!(table_file_name(Path::new("test.wal")), None);
+    }
+}
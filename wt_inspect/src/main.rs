@@ -0,0 +1,3 @@
+fn main() -> eyre::Result<()> {
+    wt_inspect::Inspector::default().run(std::env::args().skip(1))
+}